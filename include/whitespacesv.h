@@ -0,0 +1,61 @@
+/* C interface to the whitespacesv crate. Build the library with
+ * `cargo rustc --release --features ffi --crate-type cdylib`. */
+#ifndef WHITESPACESV_H
+#define WHITESPACESV_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define WSV_OK 0
+#define WSV_PARSE_ERROR 1
+#define WSV_NULL_ARGUMENT 2
+#define WSV_INVALID_UTF8 3
+
+#define WSV_ERROR_STRING_NOT_CLOSED 1
+#define WSV_ERROR_INVALID_DOUBLE_QUOTE_AFTER_VALUE 2
+#define WSV_ERROR_INVALID_CHARACTER_AFTER_STRING 3
+#define WSV_ERROR_INVALID_STRING_LINE_BREAK 4
+#define WSV_ERROR_TOO_MANY_COLUMNS 5
+#define WSV_ERROR_UNEXPECTED_LINE_BREAK 6
+#define WSV_ERROR_INVALID_UTF8 7
+#define WSV_ERROR_CELL_TOO_LARGE 8
+
+/* A null value ('-') has a NULL data pointer. Other values are len bytes
+ * of UTF-8 followed by a NUL terminator. */
+typedef struct {
+    const char *data;
+    size_t len;
+} WSVFfiValue;
+
+typedef struct {
+    const WSVFfiValue *values;
+    size_t len;
+} WSVFfiRow;
+
+typedef struct {
+    const WSVFfiRow *rows;
+    size_t len;
+} WSVFfiTable;
+
+/* Lines and columns are 1 based. */
+typedef struct {
+    int32_t error_type;
+    size_t line;
+    size_t column;
+    size_t byte_index;
+} WSVFfiError;
+
+int32_t wsv_parse(const uint8_t *source, size_t len, WSVFfiTable **out, WSVFfiError *error);
+void wsv_free(WSVFfiTable *table);
+char *wsv_write(const WSVFfiTable *table);
+void wsv_string_free(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
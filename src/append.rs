@@ -0,0 +1,206 @@
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::{write_packed_row, WriterOptions};
+
+/// Appends rows to the end of a WSV file (or any other writer), so a log
+/// kept in WSV can keep growing without being rewritten. Every row is
+/// written packed with the same quoting, null token, separator, line
+/// ending and encoding, and ends with its own line ending.
+pub struct WSVAppender<W: Write> {
+    writer: W,
+    options: WriterOptions,
+    start: AppendStart,
+    line: String,
+    /// line in the output encoding.
+    bytes: Vec<u8>,
+}
+
+/// What has to be written before the first row.
+enum AppendStart {
+    /// The output is empty, so it starts with the byte order mark.
+    Empty,
+    /// The output already ends with these bytes (the last two at most),
+    /// so a line feed is needed unless they are one.
+    After(Vec<u8>),
+    /// Something has been written already.
+    Started,
+}
+
+impl WSVAppender<File> {
+    /// Opens the file at the given path for appending, creating it if it
+    /// doesn't exist. If the file doesn't end with a line feed, one is
+    /// added before the first new row so it doesn't run into the last
+    /// existing row. A new or empty file gets the byte order mark of the
+    /// encoding first. The encoding should match the one the file was
+    /// written in.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+        let len = file.metadata()?.len();
+        let mut start = AppendStart::Empty;
+        if len != 0 {
+            let mut last_bytes = vec![0; len.min(2) as usize];
+            file.seek(SeekFrom::End(-(last_bytes.len() as i64)))?;
+            file.read_exact(&mut last_bytes)?;
+            start = AppendStart::After(last_bytes);
+        }
+        let mut appender = Self::new(file);
+        appender.start = start;
+        Ok(appender)
+    }
+}
+
+impl<W: Write> WSVAppender<W> {
+    /// Creates an appender that writes to the given writer, which should
+    /// be empty, since the first row starts with the byte order mark of
+    /// the encoding. UTF-8 has none, so a UTF-8 writer only has to be at
+    /// the start of a line.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            options: WriterOptions::default(),
+            start: AppendStart::Empty,
+            line: String::new(),
+            bytes: Vec::new(),
+        }
+    }
+
+    /// Sets the quoting, null token, separator, line ending and encoding
+    /// the rows are written with. Alignment is ignored, since rows are
+    /// written one at a time.
+    pub fn with_options(mut self, options: WriterOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Writes a row followed by a line ending.
+    pub fn append_row<S: AsRef<str>>(&mut self, cells: &[Option<S>]) -> Result<(), std::io::Error> {
+        self.line.clear();
+        write_packed_row(&mut self.line, cells, &self.options);
+        self.line.push_str(self.options.line_ending.as_str());
+        self.bytes.clear();
+        self.write_start();
+        self.options
+            .encoding
+            .encode_str(&self.line, &mut self.bytes);
+        self.writer.write_all(&self.bytes)
+    }
+
+    /// Adds whatever has to come before the first row to bytes.
+    fn write_start(&mut self) {
+        let encoding = self.options.encoding;
+        match std::mem::replace(&mut self.start, AppendStart::Started) {
+            AppendStart::Empty => self.bytes.extend_from_slice(encoding.bom()),
+            AppendStart::After(last_bytes) => {
+                let mut line_feed = Vec::new();
+                encoding.encode_str("\n", &mut line_feed);
+                if !last_bytes.ends_with(&line_feed) {
+                    self.bytes.extend_from_slice(&line_feed);
+                }
+            }
+            AppendStart::Started => {}
+        }
+    }
+
+    /// Writes each of the rows, in order.
+    pub fn append_rows<Row, S>(&mut self, rows: &[Row]) -> Result<(), std::io::Error>
+    where
+        Row: AsRef<[Option<S>]>,
+        S: AsRef<str>,
+    {
+        for row in rows {
+            self.append_row(row.as_ref())?;
+        }
+        Ok(())
+    }
+
+    /// Flushes the underlying writer. If no rows were appended to an
+    /// empty output, the byte order mark is written first, so even an
+    /// empty file is marked with its encoding.
+    pub fn flush(&mut self) -> Result<(), std::io::Error> {
+        if matches!(self.start, AppendStart::Empty) {
+            self.bytes.clear();
+            self.write_start();
+            self.writer.write_all(&self.bytes)?;
+        }
+        self.writer.flush()
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{parse, OutputEncoding, ValueQuoting, WSVAppender, WriterOptions};
+
+    #[test]
+    fn appends_to_existing_files() {
+        let path =
+            std::env::temp_dir().join(format!("whitespacesv_append_{}.wsv", std::process::id()));
+        fs::write(&path, "time level message\n1 INFO started").unwrap();
+
+        let mut appender = WSVAppender::open(&path).unwrap();
+        appender
+            .append_row(&[Some("2"), Some("WARN"), Some("disk \"sda\" full")])
+            .unwrap();
+        appender.append_row(&[Some("3"), None, Some("")]).unwrap();
+        drop(appender);
+
+        let mut appender = WSVAppender::open(&path)
+            .unwrap()
+            .with_options(WriterOptions::new().quoting(ValueQuoting::Always));
+        appender.append_rows(&[[Some("4"), Some("INFO")]]).unwrap();
+        drop(appender);
+
+        assert_eq!(
+            "time level message\n1 INFO started\n2 WARN \"disk \"\"sda\"\" full\"\n3 - \"\"\n\"4\" \"INFO\"\n",
+            fs::read_to_string(&path).unwrap()
+        );
+        assert_eq!(5, parse(&fs::read_to_string(&path).unwrap()).unwrap().len());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn appends_utf16() {
+        let path = std::env::temp_dir().join(format!(
+            "whitespacesv_append_utf16_{}.wsv",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        let options = WriterOptions::new().encoding(OutputEncoding::Utf16Le);
+        for row in [[Some("a"), Some("€")], [Some("b"), None]] {
+            let mut appender = WSVAppender::open(&path)
+                .unwrap()
+                .with_options(options.clone());
+            appender.append_row(&row).unwrap();
+        }
+        let bytes = fs::read(&path).unwrap();
+        assert_eq!([0xFF, 0xFE], bytes[..2]);
+        let units = bytes[2..]
+            .chunks(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+            .collect::<Vec<_>>();
+        assert_eq!("a €\nb -\n", String::from_utf16(&units).unwrap());
+
+        // Without a line feed at the end, one is added first.
+        fs::write(&path, [0xFF, 0xFE, b'x', 0]).unwrap();
+        let mut appender = WSVAppender::open(&path).unwrap().with_options(options);
+        appender.append_row(&[Some("y")]).unwrap();
+        drop(appender);
+        assert_eq!(
+            vec![0xFF, 0xFE, b'x', 0, b'\n', 0, b'y', 0, b'\n', 0],
+            fs::read(&path).unwrap()
+        );
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,349 @@
+//! The wsv command line tool. Every subcommand is a thin wrapper around
+//! the library, so the tool always behaves the same way the crate does.
+
+use std::borrow::Cow;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use whitespacesv::{
+    csv_to_wsv, from_json, markdown_to_wsv, parse, to_json, tsv_to_wsv, validate, wsv_to_csv,
+    wsv_to_markdown, wsv_to_tsv, ColumnAlignment, CsvOptions, CsvWriteOptions, TsvOptions,
+    WSVError, WSVToken, WSVTokenizer, WSVWriter,
+};
+
+#[derive(Parser)]
+#[command(
+    name = "wsv",
+    version,
+    about = "Work with whitespace separated value files"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check files for syntax errors. Exits with status 1 if any are found.
+    Validate {
+        /// The files to check. Reads stdin if none are given.
+        files: Vec<PathBuf>,
+    },
+    /// Reformat a file with aligned columns. Comments are kept.
+    Fmt {
+        /// How to align the columns.
+        #[arg(long, value_enum, default_value_t = Alignment::Left)]
+        align: Alignment,
+        /// Overwrite the file instead of writing to stdout.
+        #[arg(long, short, requires = "file")]
+        in_place: bool,
+        /// The file to format. Reads stdin if not given.
+        file: Option<PathBuf>,
+    },
+    /// Convert between WSV and other formats. Writes to stdout.
+    Convert {
+        /// The format of the input.
+        #[arg(long, value_enum, default_value_t = Format::Wsv)]
+        from: Format,
+        /// The format of the output.
+        #[arg(long, value_enum, default_value_t = Format::Wsv)]
+        to: Format,
+        /// The file to convert. Reads stdin if not given.
+        file: Option<PathBuf>,
+    },
+    /// Show row, column and null counts.
+    Stats {
+        /// The file to summarize. Reads stdin if not given.
+        file: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Alignment {
+    Left,
+    Right,
+    Packed,
+}
+
+impl From<Alignment> for ColumnAlignment {
+    fn from(value: Alignment) -> Self {
+        match value {
+            Alignment::Left => ColumnAlignment::Left,
+            Alignment::Right => ColumnAlignment::Right,
+            Alignment::Packed => ColumnAlignment::Packed,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Wsv,
+    Csv,
+    Tsv,
+    Json,
+    Markdown,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Validate { files } => run_validate(files),
+        Command::Fmt {
+            align,
+            in_place,
+            file,
+        } => run_fmt(align, in_place, file),
+        Command::Convert { from, to, file } => run_convert(from, to, file),
+        Command::Stats { file } => run_stats(file),
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("wsv: {}", err);
+            ExitCode::from(2)
+        }
+    }
+}
+
+type CliResult = Result<ExitCode, Box<dyn std::error::Error>>;
+
+fn read_input(file: Option<&PathBuf>) -> Result<String, std::io::Error> {
+    match file {
+        Some(file) => std::fs::read_to_string(file),
+        None => {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input)?;
+            Ok(input)
+        }
+    }
+}
+
+fn write_output(output: &str) -> Result<(), std::io::Error> {
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(output.as_bytes())?;
+    if !output.is_empty() && !output.ends_with('\n') {
+        stdout.write_all(b"\n")?;
+    }
+    stdout.flush()
+}
+
+fn run_validate(files: Vec<PathBuf>) -> CliResult {
+    let inputs = if files.is_empty() {
+        vec![(None, read_input(None)?)]
+    } else {
+        files
+            .iter()
+            .map(|file| Ok((Some(file), read_input(Some(file))?)))
+            .collect::<Result<Vec<_>, std::io::Error>>()?
+    };
+
+    let mut valid = true;
+    for (file, input) in inputs {
+        let name = file.map_or("<stdin>".into(), |file| file.display().to_string());
+        if let Err(errors) = validate(&input) {
+            valid = false;
+            for err in errors {
+                println!("{}: {}", name, err);
+            }
+        }
+    }
+    Ok(if valid {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn run_fmt(align: Alignment, in_place: bool, file: Option<PathBuf>) -> CliResult {
+    let input = read_input(file.as_ref())?;
+    let output = format(&input, align.into())?;
+    match file {
+        Some(file) if in_place => write_in_place(&file, &output)?,
+        _ => write_output(&output)?,
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Aligns the rows of the source text, keeping each comment at the end of
+/// its line and dropping trailing whitespace. Rows are the same as
+/// parse's, except that a comment-only last line is kept as well.
+fn format(input: &str, alignment: ColumnAlignment) -> Result<String, WSVError> {
+    let mut rows: Vec<Vec<Option<Cow<str>>>> = vec![Vec::new()];
+    let mut comments = vec![None];
+    for token in WSVTokenizer::new(input) {
+        match token? {
+            WSVToken::LF => {
+                rows.push(Vec::new());
+                comments.push(None);
+            }
+            WSVToken::Null => rows.last_mut().unwrap().push(None),
+            WSVToken::Value(value) => rows.last_mut().unwrap().push(Some(value)),
+            WSVToken::Comment(comment) => *comments.last_mut().unwrap() = Some(comment),
+        }
+    }
+    if rows.last().is_some_and(Vec::is_empty) && comments.last().is_some_and(Option::is_none) {
+        rows.pop();
+        comments.pop();
+    }
+
+    let aligned = WSVWriter::new(rows).align_columns(alignment).to_string();
+    let mut output = String::new();
+    for (line, comment) in aligned.split('\n').zip(comments) {
+        // The writer only pads and separates with spaces, and values that
+        // end in whitespace are quoted.
+        let line = line.trim_end_matches(' ');
+        output.push_str(line);
+        if let Some(comment) = comment {
+            if !line.is_empty() {
+                output.push(' ');
+            }
+            output.push('#');
+            output.push_str(comment);
+        }
+        output.push('\n');
+    }
+    Ok(output)
+}
+
+/// Replaces the file's contents by writing them to a temporary file next
+/// to it and renaming that over the file, so it is never left half
+/// written.
+fn write_in_place(file: &Path, output: &str) -> Result<(), std::io::Error> {
+    let name = file
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let temp = file.with_file_name(format!(".{}.{}.tmp", name, std::process::id()));
+    let result = (|| {
+        fs::write(&temp, output)?;
+        fs::set_permissions(&temp, fs::metadata(file)?.permissions())?;
+        fs::rename(&temp, file)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+fn run_convert(from: Format, to: Format, file: Option<PathBuf>) -> CliResult {
+    let input = read_input(file.as_ref())?;
+    let (wsv, alignments) = match from {
+        Format::Wsv => (input, Vec::new()),
+        Format::Csv => (
+            csv_to_wsv(input.as_bytes(), &CsvOptions::default())?,
+            Vec::new(),
+        ),
+        Format::Tsv => (
+            tsv_to_wsv(input.as_bytes(), &TsvOptions::default())?,
+            Vec::new(),
+        ),
+        Format::Json => {
+            let rows = from_json(&serde_json::from_str(&input)?)?;
+            (WSVWriter::new(rows).to_string(), Vec::new())
+        }
+        Format::Markdown => markdown_to_wsv(input.as_bytes())?,
+    };
+
+    let output = match to {
+        Format::Wsv => WSVWriter::new(parse(&wsv)?)
+            .align_columns(ColumnAlignment::Left)
+            .to_string(),
+        Format::Csv => wsv_to_csv(wsv.as_bytes(), &CsvWriteOptions::default())?,
+        Format::Tsv => wsv_to_tsv(wsv.as_bytes(), &TsvOptions::default())?,
+        Format::Json => serde_json::to_string_pretty(&to_json(&parse(&wsv)?)?)?,
+        Format::Markdown => wsv_to_markdown(wsv.as_bytes(), &alignments)?,
+    };
+    write_output(&output)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn run_stats(file: Option<PathBuf>) -> CliResult {
+    let input = read_input(file.as_ref())?;
+    let rows = parse(&input)?;
+    let stats = Stats::new(&rows);
+    let output = format!(
+        "rows: {}\nempty rows: {}\ncolumns: {}..={}\nvalues: {}\nnulls: {}",
+        stats.rows,
+        stats.empty_rows,
+        stats.min_columns,
+        stats.max_columns,
+        stats.values,
+        stats.nulls
+    );
+    write_output(&output)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Stats {
+    rows: usize,
+    empty_rows: usize,
+    min_columns: usize,
+    max_columns: usize,
+    values: usize,
+    nulls: usize,
+}
+
+impl Stats {
+    fn new<Row: AsRef<[Option<Str>]>, Str>(rows: &[Row]) -> Self {
+        let mut stats = Stats {
+            rows: rows.len(),
+            ..Default::default()
+        };
+        let mut counted = rows
+            .iter()
+            .map(|row| row.as_ref())
+            .filter(|row| !row.is_empty())
+            .peekable();
+        stats.min_columns = counted.peek().map_or(0, |row| row.len());
+        for row in counted {
+            stats.min_columns = stats.min_columns.min(row.len());
+            stats.max_columns = stats.max_columns.max(row.len());
+            stats.values += row.len();
+            stats.nulls += row.iter().filter(|value| value.is_none()).count();
+        }
+        stats.empty_rows = rows.iter().filter(|row| row.as_ref().is_empty()).count();
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use whitespacesv::{parse, ColumnAlignment};
+
+    use super::{format, Stats};
+
+    #[test]
+    fn formats_with_comments() {
+        let input = "# header\nid  name # key first\n\n1 \"a b\"\n22 -\n# end";
+        assert_eq!(
+            "# header\nid name # key first\n\n1  \"a b\"\n22 -\n# end\n",
+            format(input, ColumnAlignment::Left).unwrap()
+        );
+        assert_eq!(
+            "id name\n",
+            format("id   name\n", ColumnAlignment::Packed).unwrap()
+        );
+        assert_eq!("", format("", ColumnAlignment::Left).unwrap());
+    }
+
+    #[test]
+    fn counts_rows_and_values() {
+        let rows = parse("a b c\n\n1 - 3\n# comment\n4 -").unwrap();
+        assert_eq!(
+            Stats {
+                rows: 5,
+                empty_rows: 2,
+                min_columns: 2,
+                max_columns: 3,
+                values: 8,
+                nulls: 2,
+            },
+            Stats::new(&rows)
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use std::borrow::Cow;
+use std::io::{Read, Write};
+
+use bincode::config::{standard, Configuration};
+
+/// The bytes every cache starts with. This changes whenever the
+/// layout of the cache does, so stale caches are rejected.
+const MAGIC: &[u8; 8] = b"WSVBIN01";
+
+fn config() -> Configuration {
+    standard()
+}
+
+/// Writes parsed rows to the given writer in a compact binary format, so
+/// an expensive parse of a large file can be cached on disk and loaded
+/// back later with read_cached or decode_cached.
+pub fn write_cached<W, Row, Str>(rows: &[Row], mut writer: W) -> Result<(), std::io::Error>
+where
+    W: Write,
+    Row: AsRef<[Option<Str>]>,
+    Str: AsRef<str>,
+{
+    let rows = rows
+        .iter()
+        .map(|row| {
+            row.as_ref()
+                .iter()
+                .map(|value| value.as_ref().map(|value| value.as_ref()))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    writer.write_all(MAGIC)?;
+    bincode::encode_into_std_write(rows, &mut writer, config()).map_err(std::io::Error::other)?;
+    writer.flush()
+}
+
+/// Reads rows written by write_cached into owned Strings.
+pub fn read_cached<R: Read>(mut reader: R) -> Result<Vec<Vec<Option<String>>>, std::io::Error> {
+    let mut magic = [0; 8];
+    reader.read_exact(&mut magic)?;
+    check_magic(&magic)?;
+    bincode::decode_from_std_read(&mut reader, config()).map_err(invalid_data)
+}
+
+/// Decodes rows written by write_cached from an in-memory buffer (for
+/// example a memory mapped cache file). The values borrow from the
+/// buffer, so nothing is allocated per value and the result has the
+/// same type as the return value of parse.
+pub fn decode_cached(bytes: &[u8]) -> Result<Vec<Vec<Option<Cow<'_, str>>>>, std::io::Error> {
+    check_magic(bytes.get(..MAGIC.len()).unwrap_or_default())?;
+    let (rows, _) =
+        bincode::borrow_decode_from_slice(&bytes[MAGIC.len()..], config()).map_err(invalid_data)?;
+    Ok(rows)
+}
+
+fn check_magic(magic: &[u8]) -> Result<(), std::io::Error> {
+    if magic == MAGIC {
+        Ok(())
+    } else {
+        Err(invalid_data("not a WSV cache"))
+    }
+}
+
+fn invalid_data<E>(err: E) -> std::io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use std::io::Cursor;
+
+    use crate::{decode_cached, parse, read_cached, write_cached};
+
+    #[test]
+    fn cache_round_trips() {
+        let input = include_str!("../tests/1_stenway.com");
+        let rows = parse(input).unwrap();
+        let mut bytes = Vec::new();
+        write_cached(&rows, &mut bytes).unwrap();
+
+        assert_eq!(rows, decode_cached(&bytes).unwrap());
+        assert!(decode_cached(&bytes)
+            .unwrap()
+            .iter()
+            .flatten()
+            .flatten()
+            .all(|value| matches!(value, Cow::Borrowed(_))));
+
+        let owned = read_cached(Cursor::new(&bytes)).unwrap();
+        assert_eq!(rows.len(), owned.len());
+        for (expected, actual) in rows.iter().zip(owned.iter()) {
+            assert_eq!(
+                expected.iter().map(|v| v.as_deref()).collect::<Vec<_>>(),
+                actual.iter().map(|v| v.as_deref()).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_caches() {
+        assert!(decode_cached(b"").is_err());
+        assert!(decode_cached(b"WSVBIN00\0").is_err());
+        assert!(read_cached(Cursor::new(b"WSVBIN01\x05")).is_err());
+    }
+}
@@ -0,0 +1,134 @@
+use std::borrow::Cow;
+
+use crate::{write_packed_row, WSVError, WSVToken, WSVTokenizer, WriterOptions};
+
+/// Compares two WSV texts by their values only, so alignment, quoting
+/// choices, comments and a trailing line feed make no difference. Lines
+/// that only hold a comment are skipped entirely, but blank lines are
+/// still empty rows. Fails if either text can't be parsed.
+pub fn semantic_eq(a: &str, b: &str) -> Result<bool, WSVError> {
+    Ok(content_rows(a)? == content_rows(b)?)
+}
+
+/// Rewrites WSV text in a canonical form: packed, with values quoted only
+/// where they have to be, and every row (the last one included) ending
+/// with a single line feed. Comments and lines that only hold a comment
+/// are dropped, so two texts have the same canonical form exactly when
+/// semantic_eq says they are equal, and it can be compared or hashed
+/// directly.
+pub fn canonicalize(source_text: &str) -> Result<String, WSVError> {
+    write_canonical(source_text, false)
+}
+
+/// Same as canonicalize, but keeps comments. Comment lines are written as
+/// "#" followed by the comment, and a comment after values is written
+/// after a single space.
+pub fn canonicalize_with_comments(source_text: &str) -> Result<String, WSVError> {
+    write_canonical(source_text, true)
+}
+
+fn write_canonical(source_text: &str, keep_comments: bool) -> Result<String, WSVError> {
+    let options = WriterOptions::default();
+    let mut result = String::new();
+    let mut row = Vec::new();
+    let mut comment = None;
+    let mut write_line = |row: &mut Vec<_>, comment: Option<&str>| {
+        write_packed_row(&mut result, row, &options);
+        if let Some(comment) = comment {
+            if !row.is_empty() {
+                result.push(' ');
+            }
+            result.push('#');
+            result.push_str(comment);
+        }
+        result.push('\n');
+        row.clear();
+    };
+    let mut tokens = WSVTokenizer::new(source_text);
+    loop {
+        let token = tokens.next().transpose()?;
+        match token {
+            Some(WSVToken::Null) => row.push(None),
+            Some(WSVToken::Value(value)) => row.push(Some(value)),
+            Some(WSVToken::Comment(text)) => comment = Some(text),
+            Some(WSVToken::LF) | None => {
+                let comment = comment.take();
+                let comment_only = row.is_empty() && comment.is_some();
+                // Like parse, there is no empty row after a trailing line
+                // feed.
+                let last_blank = token.is_none() && row.is_empty() && comment.is_none();
+                if keep_comments {
+                    if !last_blank {
+                        write_line(&mut row, comment);
+                    }
+                } else if !comment_only && !last_blank {
+                    write_line(&mut row, None);
+                }
+                if token.is_none() {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Parses the text like parse, except that lines holding nothing but a
+/// comment are left out instead of becoming empty rows.
+pub(crate) fn content_rows(source_text: &str) -> Result<Vec<Vec<Option<Cow<'_, str>>>>, WSVError> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut comment_only = false;
+    for token in WSVTokenizer::new(source_text) {
+        match token? {
+            WSVToken::LF => {
+                if !comment_only {
+                    rows.push(std::mem::take(&mut row));
+                }
+                comment_only = false;
+            }
+            WSVToken::Null => row.push(None),
+            WSVToken::Value(value) => row.push(Some(value)),
+            WSVToken::Comment(_) => comment_only = row.is_empty(),
+        }
+    }
+    // Like parse, there is no empty row after a trailing line feed.
+    if !row.is_empty() {
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{canonicalize, canonicalize_with_comments, semantic_eq};
+
+    #[test]
+    fn compares_values_only() {
+        let packed = "id name\n1 \"ann b\"\n2 -\n\n3 \"\"";
+        let aligned = "# users\nid   name    # header\n 1   \"ann b\"\n 2   -\n\n\"3\" \"\"\n";
+        assert!(semantic_eq(packed, aligned).unwrap());
+        assert!(!semantic_eq(packed, "id name\n1 \"ann b\"\n2 -\n3 \"\"").unwrap());
+        assert!(!semantic_eq("a -", "a \"-\"").unwrap());
+        assert!(semantic_eq("a \"b", "a").is_err());
+    }
+
+    #[test]
+    fn canonicalizes_layout() {
+        let aligned = "# users\nid   name    # header\n 1   \"ann b\"\n\"2\"   \"-\"\n\n3 \"\"\n";
+        assert_eq!(
+            "id name\n1 \"ann b\"\n2 \"-\"\n\n3 \"\"\n",
+            canonicalize(aligned).unwrap()
+        );
+        assert_eq!(
+            "# users\nid name # header\n1 \"ann b\"\n2 \"-\"\n\n3 \"\"\n",
+            canonicalize_with_comments(aligned).unwrap()
+        );
+        assert_eq!(
+            canonicalize(aligned).unwrap(),
+            canonicalize(&canonicalize(aligned).unwrap()).unwrap()
+        );
+        assert_eq!("a\n\n", canonicalize("a\n\n").unwrap());
+        assert_eq!("", canonicalize("# nothing").unwrap());
+    }
+}
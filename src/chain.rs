@@ -0,0 +1,225 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::{WSVReadError, WSVReader};
+
+/// Reads the rows of several WSV sources one after the other, as if they
+/// were a single file. Useful for data that was split over several files
+/// (ex. by split, or by day).
+///
+/// Like WSVReader, an error doesn't stop the chain, so the rows after it
+/// can still be read.
+pub struct WSVChain<R> {
+    readers: VecDeque<WSVReader<R>>,
+    /// The index of the reader at the front of readers.
+    input: usize,
+    /// Whether no non-empty row has been read from the current input yet,
+    /// so the next one is its header.
+    at_input_start: bool,
+    skip_repeated_headers: bool,
+    check_column_counts: bool,
+    header: Option<Vec<Option<String>>>,
+}
+
+impl WSVChain<BufReader<File>> {
+    /// Opens each of the files at the given paths, to be read in order.
+    pub fn open<P: AsRef<Path>>(paths: &[P]) -> Result<Self, std::io::Error> {
+        let readers = paths
+            .iter()
+            .map(|path| Ok(BufReader::new(File::open(path)?)))
+            .collect::<Result<Vec<_>, std::io::Error>>()?;
+        Ok(Self::new(readers))
+    }
+}
+
+impl<R: BufRead> WSVChain<R> {
+    /// Creates a chain that reads each of the readers in order.
+    pub fn new<I: IntoIterator<Item = R>>(readers: I) -> Self {
+        Self {
+            readers: readers.into_iter().map(WSVReader::new).collect(),
+            input: 0,
+            at_input_start: true,
+            skip_repeated_headers: false,
+            check_column_counts: false,
+            header: None,
+        }
+    }
+
+    /// Sets whether the header row is left out of every input but the
+    /// first. The header is the first non-empty row of an input, so
+    /// leading blank and comment-only lines are skipped over. The header
+    /// of a later input is only left out if it is the same as the header
+    /// of the first input. Defaults to false.
+    pub fn skip_repeated_headers(mut self, skip_repeated_headers: bool) -> Self {
+        self.skip_repeated_headers = skip_repeated_headers;
+        self
+    }
+
+    /// Sets whether every row has to have as many values as the header of
+    /// the first input. Rows that don't are returned as
+    /// WSVChainError::ColumnCount instead. Empty rows (from blank or
+    /// comment-only lines) are not checked. Defaults to false.
+    pub fn check_column_counts(mut self, check_column_counts: bool) -> Self {
+        self.check_column_counts = check_column_counts;
+        self
+    }
+
+    /// The 0 based index of the input the most recent row came from.
+    pub fn input_index(&self) -> usize {
+        self.input
+    }
+}
+
+impl<R: BufRead> Iterator for WSVChain<R> {
+    type Item = Result<Vec<Option<String>>, WSVChainError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let reader = self.readers.front_mut()?;
+            let Some(row) = reader.next() else {
+                self.readers.pop_front();
+                if !self.readers.is_empty() {
+                    self.input += 1;
+                }
+                self.at_input_start = true;
+                continue;
+            };
+            let line = reader.line_number();
+            let row = match row {
+                Ok(row) => row,
+                Err(error) => {
+                    return Some(Err(WSVChainError::Read {
+                        input: self.input,
+                        error,
+                    }))
+                }
+            };
+
+            if self.at_input_start && !row.is_empty() {
+                self.at_input_start = false;
+                match &self.header {
+                    None => self.header = Some(row.clone()),
+                    Some(header) if self.skip_repeated_headers && *header == row => continue,
+                    Some(_) => {}
+                }
+            }
+            if self.check_column_counts && !row.is_empty() {
+                let expected = self.header.as_ref().map_or(0, Vec::len);
+                if row.len() != expected {
+                    return Some(Err(WSVChainError::ColumnCount {
+                        input: self.input,
+                        line,
+                        expected,
+                        found: row.len(),
+                    }));
+                }
+            }
+            return Some(Ok(row));
+        }
+    }
+}
+
+/// An error from a WSVChain. Inputs are 0 based indexes into the list of
+/// inputs and line numbers are 1 based.
+#[derive(Debug)]
+pub enum WSVChainError {
+    /// An input couldn't be read or parsed.
+    Read { input: usize, error: WSVReadError },
+    /// A row had a different number of values than the header of the
+    /// first input.
+    ColumnCount {
+        input: usize,
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl Display for WSVChainError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WSVChainError::Read { input, error } => {
+                write!(f, "(input: {}) {}", input + 1, error)
+            }
+            WSVChainError::ColumnCount {
+                input,
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "(input: {}, line: {}) Column Count Mismatch (expected {} values, found {})",
+                input + 1,
+                line,
+                expected,
+                found
+            ),
+        }
+    }
+}
+
+impl Error for WSVChainError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WSVChainError::Read { error, .. } => Some(error),
+            WSVChainError::ColumnCount { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{WSVChain, WSVChainError};
+
+    #[test]
+    fn chains_readers() {
+        let inputs = ["id name\n1 a\n", "id name\n2 b\n\n3\n", "4 \"d\n5 e"];
+        let rows = WSVChain::new(inputs.iter().map(|input| input.as_bytes()))
+            .skip_repeated_headers(true)
+            .check_column_counts(true)
+            .collect::<Vec<_>>();
+        assert_eq!(7, rows.len());
+        assert_eq!(
+            vec![Some("2".to_string()), Some("b".to_string())],
+            *rows[2].as_ref().unwrap()
+        );
+        assert!(rows[3].as_ref().unwrap().is_empty());
+        assert_eq!(
+            "(input: 2, line: 4) Column Count Mismatch (expected 2 values, found 1)",
+            rows[4].as_ref().unwrap_err().to_string()
+        );
+        assert!(matches!(rows[5], Err(WSVChainError::Read { input: 2, .. })));
+        assert_eq!(
+            vec![Some("5".to_string()), Some("e".to_string())],
+            *rows[6].as_ref().unwrap()
+        );
+
+        let rows = WSVChain::new(inputs.iter().map(|input| input.as_bytes()))
+            .filter_map(Result::ok)
+            .collect::<Vec<_>>();
+        assert_eq!(7, rows.len());
+    }
+
+    #[test]
+    fn finds_headers_after_comments() {
+        let inputs = ["# day 1\nid name\n1 a\n", "# day 2\n\nid name\n2 b\n"];
+        let rows = WSVChain::new(inputs.iter().map(|input| input.as_bytes()))
+            .skip_repeated_headers(true)
+            .check_column_counts(true)
+            .map(Result::unwrap)
+            .collect::<Vec<_>>();
+        let expected: Vec<Vec<Option<String>>> = vec![
+            vec![],
+            vec![Some("id".to_string()), Some("name".to_string())],
+            vec![Some("1".to_string()), Some("a".to_string())],
+            vec![],
+            vec![],
+            vec![Some("2".to_string()), Some("b".to_string())],
+        ];
+        assert_eq!(expected, rows);
+    }
+}
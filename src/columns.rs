@@ -0,0 +1,398 @@
+use std::borrow::Cow;
+
+use crate::{drop_empty_last_row, WSVError, WSVErrorType, WSVToken, WSVTokenizer, NEWLINE};
+
+/// What to do when a row has more values than the configured
+/// maximum column count.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnOverflow {
+    /// Collapse the last column and all of the extra values into a single
+    /// "rest" cell. The values are joined with a single space and nulls are
+    /// written as '-'. This is useful for semi-structured logs where the
+    /// last column is free text containing stray whitespace.
+    #[default]
+    Collapse,
+    /// Fail with a TooManyColumns error.
+    Error,
+}
+
+/// Same as parse (see the documentation there for behavior details),
+/// but caps the number of columns in each row. With ColumnOverflow::Collapse,
+/// the values after the last allowed column are folded into that column.
+/// For example, with a maximum of 3 columns the line
+/// ```wsv
+/// 2024-01-01 WARN disk is almost full
+/// ```
+/// would be returned as [Some(2024-01-01), Some(WARN), Some(disk is almost full)].
+/// A maximum of 0 is treated as 1 when collapsing.
+pub fn parse_with_max_columns(
+    source_text: &str,
+    max_columns: usize,
+    overflow: ColumnOverflow,
+) -> Result<Vec<Vec<Option<Cow<'_, str>>>>, WSVError> {
+    let mut result = vec![Vec::new()];
+    let mut tokenizer = WSVTokenizer::new(source_text);
+
+    while let Some(fallible_token) = tokenizer.next() {
+        let value = match fallible_token? {
+            WSVToken::LF => {
+                result.push(Vec::new());
+                continue;
+            }
+            WSVToken::Comment(_) => continue,
+            WSVToken::Null => None,
+            WSVToken::Value(value) => Some(value),
+        };
+
+        let line = result.last_mut().unwrap();
+        if !push_capped(line, value, max_columns, overflow) {
+            return Err(WSVError {
+                err_type: WSVErrorType::TooManyColumns,
+                location: tokenizer.current_location.clone(),
+            });
+        }
+    }
+
+    if result.last().is_some_and(|line| line.is_empty()) {
+        result.pop();
+    }
+
+    Ok(result)
+}
+
+/// Same as parse (see the documentation there for behavior details),
+/// but the column at index rest_column (0 based) greedily captures the
+/// rest of each line verbatim instead of splitting it into values. This
+/// matches the common log layout where the last column is a free text
+/// message that isn't quoted. For example, with a rest_column of 2 the line
+/// ```wsv
+/// 2024-01-01 WARN disk "sda" is almost full # from the disk monitor
+/// ```
+/// would be returned as
+/// [Some(2024-01-01), Some(WARN), Some(disk "sda" is almost full)].
+///
+/// The captured text has its surrounding whitespace trimmed and no escape
+/// sequences are replaced. If keep_comments is false, the captured text ends
+/// at the first '#' outside of double quotes like a normal comment would.
+/// Otherwise the '#' and everything after it is captured as well.
+pub fn parse_with_rest_column(
+    source_text: &str,
+    rest_column: usize,
+    keep_comments: bool,
+) -> Result<Vec<Vec<Option<Cow<'_, str>>>>, WSVError> {
+    let mut result = Vec::new();
+    let mut byte_offset = 0;
+
+    for (line_index, line) in source_text.split(NEWLINE).enumerate() {
+        let mut row = Vec::new();
+        let mut tokenizer = WSVTokenizer::new(line);
+        while row.len() < rest_column {
+            match tokenizer.next() {
+                None => break,
+                Some(Err(err)) => return Err(err.shifted(line_index, byte_offset)),
+                Some(Ok(WSVToken::Null)) => row.push(None),
+                Some(Ok(WSVToken::Value(value))) => row.push(Some(value)),
+                Some(Ok(WSVToken::LF)) | Some(Ok(WSVToken::Comment(_))) => {}
+            }
+        }
+
+        if row.len() == rest_column {
+            if let Some(err) = tokenizer.lookahead_error.take() {
+                return Err(err.shifted(line_index, byte_offset));
+            }
+            if let Some(rest) = trim_rest(&line[tokenizer.position()..], keep_comments) {
+                row.push(Some(Cow::Borrowed(rest)));
+            }
+        }
+
+        result.push(row);
+        byte_offset += line.len() + 1;
+    }
+
+    drop_empty_last_row(&mut result, Vec::is_empty);
+
+    Ok(result)
+}
+
+/// Trims the captured rest of a line, cutting it at the comment if
+/// comments aren't kept. A '#' between double quotes is part of a value,
+/// not a comment. Returns None if nothing is left.
+pub(crate) fn trim_rest(rest: &str, keep_comments: bool) -> Option<&str> {
+    let rest = if keep_comments {
+        rest
+    } else {
+        let mut in_quotes = false;
+        let comment_start = rest
+            .char_indices()
+            .find(|&(_, ch)| {
+                if ch == '"' {
+                    in_quotes = !in_quotes;
+                }
+                ch == '#' && !in_quotes
+            })
+            .map_or(rest.len(), |(index, _)| index);
+        &rest[..comment_start]
+    };
+    let rest = rest.trim_matches(WSVTokenizer::is_whitespace);
+    if rest.is_empty() {
+        None
+    } else {
+        Some(rest)
+    }
+}
+
+/// Pushes the value onto the line, respecting the maximum column count.
+/// Returns false if the value overflowed and overflow is set to Error.
+pub(crate) fn push_capped<S>(
+    line: &mut Vec<Option<S>>,
+    value: Option<S>,
+    max_columns: usize,
+    overflow: ColumnOverflow,
+) -> bool
+where
+    S: AsRef<str> + From<String>,
+{
+    if line.len() < max_columns {
+        line.push(value);
+        return true;
+    }
+
+    match overflow {
+        ColumnOverflow::Error => false,
+        ColumnOverflow::Collapse => {
+            match line.last_mut() {
+                None => line.push(value),
+                Some(last) => {
+                    let mut rest = match last.as_ref() {
+                        None => "-".to_string(),
+                        Some(text) => text.as_ref().to_string(),
+                    };
+                    rest.push(' ');
+                    rest.push_str(value.as_ref().map(|text| text.as_ref()).unwrap_or("-"));
+                    *last = Some(S::from(rest));
+                }
+            }
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parse, parse_lazy, parse_with_max_columns, parse_with_rest_column, ColumnOverflow,
+        WSVErrorType, WSVHeaders,
+    };
+
+    #[test]
+    fn collapses_extra_columns() {
+        let input = "2024-01-01 WARN disk is \"almost full\" -\n2024-01-02 INFO ok\nshort";
+        let result = parse_with_max_columns(input, 3, ColumnOverflow::Collapse).unwrap();
+        assert_eq!(
+            vec![
+                vec![
+                    Some("2024-01-01"),
+                    Some("WARN"),
+                    Some("disk is almost full -")
+                ],
+                vec![Some("2024-01-02"), Some("INFO"), Some("ok")],
+                vec![Some("short")],
+            ],
+            result
+                .iter()
+                .map(|line| line.iter().map(|v| v.as_deref()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+
+        let lazy = parse_lazy(input.chars())
+            .max_columns(3, ColumnOverflow::Collapse)
+            .map(|line| line.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(Some("disk is almost full -"), lazy[0][2].as_deref());
+        assert_eq!(3, lazy.len());
+    }
+
+    #[test]
+    fn errors_on_extra_columns() {
+        let input = "a b\nc d e";
+        let err = parse_with_max_columns(input, 2, ColumnOverflow::Error).unwrap_err();
+        assert_eq!(WSVErrorType::TooManyColumns, err.err_type());
+        assert_eq!(2, err.location().line());
+
+        let mut lazy = parse_lazy(input.chars()).max_columns(2, ColumnOverflow::Error);
+        assert!(lazy.next().unwrap().is_ok());
+        let err = lazy.next().unwrap().unwrap_err();
+        assert_eq!(WSVErrorType::TooManyColumns, err.err_type());
+        assert!(lazy.next().is_none());
+    }
+
+    #[test]
+    fn captures_rest_of_line() {
+        let input = "2024-01-01 WARN disk \"sda\" is  full # monitor\n\n2024-01-02 INFO\n- x   #only a comment  ";
+        let expected = vec![
+            vec![
+                Some("2024-01-01"),
+                Some("WARN"),
+                Some("disk \"sda\" is  full"),
+            ],
+            vec![],
+            vec![Some("2024-01-02"), Some("INFO")],
+            vec![None, Some("x")],
+        ];
+
+        let result = parse_with_rest_column(input, 2, false).unwrap();
+        assert_eq!(
+            expected,
+            result
+                .iter()
+                .map(|line| line.iter().map(|v| v.as_deref()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+
+        let lazy = parse_lazy(input.chars())
+            .rest_column(2, false)
+            .map(|line| line.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            expected,
+            lazy.iter()
+                .map(|line| line.iter().map(|v| v.as_deref()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+
+        let kept = parse_with_rest_column(input, 2, true).unwrap();
+        assert_eq!(
+            Some("disk \"sda\" is  full # monitor"),
+            kept[0][2].as_deref()
+        );
+        assert_eq!(Some("#only a comment"), kept[3][2].as_deref());
+
+        let input = "x \"a # b\" c # note";
+        let expected = vec![vec![Some("x"), Some("\"a # b\" c")]];
+        let result = parse_with_rest_column(input, 1, false).unwrap();
+        assert_eq!(
+            expected,
+            result
+                .iter()
+                .map(|line| line.iter().map(|v| v.as_deref()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+        let lazy = parse_lazy(input.chars())
+            .rest_column(1, false)
+            .map(|line| line.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            expected,
+            lazy.iter()
+                .map(|line| line.iter().map(|v| v.as_deref()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn rest_column_zero_captures_whole_lines() {
+        let input = "a b c\n\"x\"\n";
+        let result = parse_with_rest_column(input, 0, true).unwrap();
+        assert_eq!(2, result.len());
+        assert_eq!(Some("a b c"), result[0][0].as_deref());
+        assert_eq!(
+            parse_lazy(input.chars())
+                .rest_column(0, true)
+                .map(|line| line.unwrap())
+                .collect::<Vec<_>>(),
+            vec![
+                vec![Some("a b c".to_string())],
+                vec![Some("\"x\"".to_string())]
+            ]
+        );
+        assert_eq!(parse(input).unwrap().len(), result.len());
+    }
+
+    #[test]
+    fn selects_columns() {
+        let input =
+            "id name \"long note\" age\n1 ann \"a \"\"b\"\"\" 30\n\n2 - x\n3 y \"z\"/\"\" 4 extra";
+        let selected = parse_lazy(input.chars())
+            .select_columns(&[3, 1, 1])
+            .map(|line| line.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                vec![Some("age"), Some("name"), Some("name")],
+                vec![Some("30"), Some("ann"), Some("ann")],
+                vec![],
+                vec![None, None, None],
+                vec![Some("4"), Some("y"), Some("y")],
+            ],
+            selected
+                .iter()
+                .map(|line| line.iter().map(|v| v.as_deref()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+
+        let mut lines = parse_lazy(input.chars());
+        let headers = WSVHeaders::new(&lines.next().unwrap().unwrap()).unwrap();
+        assert_eq!(None, headers.indexes(&["id", "missing"]));
+        let ids = lines
+            .select_columns(&headers.indexes(&["id", "long note"]).unwrap())
+            .map(|line| line.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(Some("a \"b\""), ids[0][1].as_deref());
+        assert_eq!(Some("z\n"), ids[3][1].as_deref());
+
+        let collapsed = parse_lazy("a b c d".chars())
+            .max_columns(2, ColumnOverflow::Collapse)
+            .select_columns(&[1])
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(vec![Some("b c d".to_string())], collapsed);
+
+        // Skipped values are still checked.
+        let err = parse_lazy("a \"b\"c d".chars())
+            .select_columns(&[0])
+            .find_map(Result::err)
+            .unwrap();
+        assert_eq!(WSVErrorType::InvalidCharacterAfterString, err.err_type());
+    }
+
+    #[test]
+    fn filters_rows() {
+        let input =
+            "1 INFO started\n\n2 ERROR \"disk full\"\n3 INFO ok\n4 ERROR \"net down\" # retry";
+        let errors = parse_lazy(input.chars())
+            .filter_rows(&[1], |cells| cells[0] == Some("ERROR"))
+            .map(|line| line.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(2, errors.len());
+        assert_eq!(Some("disk full"), errors[0][2].as_deref());
+        assert_eq!(Some("4"), errors[1][0].as_deref());
+
+        let mut seen = Vec::new();
+        let messages = parse_lazy(input.chars())
+            .select_columns(&[2])
+            .filter_rows(&[0], |cells| {
+                seen.push(cells[0].map(str::to_string));
+                cells[0] != Some("3")
+            })
+            .map(|line| line.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                vec![Some("started".to_string())],
+                vec![Some("disk full".to_string())],
+                vec![Some("net down".to_string())],
+            ],
+            messages
+        );
+        assert_eq!(4, seen.len());
+
+        // Errors in lines that don't match are still returned.
+        let err = parse_lazy("a b\nc \"d\"e".chars())
+            .filter_rows(&[0], |_| false)
+            .next()
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(WSVErrorType::InvalidCharacterAfterString, err.err_type());
+    }
+}
@@ -0,0 +1,193 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+use crate::WSVReader;
+
+/// The magic number every gzip stream starts with.
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+
+/// The magic number every zstd frame starts with.
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// How many bytes of chars are collected before they are passed on to a
+/// compressing writer.
+const WRITE_CHUNK_LEN: usize = 8 * 1024;
+
+/// A compression format that open_decompressed can detect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionFormat {
+    /// Not compressed (or compressed in a format that isn't recognized).
+    None,
+    /// gzip, as used by .wsv.gz files. Needs the flate2 feature.
+    Gzip,
+    /// Zstandard, as used by .wsv.zst files. Needs the zstd feature.
+    Zstd,
+}
+
+impl CompressionFormat {
+    /// Detects the compression format from the first few bytes of a
+    /// file, using the magic number each format starts with.
+    pub fn detect(start: &[u8]) -> Self {
+        if start.starts_with(GZIP_MAGIC) {
+            CompressionFormat::Gzip
+        } else if start.starts_with(ZSTD_MAGIC) {
+            CompressionFormat::Zstd
+        } else {
+            CompressionFormat::None
+        }
+    }
+}
+
+impl WSVReader<Box<dyn BufRead + Send>> {
+    /// Opens the file at the given path the same way as open, but
+    /// decompresses it on the fly if it is compressed. The compression
+    /// format is detected from the start of the file rather than from its
+    /// extension, so a .wsv.gz file that was renamed still works.
+    /// Concatenated gzip streams and multi-frame zstd files are read as
+    /// a single file. Opening a file compressed with a format whose
+    /// feature isn't enabled returns an Unsupported error.
+    pub fn open_decompressed<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
+        let mut file = BufReader::new(File::open(path)?);
+        let reader: Box<dyn BufRead + Send> = match CompressionFormat::detect(file.fill_buf()?) {
+            CompressionFormat::None => Box::new(file),
+            #[cfg(feature = "flate2")]
+            CompressionFormat::Gzip => {
+                Box::new(BufReader::new(flate2::bufread::MultiGzDecoder::new(file)))
+            }
+            #[cfg(feature = "zstd")]
+            CompressionFormat::Zstd => Box::new(BufReader::new(
+                zstd::stream::read::Decoder::with_buffer(file)?,
+            )),
+            #[allow(unreachable_patterns)]
+            format => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Unsupported,
+                    format!("{:?} Compression Is Not Enabled", format),
+                ))
+            }
+        };
+        Ok(Self::new(reader))
+    }
+}
+
+/// Writes the chars of a WSV document (such as a WSVWriter) to the given
+/// writer as gzip, compressing it as it goes so the whole document is
+/// never held in memory. Returns the inner writer once the gzip stream
+/// has been finished.
+#[cfg(feature = "flate2")]
+pub fn write_gzip<Chars, W>(wsv: Chars, writer: W) -> Result<W, std::io::Error>
+where
+    Chars: IntoIterator<Item = char>,
+    W: Write,
+{
+    let mut encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+    write_chars(wsv, &mut encoder)?;
+    encoder.finish()
+}
+
+/// Writes the chars of a WSV document (such as a WSVWriter) to the given
+/// writer as zstd at the given compression level (1 to 22, or 0 for the
+/// default), compressing it as it goes so the whole document is never
+/// held in memory. Returns the inner writer once the zstd frame has been
+/// finished.
+#[cfg(feature = "zstd")]
+pub fn write_zstd<Chars, W>(wsv: Chars, writer: W, level: i32) -> Result<W, std::io::Error>
+where
+    Chars: IntoIterator<Item = char>,
+    W: Write,
+{
+    let mut encoder = zstd::stream::write::Encoder::new(writer, level)?;
+    write_chars(wsv, &mut encoder)?;
+    encoder.finish()
+}
+
+/// Writes the chars to the writer in chunks.
+fn write_chars<Chars, W>(chars: Chars, writer: &mut W) -> Result<(), std::io::Error>
+where
+    Chars: IntoIterator<Item = char>,
+    W: Write,
+{
+    let mut chunk = String::with_capacity(WRITE_CHUNK_LEN + 4);
+    for ch in chars {
+        chunk.push(ch);
+        if chunk.len() >= WRITE_CHUNK_LEN {
+            writer.write_all(chunk.as_bytes())?;
+            chunk.clear();
+        }
+    }
+    writer.write_all(chunk.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, CompressionFormat, WSVReader, WSVWriter};
+
+    #[cfg(feature = "flate2")]
+    #[test]
+    fn reads_and_writes_gzip() {
+        use crate::write_gzip;
+
+        let rows = parse("a b\n\"c d\" -\n").unwrap();
+        let compressed = write_gzip(WSVWriter::new(rows.clone()), Vec::new()).unwrap();
+        assert_eq!(
+            CompressionFormat::Gzip,
+            CompressionFormat::detect(&compressed)
+        );
+
+        let dir = std::env::temp_dir();
+        let gz_path = dir.join(format!("wsv-gzip-{}.wsv.gz", std::process::id()));
+        let plain_path = dir.join(format!("wsv-gzip-{}.wsv", std::process::id()));
+        std::fs::write(&gz_path, &compressed).unwrap();
+        std::fs::write(&plain_path, WSVWriter::new(rows.clone()).to_string()).unwrap();
+
+        for path in [&gz_path, &plain_path] {
+            let read = WSVReader::open_decompressed(path)
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(
+                rows.iter()
+                    .map(|row| row.iter().map(|v| v.as_deref()).collect::<Vec<_>>())
+                    .collect::<Vec<_>>(),
+                read.iter()
+                    .map(|row| row.iter().map(|v| v.as_deref()).collect::<Vec<_>>())
+                    .collect::<Vec<_>>()
+            );
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn reads_and_writes_zstd() {
+        use crate::write_zstd;
+
+        let rows = parse("a b\n\"c d\" -\n").unwrap();
+        let mut compressed = write_zstd(WSVWriter::new(rows.clone()), Vec::new(), 0).unwrap();
+        assert_eq!(
+            CompressionFormat::Zstd,
+            CompressionFormat::detect(&compressed)
+        );
+        // A second frame is read as more of the same file.
+        compressed = write_zstd("\ne f".chars(), compressed, 3).unwrap();
+
+        let path = std::env::temp_dir().join(format!("wsv-zstd-{}.wsv.zst", std::process::id()));
+        std::fs::write(&path, &compressed).unwrap();
+        let read = WSVReader::open_decompressed(&path)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            vec![
+                vec![Some("a"), Some("b")],
+                vec![Some("c d"), None],
+                vec![Some("e"), Some("f")],
+            ],
+            read.iter()
+                .map(|row| row.iter().map(|v| v.as_deref()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
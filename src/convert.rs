@@ -0,0 +1,756 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::io::Read;
+
+use crate::{parse, ColumnAlignment, WSVError, WSVWriter};
+
+/// Options for reading CSV in csv_to_wsv.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// The character between fields. Defaults to ','.
+    pub delimiter: char,
+    /// Whether unquoted empty fields become nulls ('-') instead of empty
+    /// strings. A quoted empty field ("") is always an empty string.
+    /// Defaults to true.
+    pub empty_as_null: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            empty_as_null: true,
+        }
+    }
+}
+
+/// Converts CSV read from the given reader into WSV. Quoted fields may
+/// contain delimiters, escaped quotes ("") and line breaks, all of which
+/// are written back out with the WSV escaping rules. Records may end
+/// with either "\n" or "\r\n". Empty lines become empty rows.
+pub fn csv_to_wsv<R: Read>(mut reader: R, options: &CsvOptions) -> Result<String, WSVConvertError> {
+    let mut source = String::new();
+    reader.read_to_string(&mut source)?;
+    let rows = parse_csv(&source, options)?;
+    Ok(WSVWriter::new(rows).to_string())
+}
+
+/// When wsv_to_csv puts quotes around a field.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QuoteStyle {
+    /// Only quote fields that contain the delimiter, a quote or a line
+    /// break, plus empty strings if nulls are written as empty fields.
+    #[default]
+    Necessary,
+    /// Quote every field except nulls.
+    Always,
+    /// Never quote anything. Fields that would need quotes are an error.
+    Never,
+}
+
+/// Options for writing CSV in wsv_to_csv.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvWriteOptions {
+    /// The character between fields. Defaults to ','.
+    pub delimiter: char,
+    /// When to quote fields. Defaults to QuoteStyle::Necessary.
+    pub quote_style: QuoteStyle,
+    /// The text written for null ('-') values. It is never quoted.
+    /// Defaults to an empty field.
+    pub null_text: String,
+}
+
+impl Default for CsvWriteOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            quote_style: QuoteStyle::default(),
+            null_text: String::new(),
+        }
+    }
+}
+
+/// Converts WSV read from the given reader into CSV. Every row becomes
+/// one record, with records separated by "\n". Values are quoted and
+/// escaped according to the options.
+pub fn wsv_to_csv<R: Read>(
+    mut reader: R,
+    options: &CsvWriteOptions,
+) -> Result<String, WSVConvertError> {
+    let mut source = String::new();
+    reader.read_to_string(&mut source)?;
+
+    let mut result = String::new();
+    for (line_index, row) in parse(&source)?.iter().enumerate() {
+        if line_index != 0 {
+            result.push('\n');
+        }
+        for (i, value) in row.iter().enumerate() {
+            if i != 0 {
+                result.push(options.delimiter);
+            }
+            let Some(value) = value else {
+                result.push_str(&options.null_text);
+                continue;
+            };
+
+            let needs_quotes = value.contains(['"', '\n', '\r', options.delimiter])
+                || (value.is_empty() && options.null_text.is_empty());
+            let quote = match options.quote_style {
+                QuoteStyle::Always => true,
+                QuoteStyle::Necessary => needs_quotes,
+                QuoteStyle::Never if needs_quotes && !value.is_empty() => {
+                    return Err(WSVConvertError::InvalidInput {
+                        line: line_index + 1,
+                        message: "Value Needs Quotes",
+                    });
+                }
+                QuoteStyle::Never => false,
+            };
+
+            if quote {
+                result.push('"');
+                result.push_str(&value.replace('"', "\"\""));
+                result.push('"');
+            } else {
+                result.push_str(value);
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// How TSV values that contain tabs, line breaks or backslashes are handled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TsvEscaping {
+    /// Tabs, line feeds, carriage returns and backslashes are written as
+    /// \t, \n, \r and \\ and read back the same way. This is the
+    /// convention used by most databases and tools that export TSV.
+    #[default]
+    Backslash,
+    /// Values are read and written exactly as they are. Writing a value
+    /// that contains a tab or a line break is an error.
+    None,
+}
+
+/// Options for tsv_to_wsv and wsv_to_tsv.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TsvOptions {
+    /// How special characters in values are handled.
+    /// Defaults to TsvEscaping::Backslash.
+    pub escaping: TsvEscaping,
+    /// The text that stands for null ('-'). Defaults to an empty field,
+    /// which means empty strings can't be told apart from nulls. Set this
+    /// to something like "\\N" if that matters.
+    pub null_text: String,
+}
+
+/// Converts TSV (tab separated values, with no quoting) read from the given
+/// reader into WSV. Records may end with either "\n" or "\r\n".
+pub fn tsv_to_wsv<R: Read>(mut reader: R, options: &TsvOptions) -> Result<String, WSVConvertError> {
+    let mut source = String::new();
+    reader.read_to_string(&mut source)?;
+    let source = source.strip_suffix('\n').unwrap_or(&source);
+
+    let rows = source
+        .split('\n')
+        .map(|line| {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            if line.is_empty() {
+                return Vec::new();
+            }
+            line.split('\t')
+                .map(|field| {
+                    if field == options.null_text {
+                        None
+                    } else if options.escaping == TsvEscaping::Backslash {
+                        Some(unescape_tsv(field))
+                    } else {
+                        Some(field.to_string())
+                    }
+                })
+                .collect()
+        })
+        .collect::<Vec<Vec<_>>>();
+    Ok(WSVWriter::new(rows).to_string())
+}
+
+/// Converts WSV read from the given reader into TSV, with records
+/// separated by "\n".
+pub fn wsv_to_tsv<R: Read>(mut reader: R, options: &TsvOptions) -> Result<String, WSVConvertError> {
+    let mut source = String::new();
+    reader.read_to_string(&mut source)?;
+
+    let mut result = String::new();
+    for (line_index, row) in parse(&source)?.iter().enumerate() {
+        if line_index != 0 {
+            result.push('\n');
+        }
+        for (i, value) in row.iter().enumerate() {
+            if i != 0 {
+                result.push('\t');
+            }
+            match (value, options.escaping) {
+                (None, _) => result.push_str(&options.null_text),
+                (Some(value), TsvEscaping::Backslash) => {
+                    for ch in value.chars() {
+                        match ch {
+                            '\t' => result.push_str("\\t"),
+                            '\n' => result.push_str("\\n"),
+                            '\r' => result.push_str("\\r"),
+                            '\\' => result.push_str("\\\\"),
+                            ch => result.push(ch),
+                        }
+                    }
+                }
+                (Some(value), TsvEscaping::None) => {
+                    if value.contains(['\t', '\n', '\r']) {
+                        return Err(WSVConvertError::InvalidInput {
+                            line: line_index + 1,
+                            message: "Value Contains A Tab Or Line Break",
+                        });
+                    }
+                    result.push_str(value);
+                }
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Replaces the backslash escapes in a TSV field. Unknown
+/// escapes are left as they are.
+fn unescape_tsv(field: &str) -> String {
+    let mut result = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            result.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => result.push('\t'),
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('\\') => result.push('\\'),
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// Converts WSV read from the given reader into a GitHub flavored
+/// Markdown table. The first row is used as the header row. The given
+/// alignments are used for the markers in the delimiter row, one per
+/// column. Columns without an alignment (and Packed columns) get no
+/// marker. Nulls and empty strings are both written as empty cells,
+/// pipes are escaped as "\|" and line breaks are written as "<br>".
+pub fn wsv_to_markdown<R: Read>(
+    mut reader: R,
+    alignments: &[ColumnAlignment],
+) -> Result<String, WSVConvertError> {
+    let mut source = String::new();
+    reader.read_to_string(&mut source)?;
+
+    let rows = parse(&source)?
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|value| match value {
+                    None => String::new(),
+                    Some(value) => value.replace('|', "\\|").replace('\n', "<br>"),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let column_count = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if column_count == 0 {
+        return Ok(String::new());
+    }
+
+    // The delimiter row needs at least 3 characters per column.
+    let mut widths = vec![3; column_count];
+    for row in rows.iter() {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(value.chars().count());
+        }
+    }
+
+    let mut result = String::new();
+    for (line_index, row) in rows.iter().enumerate() {
+        for (column, width) in widths.iter().enumerate() {
+            let value = row.get(column).map(String::as_str).unwrap_or_default();
+            let padding = width - value.chars().count();
+            result.push_str("| ");
+            match alignments.get(column) {
+                Some(ColumnAlignment::Right) => {
+                    result.push_str(&" ".repeat(padding));
+                    result.push_str(value);
+                }
+                _ => {
+                    result.push_str(value);
+                    result.push_str(&" ".repeat(padding));
+                }
+            }
+            result.push(' ');
+        }
+        result.push_str("|\n");
+
+        if line_index == 0 {
+            for (column, width) in widths.iter().enumerate() {
+                result.push_str("| ");
+                match alignments.get(column) {
+                    Some(ColumnAlignment::Left) => {
+                        result.push(':');
+                        result.push_str(&"-".repeat(width - 1));
+                    }
+                    Some(ColumnAlignment::Right) => {
+                        result.push_str(&"-".repeat(width - 1));
+                        result.push(':');
+                    }
+                    _ => result.push_str(&"-".repeat(*width)),
+                }
+                result.push(' ');
+            }
+            result.push_str("|\n");
+        }
+    }
+    Ok(result)
+}
+
+/// Converts the GitHub flavored Markdown table read from the given reader
+/// into WSV, along with the alignment of each column. The table must
+/// start on the first non-blank line and ends at the next blank line.
+/// Empty cells become nulls, "\|" becomes a pipe and "<br>" becomes a
+/// line break. As in GitHub's renderer, rows with fewer cells than the
+/// header are filled up with empty cells and extra cells are ignored.
+///
+/// ColumnAlignment has no centered variant, so both centered columns
+/// (":-:") and columns without any marker are read as Packed.
+pub fn markdown_to_wsv<R: Read>(
+    mut reader: R,
+) -> Result<(String, Vec<ColumnAlignment>), WSVConvertError> {
+    let mut source = String::new();
+    reader.read_to_string(&mut source)?;
+
+    let mut lines = source
+        .lines()
+        .enumerate()
+        .skip_while(|(_, line)| line.trim().is_empty())
+        .take_while(|(_, line)| !line.trim().is_empty());
+
+    let Some((_, header)) = lines.next() else {
+        return Ok((String::new(), Vec::new()));
+    };
+    let header = split_markdown_row(header);
+
+    let invalid_delimiter_row = |line_index: usize| WSVConvertError::InvalidInput {
+        line: line_index + 1,
+        message: "Invalid Delimiter Row",
+    };
+    let Some((line_index, delimiter_row)) = lines.next() else {
+        return Err(invalid_delimiter_row(source.lines().count()));
+    };
+    let alignments = split_markdown_row(delimiter_row)
+        .into_iter()
+        .map(|marker| {
+            let marker = marker.unwrap_or_default();
+            let dashes = marker.trim_start_matches(':').trim_end_matches(':');
+            if dashes.is_empty() || !dashes.chars().all(|ch| ch == '-') {
+                return Err(invalid_delimiter_row(line_index));
+            }
+            Ok(match (marker.starts_with(':'), marker.ends_with(':')) {
+                (true, false) => ColumnAlignment::Left,
+                (false, true) => ColumnAlignment::Right,
+                _ => ColumnAlignment::Packed,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if alignments.len() != header.len() {
+        return Err(invalid_delimiter_row(line_index));
+    }
+
+    let mut rows = vec![header];
+    for (_, line) in lines {
+        let mut row = split_markdown_row(line);
+        row.resize(alignments.len(), None);
+        rows.push(row);
+    }
+    Ok((WSVWriter::new(rows).to_string(), alignments))
+}
+
+/// Splits a Markdown table row into its trimmed cells.
+fn split_markdown_row(line: &str) -> Vec<Option<String>> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = match line.strip_suffix('|') {
+        Some(rest) if !rest.ends_with('\\') => rest,
+        _ => line,
+    };
+
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = line.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' if chars.peek() == Some(&'|') => {
+                chars.next();
+                cell.push('|');
+            }
+            '|' => cells.push(markdown_cell(&std::mem::take(&mut cell))),
+            ch => cell.push(ch),
+        }
+    }
+    cells.push(markdown_cell(&cell));
+    cells
+}
+
+fn markdown_cell(cell: &str) -> Option<String> {
+    let value = cell
+        .trim()
+        .replace("<br>", "\n")
+        .replace("<br/>", "\n")
+        .replace("<br />", "\n");
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+/// Splits CSV source text into rows of values.
+fn parse_csv(
+    source: &str,
+    options: &CsvOptions,
+) -> Result<Vec<Vec<Option<String>>>, WSVConvertError> {
+    let source = source.strip_prefix('\u{FEFF}').unwrap_or(source);
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut row_quoted = false;
+    let mut chars = source.chars().peekable();
+    let mut line = 1;
+
+    while chars.peek().is_some() {
+        let value = if chars.peek() == Some(&'"') {
+            chars.next();
+            row_quoted = true;
+            let start_line = line;
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    None => {
+                        return Err(WSVConvertError::InvalidInput {
+                            line: start_line,
+                            message: "Quoted Field Not Closed",
+                        })
+                    }
+                    Some('"') if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        value.push('"');
+                    }
+                    Some('"') => break,
+                    Some(ch) => {
+                        if ch == '\n' {
+                            line += 1;
+                        }
+                        value.push(ch);
+                    }
+                }
+            }
+            match chars.peek() {
+                None | Some('\n') | Some('\r') => {}
+                Some(ch) if *ch == options.delimiter => {}
+                Some(_) => {
+                    return Err(WSVConvertError::InvalidInput {
+                        line,
+                        message: "Invalid Character After Quoted Field",
+                    })
+                }
+            }
+            Some(value)
+        } else {
+            let mut value = String::new();
+            while let Some(ch) = chars.next_if(|ch| *ch != options.delimiter && *ch != '\n') {
+                value.push(ch);
+            }
+            if chars.peek() == Some(&'\n') && value.ends_with('\r') {
+                value.pop();
+            }
+            if value.is_empty() && options.empty_as_null {
+                None
+            } else {
+                Some(value)
+            }
+        };
+        row.push(value);
+
+        if chars.next_if_eq(&'\r').is_some() && chars.peek() != Some(&'\n') {
+            return Err(WSVConvertError::InvalidInput {
+                line,
+                message: "Invalid Character After Quoted Field",
+            });
+        }
+        match chars.next() {
+            Some('\n') => {
+                line += 1;
+                rows.push(finish_row(std::mem::take(&mut row), row_quoted));
+                row_quoted = false;
+            }
+            // A delimiter at the very end still has an empty field after it.
+            Some(_) if chars.peek().is_none() => {
+                row.push(if options.empty_as_null {
+                    None
+                } else {
+                    Some(String::new())
+                });
+            }
+            _ => {}
+        }
+    }
+
+    if !row.is_empty() {
+        rows.push(finish_row(row, row_quoted));
+    }
+    Ok(rows)
+}
+
+/// An empty line is read as a single empty field, but
+/// is much more useful as an empty row. A line holding
+/// only "" really is one empty string, so it is kept.
+fn finish_row(row: Vec<Option<String>>, quoted: bool) -> Vec<Option<String>> {
+    if quoted {
+        return row;
+    }
+    match row.as_slice() {
+        [None] => Vec::new(),
+        [Some(value)] if value.is_empty() => Vec::new(),
+        _ => row,
+    }
+}
+
+/// An error from one of the functions in the convert module.
+#[derive(Debug)]
+pub enum WSVConvertError {
+    /// An IO error from the underlying reader.
+    Io(std::io::Error),
+    /// The input was WSV and it was invalid.
+    WSV(WSVError),
+    /// The input was some other format and it was invalid.
+    InvalidInput { line: usize, message: &'static str },
+}
+
+impl Display for WSVConvertError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WSVConvertError::Io(err) => write!(f, "{}", err),
+            WSVConvertError::WSV(err) => write!(f, "{}", err),
+            WSVConvertError::InvalidInput { line, message } => {
+                write!(f, "(line: {}) {}", line, message)
+            }
+        }
+    }
+}
+
+impl Error for WSVConvertError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WSVConvertError::Io(err) => Some(err),
+            WSVConvertError::WSV(err) => Some(err),
+            WSVConvertError::InvalidInput { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for WSVConvertError {
+    fn from(value: std::io::Error) -> Self {
+        WSVConvertError::Io(value)
+    }
+}
+
+impl From<WSVError> for WSVConvertError {
+    fn from(value: WSVError) -> Self {
+        WSVConvertError::WSV(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        csv_to_wsv, markdown_to_wsv, parse, tsv_to_wsv, wsv_to_csv, wsv_to_markdown, wsv_to_tsv,
+        ColumnAlignment, CsvOptions, CsvWriteOptions, QuoteStyle, TsvEscaping, TsvOptions,
+        WSVConvertError,
+    };
+
+    #[test]
+    fn converts_csv() {
+        let csv = "\u{FEFF}id,name,note\r\n1,\"Smith, Ann\",\"said \"\"hi\"\"\"\r\n2,,\"\"\n\n3,\"multi\nline\",# -\n4,-,";
+        let wsv = csv_to_wsv(csv.as_bytes(), &CsvOptions::default()).unwrap();
+        let rows = parse(&wsv).unwrap();
+        assert_eq!(
+            vec![
+                vec![Some("id"), Some("name"), Some("note")],
+                vec![Some("1"), Some("Smith, Ann"), Some("said \"hi\"")],
+                vec![Some("2"), None, Some("")],
+                vec![],
+                vec![Some("3"), Some("multi\nline"), Some("# -")],
+                vec![Some("4"), Some("-"), None],
+            ],
+            rows.iter()
+                .map(|row| row.iter().map(|v| v.as_deref()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+
+        let options = CsvOptions {
+            delimiter: ';',
+            empty_as_null: false,
+        };
+        let wsv = csv_to_wsv("a;;b,c".as_bytes(), &options).unwrap();
+        assert_eq!(
+            vec![vec![Some("a"), Some(""), Some("b,c")]],
+            parse(&wsv)
+                .unwrap()
+                .iter()
+                .map(|row| row.iter().map(|v| v.as_deref()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn keeps_quoted_empty_csv_lines() {
+        let wsv = csv_to_wsv("a\n\"\"\n\nb\n\"\"".as_bytes(), &CsvOptions::default()).unwrap();
+        assert_eq!(
+            vec![
+                vec![Some("a")],
+                vec![Some("")],
+                vec![],
+                vec![Some("b")],
+                vec![Some("")],
+            ],
+            parse(&wsv)
+                .unwrap()
+                .iter()
+                .map(|row| row.iter().map(|v| v.as_deref()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_csv() {
+        for (csv, line) in [("a\n\"unclosed\n", 2), ("a\n\"x\"y,z", 2)] {
+            match csv_to_wsv(csv.as_bytes(), &CsvOptions::default()) {
+                Err(WSVConvertError::InvalidInput { line: actual, .. }) => assert_eq!(line, actual),
+                other => panic!("Expected invalid input, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn converts_wsv_to_csv() {
+        let wsv = "id name note\n1 \"Smith, Ann\" \"said \"\"hi\"\"\"\n2 - \"\"\n3 \"a\"/\"b\"";
+        let csv = wsv_to_csv(wsv.as_bytes(), &CsvWriteOptions::default()).unwrap();
+        assert_eq!(
+            "id,name,note\n1,\"Smith, Ann\",\"said \"\"hi\"\"\"\n2,,\"\"\n3,\"a\nb\"",
+            csv
+        );
+        let round_tripped = csv_to_wsv(csv.as_bytes(), &CsvOptions::default()).unwrap();
+        assert_eq!(parse(wsv).unwrap(), parse(&round_tripped).unwrap());
+
+        let options = CsvWriteOptions {
+            delimiter: '\t',
+            quote_style: QuoteStyle::Always,
+            null_text: "NULL".to_string(),
+        };
+        let csv = wsv_to_csv("a - \"\"".as_bytes(), &options).unwrap();
+        assert_eq!("\"a\"\tNULL\t\"\"", csv);
+
+        let options = CsvWriteOptions {
+            quote_style: QuoteStyle::Never,
+            ..Default::default()
+        };
+        assert_eq!("a,", wsv_to_csv("a \"\"".as_bytes(), &options).unwrap());
+        assert!(matches!(
+            wsv_to_csv("a\n\"b,c\"".as_bytes(), &options),
+            Err(WSVConvertError::InvalidInput { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn converts_tsv() {
+        let tsv = "id\tnote\tpath\r\n1\tline\\none\\ttab\tC:\\\\dir\n\n2\t\tx y\n";
+        let wsv = tsv_to_wsv(tsv.as_bytes(), &TsvOptions::default()).unwrap();
+        assert_eq!(
+            vec![
+                vec![Some("id"), Some("note"), Some("path")],
+                vec![Some("1"), Some("line\none\ttab"), Some("C:\\dir")],
+                vec![],
+                vec![Some("2"), None, Some("x y")],
+            ],
+            parse(&wsv)
+                .unwrap()
+                .iter()
+                .map(|row| row.iter().map(|v| v.as_deref()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            tsv.replace("\r\n", "\n").trim_end(),
+            wsv_to_tsv(wsv.as_bytes(), &TsvOptions::default()).unwrap()
+        );
+
+        let options = TsvOptions {
+            escaping: TsvEscaping::None,
+            null_text: "\\N".to_string(),
+        };
+        let wsv = tsv_to_wsv("a\\b\t\\N\t".as_bytes(), &options).unwrap();
+        assert_eq!(
+            vec![vec![Some("a\\b"), None, Some("")]],
+            parse(&wsv)
+                .unwrap()
+                .iter()
+                .map(|row| row.iter().map(|v| v.as_deref()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+        assert!(matches!(
+            wsv_to_tsv("\"a\"/\"b\"".as_bytes(), &options),
+            Err(WSVConvertError::InvalidInput { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn converts_markdown_tables() {
+        let wsv = "id name note\n1 \"a|b\" -\n22 \"x\"/\"y\" -";
+        let alignments = [ColumnAlignment::Right, ColumnAlignment::Left];
+        let markdown = wsv_to_markdown(wsv.as_bytes(), &alignments).unwrap();
+        assert_eq!(
+            "|  id | name   | note |\n\
+             | --: | :----- | ---- |\n\
+             |   1 | a\\|b   |      |\n\
+             |  22 | x<br>y |      |\n",
+            markdown
+        );
+
+        let (back, read_alignments) = markdown_to_wsv(markdown.as_bytes()).unwrap();
+        assert_eq!(
+            vec![
+                ColumnAlignment::Right,
+                ColumnAlignment::Left,
+                ColumnAlignment::Packed
+            ],
+            read_alignments
+        );
+        assert_eq!(parse(wsv).unwrap(), parse(&back).unwrap());
+
+        let (back, read_alignments) =
+            markdown_to_wsv("\nA | B\n:-: | -\n1\n\nignored".as_bytes()).unwrap();
+        assert_eq!(vec![ColumnAlignment::Packed; 2], read_alignments);
+        assert_eq!(parse("A B\n1 -").unwrap(), parse(&back).unwrap());
+
+        assert!(matches!(
+            markdown_to_wsv("| a | b |\n| - |".as_bytes()),
+            Err(WSVConvertError::InvalidInput { line: 2, .. })
+        ));
+    }
+}
@@ -0,0 +1,150 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime};
+
+use crate::schema::null_value;
+use crate::ColumnConverter;
+
+/// A RowSchema column of dates in the given chrono format (see
+/// chrono::format::strftime). The default format is "%Y-%m-%d".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateColumn {
+    format: String,
+}
+
+impl DateColumn {
+    pub fn new(format: impl Into<String>) -> Self {
+        Self {
+            format: format.into(),
+        }
+    }
+
+    /// Formats a date the same way this column parses it, for use with
+    /// the WSVWriter.
+    pub fn format(&self, value: &NaiveDate) -> String {
+        value.format(&self.format).to_string()
+    }
+}
+
+impl Default for DateColumn {
+    fn default() -> Self {
+        Self::new("%Y-%m-%d")
+    }
+}
+
+impl ColumnConverter for DateColumn {
+    type Output = NaiveDate;
+
+    fn convert(&self, value: Option<&str>) -> Result<Self::Output, String> {
+        let value = value.ok_or_else(null_value)?;
+        NaiveDate::parse_from_str(value, &self.format).map_err(|err| err.to_string())
+    }
+}
+
+/// A RowSchema column of dates and times without a time zone in the
+/// given chrono format. The default format is "%Y-%m-%dT%H:%M:%S%.f".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NaiveDateTimeColumn {
+    format: String,
+}
+
+impl NaiveDateTimeColumn {
+    pub fn new(format: impl Into<String>) -> Self {
+        Self {
+            format: format.into(),
+        }
+    }
+
+    /// Formats a date and time the same way this column parses it, for
+    /// use with the WSVWriter.
+    pub fn format(&self, value: &NaiveDateTime) -> String {
+        value.format(&self.format).to_string()
+    }
+}
+
+impl Default for NaiveDateTimeColumn {
+    fn default() -> Self {
+        Self::new("%Y-%m-%dT%H:%M:%S%.f")
+    }
+}
+
+impl ColumnConverter for NaiveDateTimeColumn {
+    type Output = NaiveDateTime;
+
+    fn convert(&self, value: Option<&str>) -> Result<Self::Output, String> {
+        let value = value.ok_or_else(null_value)?;
+        NaiveDateTime::parse_from_str(value, &self.format).map_err(|err| err.to_string())
+    }
+}
+
+/// A RowSchema column of dates and times with a UTC offset in the given
+/// chrono format. The format must include an offset (such as %z). The
+/// default format is "%+", which is RFC 3339 (ex. 2024-01-31T08:30:00+01:00).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateTimeColumn {
+    format: String,
+}
+
+impl DateTimeColumn {
+    pub fn new(format: impl Into<String>) -> Self {
+        Self {
+            format: format.into(),
+        }
+    }
+
+    /// Formats a date and time the same way this column parses it, for
+    /// use with the WSVWriter.
+    pub fn format(&self, value: &DateTime<FixedOffset>) -> String {
+        value.format(&self.format).to_string()
+    }
+}
+
+impl Default for DateTimeColumn {
+    fn default() -> Self {
+        Self::new("%+")
+    }
+}
+
+impl ColumnConverter for DateTimeColumn {
+    type Output = DateTime<FixedOffset>;
+
+    fn convert(&self, value: Option<&str>) -> Result<Self::Output, String> {
+        let value = value.ok_or_else(null_value)?;
+        DateTime::parse_from_str(value, &self.format).map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::{DateColumn, DateTimeColumn, OptionalColumn, RowSchema, WSVWriter};
+
+    #[test]
+    fn converts_dates_and_times() {
+        let dates = DateColumn::new("%d/%m/%Y");
+        let schema = RowSchema::new((dates.clone(), OptionalColumn(DateTimeColumn::default())));
+        let rows = schema
+            .parse("31/01/2024 2024-01-31T08:30:00+01:00\n01/02/2024 -")
+            .unwrap();
+        assert_eq!(NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(), rows[1].0);
+        assert_eq!(
+            "2024-01-31T07:30:00+00:00",
+            rows[0].1.unwrap().to_utc().to_rfc3339()
+        );
+        assert!(rows[1].1.is_none());
+
+        let written = WSVWriter::new(rows.iter().map(|(date, time)| {
+            [
+                Some(dates.format(date)),
+                time.map(|time| DateTimeColumn::default().format(&time)),
+            ]
+        }))
+        .to_string();
+        assert_eq!(rows, schema.parse(&written).unwrap());
+
+        let err = schema.parse("2024-01-31").unwrap_err();
+        assert_eq!(
+            "(line: 1, value: 1) Invalid Value (\"2024-01-31\"): input contains invalid characters",
+            err.to_string()
+        );
+    }
+}
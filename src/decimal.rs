@@ -0,0 +1,81 @@
+use rust_decimal::Decimal;
+
+use crate::schema::null_value;
+use crate::ColumnConverter;
+
+/// A RowSchema column of exact decimal numbers, for money and other
+/// values that must not round-trip through f64. Unlike
+/// ParsedColumn::<Decimal>, values with more digits than a Decimal can
+/// hold are errors instead of being rounded. Decimal also implements
+/// FromStr, so parse_as::<Decimal> works for files of only decimals.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DecimalColumn {
+    scale: Option<u32>,
+}
+
+impl DecimalColumn {
+    /// Creates a column that accepts any number of decimal places.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a column that accepts at most the given number of decimal
+    /// places (ex. 2 for most currencies) and always writes exactly that
+    /// many.
+    pub fn with_scale(scale: u32) -> Self {
+        Self { scale: Some(scale) }
+    }
+
+    /// Formats a decimal the same way this column parses it, for use with
+    /// the WSVWriter.
+    pub fn format(&self, value: &Decimal) -> String {
+        match self.scale {
+            None => value.to_string(),
+            Some(scale) => format!("{:.*}", scale as usize, value),
+        }
+    }
+}
+
+impl ColumnConverter for DecimalColumn {
+    type Output = Decimal;
+
+    fn convert(&self, value: Option<&str>) -> Result<Self::Output, String> {
+        let value = value.ok_or_else(null_value)?;
+        let decimal = Decimal::from_str_exact(value).map_err(|err| err.to_string())?;
+        match self.scale {
+            Some(scale) if decimal.scale() > scale => {
+                Err(format!("More Than {} Decimal Places", scale))
+            }
+            _ => Ok(decimal),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+
+    use crate::{parse_as, DecimalColumn, RowSchema, TextColumn};
+
+    #[test]
+    fn converts_decimals_exactly() {
+        let amounts = DecimalColumn::with_scale(2);
+        let schema = RowSchema::new((TextColumn, amounts));
+        let rows = schema.parse("coffee 3.1\ntea 0.10").unwrap();
+        assert_eq!(Decimal::new(31, 1), rows[0].1);
+        assert_eq!("3.10", amounts.format(&rows[0].1));
+        assert_eq!("0.10", amounts.format(&rows[1].1));
+        assert_eq!(
+            "(line: 1, value: 2) Invalid Value (\"0.125\"): More Than 2 Decimal Places",
+            schema.parse("cake 0.125").unwrap_err().to_string()
+        );
+
+        let sum = parse_as::<Decimal>("0.1 0.2")
+            .unwrap()
+            .concat()
+            .into_iter()
+            .flatten()
+            .sum::<Decimal>();
+        assert_eq!("0.3", sum.to_string());
+    }
+}
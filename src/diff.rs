@@ -0,0 +1,477 @@
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt::Display;
+
+/// A single difference between two documents, as returned by diff and
+/// diff_by_key. Row numbers are 0 based indexes into the document they
+/// refer to. old_row is an index into the first (old) document and
+/// new_row is an index into the second (new) document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WSVDiff {
+    /// A row that is only in the new document.
+    RowAdded {
+        new_row: usize,
+        values: Vec<Option<String>>,
+    },
+    /// A row that is only in the old document.
+    RowRemoved {
+        old_row: usize,
+        values: Vec<Option<String>>,
+    },
+    /// A row whose position changed relative to the rows around it.
+    /// Only reported by diff_by_key.
+    RowMoved { old_row: usize, new_row: usize },
+    /// A cell whose value changed.
+    CellChanged {
+        old_row: usize,
+        new_row: usize,
+        column: usize,
+        old: Option<String>,
+        new: Option<String>,
+    },
+    /// A cell past the end of the old row.
+    CellAdded {
+        old_row: usize,
+        new_row: usize,
+        column: usize,
+        value: Option<String>,
+    },
+    /// A cell past the end of the new row.
+    CellRemoved {
+        old_row: usize,
+        new_row: usize,
+        column: usize,
+        value: Option<String>,
+    },
+}
+
+/// Compares two parsed documents cell by cell, matching rows up by their
+/// position. Rows past the end of the shorter document are reported as
+/// added or removed. Use diff_by_key if rows may have been reordered,
+/// inserted or deleted in the middle of the document.
+pub fn diff<RowA, StrA, RowB, StrB>(old: &[RowA], new: &[RowB]) -> Vec<WSVDiff>
+where
+    RowA: AsRef<[Option<StrA>]>,
+    StrA: AsRef<str>,
+    RowB: AsRef<[Option<StrB>]>,
+    StrB: AsRef<str>,
+{
+    let mut diffs = Vec::new();
+    for (row, old_values) in old.iter().enumerate().skip(new.len()) {
+        diffs.push(WSVDiff::RowRemoved {
+            old_row: row,
+            values: to_owned_row(old_values.as_ref()),
+        });
+    }
+    for (row, new_values) in new.iter().enumerate() {
+        match old.get(row) {
+            None => diffs.push(WSVDiff::RowAdded {
+                new_row: row,
+                values: to_owned_row(new_values.as_ref()),
+            }),
+            Some(old_values) => diff_cells(
+                row,
+                old_values.as_ref(),
+                row,
+                new_values.as_ref(),
+                &mut diffs,
+            ),
+        }
+    }
+    diffs
+}
+
+/// Compares two parsed documents cell by cell, matching rows up by the
+/// value in the given key column. This is the diff to use for tables with
+/// an id column, since inserting or sorting rows doesn't turn every row
+/// after it into a change. Rows whose key is missing or null never match
+/// any other row. If a key is used more than once, the first row with that
+/// key in the old document matches the first one in the new document, and
+/// so on.
+///
+/// Matched rows that changed position relative to the other matched rows
+/// are reported as moved. As few rows as possible are reported as moved.
+pub fn diff_by_key<RowA, StrA, RowB, StrB>(
+    old: &[RowA],
+    new: &[RowB],
+    key_column: usize,
+) -> Vec<WSVDiff>
+where
+    RowA: AsRef<[Option<StrA>]>,
+    StrA: AsRef<str>,
+    RowB: AsRef<[Option<StrB>]>,
+    StrB: AsRef<str>,
+{
+    let mut old_rows_by_key = HashMap::<&str, VecDeque<usize>>::new();
+    for (row, values) in old.iter().enumerate() {
+        if let Some(key) = key_of(values.as_ref(), key_column) {
+            old_rows_by_key.entry(key).or_default().push_back(row);
+        }
+    }
+
+    // (old_row, new_row) pairs in the order of the new document.
+    let mut matches = Vec::new();
+    let mut matched_old = vec![false; old.len()];
+    for (row, values) in new.iter().enumerate() {
+        let old_row = key_of(values.as_ref(), key_column)
+            .and_then(|key| old_rows_by_key.get_mut(key)?.pop_front());
+        if let Some(old_row) = old_row {
+            matched_old[old_row] = true;
+            matches.push((old_row, row));
+        }
+    }
+
+    let mut diffs = Vec::new();
+    for (row, values) in old.iter().enumerate() {
+        if !matched_old[row] {
+            diffs.push(WSVDiff::RowRemoved {
+                old_row: row,
+                values: to_owned_row(values.as_ref()),
+            });
+        }
+    }
+
+    let in_order = longest_increasing(&matches);
+    let mut matches = matches.into_iter().zip(in_order).peekable();
+    for (row, values) in new.iter().enumerate() {
+        match matches.next_if(|((_, new_row), _)| *new_row == row) {
+            None => diffs.push(WSVDiff::RowAdded {
+                new_row: row,
+                values: to_owned_row(values.as_ref()),
+            }),
+            Some(((old_row, new_row), in_order)) => {
+                if !in_order {
+                    diffs.push(WSVDiff::RowMoved { old_row, new_row });
+                }
+                diff_cells(
+                    old_row,
+                    old[old_row].as_ref(),
+                    new_row,
+                    values.as_ref(),
+                    &mut diffs,
+                );
+            }
+        }
+    }
+    diffs
+}
+
+/// Applies diffs made by diff or diff_by_key to the old document they were
+/// made from, giving back the new document. Every diff is checked against
+/// the document, so applying diffs to a document that has since changed
+/// fails instead of silently producing garbage.
+pub fn apply<Row, Str>(
+    old: &[Row],
+    diffs: &[WSVDiff],
+) -> Result<Vec<Vec<Option<String>>>, WSVPatchError>
+where
+    Row: AsRef<[Option<Str>]>,
+    Str: AsRef<str>,
+{
+    let mut rows = old
+        .iter()
+        .map(|row| Some(to_owned_row(row.as_ref())))
+        .collect::<Vec<_>>();
+    let mut truncate_at = vec![None; old.len()];
+    let mut placed = HashMap::new();
+    let mut new_len = old.len();
+
+    for diff in diffs {
+        let mismatch = || WSVPatchError::DoesNotMatch(diff.clone());
+        match diff {
+            WSVDiff::RowAdded { new_row, values } => {
+                new_len += 1;
+                placed.insert(*new_row, values.clone());
+            }
+            WSVDiff::RowRemoved { old_row, values } => {
+                let row = rows.get_mut(*old_row).ok_or_else(mismatch)?;
+                if row.as_ref() != Some(values) {
+                    return Err(mismatch());
+                }
+                *row = None;
+                new_len -= 1;
+            }
+            // Moved rows are placed once their cells have been patched.
+            WSVDiff::RowMoved { old_row, .. } => {
+                if rows.get(*old_row).is_none() {
+                    return Err(mismatch());
+                }
+            }
+            WSVDiff::CellChanged {
+                old_row,
+                column,
+                old,
+                new,
+                ..
+            } => {
+                let value = rows
+                    .get_mut(*old_row)
+                    .and_then(|row| row.as_mut()?.get_mut(*column))
+                    .filter(|value| *value == old)
+                    .ok_or_else(mismatch)?;
+                *value = new.clone();
+            }
+            WSVDiff::CellAdded {
+                old_row,
+                column,
+                value,
+                ..
+            } => {
+                let row = rows
+                    .get_mut(*old_row)
+                    .and_then(|row| row.as_mut())
+                    .filter(|row| row.len() == *column)
+                    .ok_or_else(mismatch)?;
+                row.push(value.clone());
+            }
+            WSVDiff::CellRemoved {
+                old_row,
+                column,
+                value,
+                ..
+            } => {
+                rows.get(*old_row)
+                    .and_then(|row| row.as_ref()?.get(*column))
+                    .filter(|current| *current == value)
+                    .ok_or_else(mismatch)?;
+                let truncate_at = &mut truncate_at[*old_row];
+                *truncate_at = Some(truncate_at.unwrap_or(*column).min(*column));
+            }
+        }
+    }
+
+    for (row, truncate_at) in rows.iter_mut().zip(truncate_at) {
+        if let (Some(row), Some(truncate_at)) = (row, truncate_at) {
+            row.truncate(truncate_at);
+        }
+    }
+    for diff in diffs {
+        if let WSVDiff::RowMoved { old_row, new_row } = diff {
+            let row = rows[*old_row]
+                .take()
+                .ok_or_else(|| WSVPatchError::DoesNotMatch(diff.clone()))?;
+            placed.insert(*new_row, row);
+        }
+    }
+
+    // Every row that wasn't placed by a diff keeps its relative order.
+    let mut stable = rows.into_iter().flatten();
+    let mut result = Vec::with_capacity(new_len);
+    for new_row in 0..new_len {
+        match placed.remove(&new_row) {
+            Some(row) => result.push(row),
+            None => result.push(stable.next().ok_or(WSVPatchError::WrongRowCount)?),
+        }
+    }
+    if stable.next().is_some() || !placed.is_empty() {
+        return Err(WSVPatchError::WrongRowCount);
+    }
+    Ok(result)
+}
+
+/// An error from apply.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WSVPatchError {
+    /// The diff refers to a row or value that is not in the document.
+    DoesNotMatch(WSVDiff),
+    /// The diffs describe a document with a different number of rows.
+    WrongRowCount,
+}
+
+impl Display for WSVPatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WSVPatchError::DoesNotMatch(diff) => {
+                write!(f, "Diff Does Not Match The Document ({:?})", diff)
+            }
+            WSVPatchError::WrongRowCount => write!(f, "Diffs Do Not Match The Row Count"),
+        }
+    }
+}
+
+impl Error for WSVPatchError {}
+
+fn key_of<Str: AsRef<str>>(row: &[Option<Str>], key_column: usize) -> Option<&str> {
+    row.get(key_column)?.as_ref().map(|key| key.as_ref())
+}
+
+fn diff_cells<StrA, StrB>(
+    old_row: usize,
+    old: &[Option<StrA>],
+    new_row: usize,
+    new: &[Option<StrB>],
+    diffs: &mut Vec<WSVDiff>,
+) where
+    StrA: AsRef<str>,
+    StrB: AsRef<str>,
+{
+    for column in 0..old.len().max(new.len()) {
+        let old_value = old
+            .get(column)
+            .map(|value| value.as_ref().map(|v| v.as_ref()));
+        let new_value = new
+            .get(column)
+            .map(|value| value.as_ref().map(|v| v.as_ref()));
+        match (old_value, new_value) {
+            (Some(old), Some(new)) if old != new => diffs.push(WSVDiff::CellChanged {
+                old_row,
+                new_row,
+                column,
+                old: old.map(str::to_string),
+                new: new.map(str::to_string),
+            }),
+            (None, Some(value)) => diffs.push(WSVDiff::CellAdded {
+                old_row,
+                new_row,
+                column,
+                value: value.map(str::to_string),
+            }),
+            (Some(value), None) => diffs.push(WSVDiff::CellRemoved {
+                old_row,
+                new_row,
+                column,
+                value: value.map(str::to_string),
+            }),
+            _ => {}
+        }
+    }
+}
+
+/// Marks which of the (old_row, new_row) pairs are part of the longest
+/// run of pairs whose old rows are still in increasing order. Those rows
+/// kept their relative order, and every other pair is a moved row.
+fn longest_increasing(pairs: &[(usize, usize)]) -> Vec<bool> {
+    // Patience sorting: tails[len] is the index of the pair that ends the
+    // best increasing run of length len + 1 found so far.
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous = vec![None; pairs.len()];
+    for (i, (old_row, _)) in pairs.iter().enumerate() {
+        let len = tails.partition_point(|tail| pairs[*tail].0 < *old_row);
+        if len > 0 {
+            previous[i] = Some(tails[len - 1]);
+        }
+        if len == tails.len() {
+            tails.push(i);
+        } else {
+            tails[len] = i;
+        }
+    }
+
+    let mut in_order = vec![false; pairs.len()];
+    let mut current = tails.last().copied();
+    while let Some(i) = current {
+        in_order[i] = true;
+        current = previous[i];
+    }
+    in_order
+}
+
+fn to_owned_value<Str: AsRef<str>>(value: &Option<Str>) -> Option<String> {
+    value.as_ref().map(|value| value.as_ref().to_string())
+}
+
+fn to_owned_row<Str: AsRef<str>>(row: &[Option<Str>]) -> Vec<Option<String>> {
+    row.iter().map(to_owned_value).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{apply, diff, diff_by_key, parse, WSVDiff, WSVPatchError};
+
+    #[test]
+    fn diffs_by_position() {
+        let old = parse("a b c\n1 2\nx").unwrap();
+        let new = parse("a B c\n1 2 3").unwrap();
+        assert_eq!(
+            vec![
+                WSVDiff::RowRemoved {
+                    old_row: 2,
+                    values: vec![Some("x".to_string())]
+                },
+                WSVDiff::CellChanged {
+                    old_row: 0,
+                    new_row: 0,
+                    column: 1,
+                    old: Some("b".to_string()),
+                    new: Some("B".to_string())
+                },
+                WSVDiff::CellAdded {
+                    old_row: 1,
+                    new_row: 1,
+                    column: 2,
+                    value: Some("3".to_string())
+                },
+            ],
+            diff(&old, &new)
+        );
+        assert!(diff(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn diffs_by_key() {
+        let old = parse("id name\n1 ann\n2 bob\n3 cy\n4 dee\n- nokey").unwrap();
+        let new = parse("id name\n4 dee\n1 ann\n3 -\n5 eve").unwrap();
+        assert_eq!(
+            vec![
+                WSVDiff::RowRemoved {
+                    old_row: 2,
+                    values: vec![Some("2".to_string()), Some("bob".to_string())]
+                },
+                WSVDiff::RowRemoved {
+                    old_row: 5,
+                    values: vec![None, Some("nokey".to_string())]
+                },
+                WSVDiff::RowMoved {
+                    old_row: 4,
+                    new_row: 1
+                },
+                WSVDiff::CellChanged {
+                    old_row: 3,
+                    new_row: 3,
+                    column: 1,
+                    old: Some("cy".to_string()),
+                    new: None
+                },
+                WSVDiff::RowAdded {
+                    new_row: 4,
+                    values: vec![Some("5".to_string()), Some("eve".to_string())]
+                },
+            ],
+            diff_by_key(&old, &new, 0)
+        );
+    }
+
+    #[test]
+    fn applies_diffs() {
+        let old = parse("id name\n1 ann\n2 bob\n3 cy x\n4 dee\n- nokey").unwrap();
+        let new = parse("id name\n4 dee\n1 ann\n5 eve\n3 - y z\n2").unwrap();
+        for diffs in [diff(&old, &new), diff_by_key(&old, &new, 0)] {
+            let patched = apply(&old, &diffs).unwrap();
+            assert_eq!(
+                new.iter()
+                    .map(|row| row.iter().map(|v| v.as_deref()).collect::<Vec<_>>())
+                    .collect::<Vec<_>>(),
+                patched
+                    .iter()
+                    .map(|row| row.iter().map(|v| v.as_deref()).collect::<Vec<_>>())
+                    .collect::<Vec<_>>()
+            );
+        }
+
+        let diffs = diff_by_key(&old, &new, 0);
+        assert!(matches!(
+            apply(&new, &diffs),
+            Err(WSVPatchError::DoesNotMatch(_))
+        ));
+        assert_eq!(
+            Err(WSVPatchError::WrongRowCount),
+            apply(
+                &old[..1],
+                &[WSVDiff::RowAdded {
+                    new_row: 5,
+                    values: Vec::new()
+                }]
+            )
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use sha2::{Digest, Sha256};
+
+use crate::canonical::content_rows;
+use crate::{write_packed_row, WSVError, WriterOptions};
+
+/// Computes a SHA-256 hash of the values in the rows, which only changes
+/// when the data does. The hash is taken over the canonical form (see
+/// canonicalize). digest_source leaves out lines that hold nothing but a
+/// comment (and whitespace), so it matches this for the rows parse gives
+/// once those comment-only rows are removed. Blank lines are still empty
+/// rows on both sides.
+pub fn digest<Row, S>(rows: &[Row]) -> [u8; 32]
+where
+    Row: AsRef<[Option<S>]>,
+    S: AsRef<str>,
+{
+    let options = WriterOptions::default();
+    let mut hasher = Sha256::new();
+    let mut line = String::new();
+    for row in rows {
+        line.clear();
+        write_packed_row(&mut line, row.as_ref(), &options);
+        line.push('\n');
+        hasher.update(line.as_bytes());
+    }
+    hasher.finalize().into()
+}
+
+/// Same as digest, but for WSV text. Alignment, quoting and comments
+/// make no difference to the hash. Fails if the text can't be parsed.
+pub fn digest_source(source_text: &str) -> Result<[u8; 32], WSVError> {
+    Ok(digest(&content_rows(source_text)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{canonicalize, digest, digest_source};
+
+    #[test]
+    fn hashes_values_only() {
+        let aligned = "# prices\nsku   price\n\"a1\"  \"1.50\" # sale\nb2    -\n";
+        let packed = "sku price\na1 1.50\nb2 -";
+        assert_eq!(
+            digest_source(aligned).unwrap(),
+            digest_source(packed).unwrap()
+        );
+        assert_eq!(
+            digest_source(aligned).unwrap(),
+            digest(&[
+                vec![Some("sku"), Some("price")],
+                vec![Some("a1"), Some("1.50")],
+                vec![Some("b2"), None],
+            ])
+        );
+        assert_ne!(
+            digest_source(packed).unwrap(),
+            digest_source("sku price\na1 1.50\nb2 \"-\"").unwrap()
+        );
+
+        // The hash is the SHA-256 of the canonical form.
+        assert_eq!(
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            digest_source(&canonicalize("# empty").unwrap())
+                .unwrap()
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>()
+        );
+    }
+}
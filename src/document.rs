@@ -0,0 +1,899 @@
+use std::error::Error;
+use std::fmt::Display;
+
+use std::mem::take;
+
+use crate::{
+    check_column_order, move_column_order, reorder_values, WSVError, WSVToken, WSVTokenizer,
+    WSVWriter, WriterOptions,
+};
+
+/// An owned, editable table of WSV values. Every row of a document has
+/// the same number of columns: rows that are added short are padded
+/// with nulls, and rows that are added long widen every other row, so
+/// edits can't leave the table jagged by accident.
+///
+/// Comments belong to the rows they describe, so they move with their
+/// row when rows are added or removed around it. The comment lines
+/// directly above a row are its leading comments and the comment at the
+/// end of its line is its trailing comment. Comment lines at the top of
+/// the file that are followed by a blank line belong to the document,
+/// as do the ones after the last row. Blank lines are not kept.
+///
+/// Every edit is recorded, so it can be taken back with undo and made
+/// again with redo. Two documents are equal if they have the same rows
+/// and comments, whatever their histories.
+#[derive(Debug, Clone, Default)]
+pub struct WSVDocument {
+    rows: Vec<Vec<Option<String>>>,
+    row_comments: Vec<WSVRowComments>,
+    column_count: usize,
+    comments: Vec<String>,
+    end_comments: Vec<String>,
+    undo: Vec<DocumentEdit>,
+    redo: Vec<DocumentEdit>,
+}
+
+/// The comments attached to a row of a WSVDocument, without their '#'.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WSVRowComments {
+    /// The comment lines directly above the row.
+    pub leading: Vec<String>,
+    /// The comment at the end of the row's line.
+    pub trailing: Option<String>,
+}
+
+/// A single change to a WSVDocument, with enough information to make
+/// it or take it back.
+#[derive(Debug, Clone)]
+enum DocumentEdit {
+    InsertRow {
+        index: usize,
+        row: Vec<Option<String>>,
+        /// The column count before and after the insert, which differ if
+        /// the row widened the document.
+        column_counts: (usize, usize),
+    },
+    RemoveRow {
+        index: usize,
+        row: Vec<Option<String>>,
+        comments: WSVRowComments,
+    },
+    InsertColumn {
+        index: usize,
+        default: Option<String>,
+    },
+    RemoveColumn {
+        index: usize,
+        values: Vec<Option<String>>,
+    },
+    Set {
+        row: usize,
+        col: usize,
+        before: Option<String>,
+        after: Option<String>,
+    },
+    /// order[i] is the index the column at i had before.
+    ReorderColumns { order: Vec<usize> },
+    SetRowComments {
+        row: usize,
+        before: WSVRowComments,
+        after: WSVRowComments,
+    },
+    /// Replaces the comments at the top of the document, or the ones at
+    /// the end if end is set.
+    SetComments {
+        end: bool,
+        before: Vec<String>,
+        after: Vec<String>,
+    },
+}
+
+impl WSVDocument {
+    /// Creates an empty document with no rows or columns.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a document from the given rows, padding short rows with
+    /// nulls to the width of the widest one.
+    pub fn from_rows(rows: Vec<Vec<Option<String>>>) -> Self {
+        let mut document = Self {
+            column_count: rows.iter().map(Vec::len).max().unwrap_or(0),
+            row_comments: vec![WSVRowComments::default(); rows.len()],
+            rows,
+            ..Self::default()
+        };
+        for row in document.rows.iter_mut() {
+            row.resize(document.column_count, None);
+        }
+        document
+    }
+
+    /// Parses the source text into a document, keeping its comments.
+    /// Blank and comment-only lines don't become rows. See parse for
+    /// other details.
+    pub fn parse(source_text: &str) -> Result<Self, WSVError> {
+        let mut rows = Vec::new();
+        let mut row_comments = Vec::new();
+        let mut comments = None;
+        let mut pending = Vec::new();
+        let mut values = Vec::new();
+        let mut comment = None;
+        let mut tokens = WSVTokenizer::new(source_text);
+        loop {
+            let token = tokens.next().transpose()?;
+            match token {
+                Some(WSVToken::Null) => values.push(None),
+                Some(WSVToken::Value(value)) => values.push(Some(value.into_owned())),
+                Some(WSVToken::Comment(text)) => comment = Some(text.to_string()),
+                Some(WSVToken::LF) | None => {
+                    if !values.is_empty() {
+                        rows.push(take(&mut values));
+                        row_comments.push(WSVRowComments {
+                            leading: take(&mut pending),
+                            trailing: comment.take(),
+                        });
+                    } else if let Some(comment) = comment.take() {
+                        pending.push(comment);
+                    } else if rows.is_empty() && comments.is_none() && !pending.is_empty() {
+                        // A blank line ends the comments at the top.
+                        comments = Some(take(&mut pending));
+                    }
+                    if token.is_none() {
+                        break;
+                    }
+                }
+            }
+        }
+
+        let mut document = Self::from_rows(rows);
+        document.row_comments = row_comments;
+        document.comments = comments.unwrap_or_default();
+        document.end_comments = pending;
+        Ok(document)
+    }
+
+    /// The number of rows.
+    pub fn row_count(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// The number of columns, which is the same for every row.
+    pub fn column_count(&self) -> usize {
+        self.column_count
+    }
+
+    /// All of the rows, in order.
+    pub fn rows(&self) -> &[Vec<Option<String>>] {
+        &self.rows
+    }
+
+    /// Inserts a row at the given index, shifting the rows after it
+    /// down. The row is padded with nulls if it is short, and every
+    /// other row is if it is long.
+    ///
+    /// Panics if the index is greater than the row count.
+    pub fn insert_row(&mut self, index: usize, mut row: Vec<Option<String>>) {
+        assert!(
+            index <= self.rows.len(),
+            "row index {} out of range for a document with {} rows",
+            index,
+            self.rows.len()
+        );
+        let column_count = self.column_count.max(row.len());
+        row.resize(column_count, None);
+        self.edit(DocumentEdit::InsertRow {
+            index,
+            row,
+            column_counts: (self.column_count, column_count),
+        });
+    }
+
+    /// Adds a row after the last one. See insert_row.
+    pub fn push_row(&mut self, row: Vec<Option<String>>) {
+        self.insert_row(self.rows.len(), row);
+    }
+
+    /// Removes and returns the row at the given index.
+    ///
+    /// Panics if the index is out of range.
+    pub fn remove_row(&mut self, index: usize) -> Vec<Option<String>> {
+        assert!(
+            index < self.rows.len(),
+            "row index {} out of range for a document with {} rows",
+            index,
+            self.rows.len()
+        );
+        let row = self.rows[index].clone();
+        self.edit(DocumentEdit::RemoveRow {
+            index,
+            row: row.clone(),
+            comments: self.row_comments[index].clone(),
+        });
+        row
+    }
+
+    /// Inserts a column at the given index, shifting the columns after
+    /// it right. Every row gets the default value in the new column.
+    ///
+    /// Panics if the index is greater than the column count.
+    pub fn insert_column(&mut self, index: usize, default: Option<String>) {
+        assert!(
+            index <= self.column_count,
+            "column index {} out of range for a document with {} columns",
+            index,
+            self.column_count
+        );
+        self.edit(DocumentEdit::InsertColumn { index, default });
+    }
+
+    /// Removes the column at the given index and returns its values,
+    /// one per row.
+    ///
+    /// Panics if the index is out of range.
+    pub fn remove_column(&mut self, index: usize) -> Vec<Option<String>> {
+        assert!(
+            index < self.column_count,
+            "column index {} out of range for a document with {} columns",
+            index,
+            self.column_count
+        );
+        let values = self
+            .rows
+            .iter()
+            .map(|row| row[index].clone())
+            .collect::<Vec<_>>();
+        self.edit(DocumentEdit::RemoveColumn {
+            index,
+            values: values.clone(),
+        });
+        values
+    }
+
+    /// Puts the columns in the given order. order[i] is the index the
+    /// column at i had before, so [2, 0, 1] moves the last column to the
+    /// front. When the first row holds the column names they are reordered
+    /// along with everything else. Writer options with column widths can be
+    /// kept in sync with WriterOptions::reorder_columns.
+    ///
+    /// Panics unless the order names each column exactly once.
+    pub fn reorder_columns(&mut self, order: &[usize]) {
+        check_column_order(order, self.column_count);
+        self.edit(DocumentEdit::ReorderColumns {
+            order: order.to_vec(),
+        });
+    }
+
+    /// Moves the column at from so it ends up at to, shifting the columns
+    /// in between over by one.
+    ///
+    /// Panics if either index is out of range.
+    pub fn move_column(&mut self, from: usize, to: usize) {
+        assert!(
+            from < self.column_count && to < self.column_count,
+            "column index {} out of range for a document with {} columns",
+            from.max(to),
+            self.column_count
+        );
+        self.reorder_columns(&move_column_order(self.column_count, from, to));
+    }
+
+    /// The comments attached to the row at the given index, or None if
+    /// it is out of range.
+    pub fn row_comments(&self, row: usize) -> Option<&WSVRowComments> {
+        self.row_comments.get(row)
+    }
+
+    /// Replaces the comments attached to a row and returns the old ones.
+    ///
+    /// Panics if the row is out of range or a comment contains a line
+    /// feed.
+    pub fn set_row_comments(&mut self, row: usize, comments: WSVRowComments) -> WSVRowComments {
+        assert!(
+            row < self.rows.len(),
+            "row index {} out of range for a document with {} rows",
+            row,
+            self.rows.len()
+        );
+        check_comments(comments.leading.iter().chain(&comments.trailing));
+        let before = self.row_comments[row].clone();
+        self.edit(DocumentEdit::SetRowComments {
+            row,
+            before: before.clone(),
+            after: comments,
+        });
+        before
+    }
+
+    /// The comment lines at the top of the document.
+    pub fn comments(&self) -> &[String] {
+        &self.comments
+    }
+
+    /// Replaces the comment lines at the top of the document. They are
+    /// written with a blank line after them, to keep them apart from the
+    /// first row's comments.
+    ///
+    /// Panics if a comment contains a line feed.
+    pub fn set_comments(&mut self, comments: Vec<String>) {
+        check_comments(&comments);
+        self.edit(DocumentEdit::SetComments {
+            end: false,
+            before: self.comments.clone(),
+            after: comments,
+        });
+    }
+
+    /// The comment lines after the last row.
+    pub fn end_comments(&self) -> &[String] {
+        &self.end_comments
+    }
+
+    /// Replaces the comment lines after the last row.
+    ///
+    /// Panics if a comment contains a line feed.
+    pub fn set_end_comments(&mut self, comments: Vec<String>) {
+        check_comments(&comments);
+        self.edit(DocumentEdit::SetComments {
+            end: true,
+            before: self.end_comments.clone(),
+            after: comments,
+        });
+    }
+
+    /// Gets the value of a cell. Null cells are Ok(None).
+    pub fn get(&self, row: usize, col: usize) -> Result<Option<&str>, WSVDocumentError> {
+        match self.rows.get(row).and_then(|cells| cells.get(col)) {
+            None => Err(self.out_of_range(row, col)),
+            Some(value) => Ok(value.as_deref()),
+        }
+    }
+
+    /// Sets the value of a cell and returns the value it replaced.
+    pub fn set(
+        &mut self,
+        row: usize,
+        col: usize,
+        value: Option<String>,
+    ) -> Result<Option<String>, WSVDocumentError> {
+        let before = self.get(row, col)?.map(str::to_string);
+        self.edit(DocumentEdit::Set {
+            row,
+            col,
+            before: before.clone(),
+            after: value,
+        });
+        Ok(before)
+    }
+
+    /// Same as get, but takes a spreadsheet style reference like "B3"
+    /// (column B, row 3). See parse_reference.
+    pub fn get_ref(&self, reference: &str) -> Result<Option<&str>, WSVDocumentError> {
+        let (row, col) = Self::parse_reference(reference)?;
+        self.get(row, col)
+    }
+
+    /// Same as set, but takes a spreadsheet style reference like "B3"
+    /// (column B, row 3). See parse_reference.
+    pub fn set_ref(
+        &mut self,
+        reference: &str,
+        value: Option<String>,
+    ) -> Result<Option<String>, WSVDocumentError> {
+        let (row, col) = Self::parse_reference(reference)?;
+        self.set(row, col, value)
+    }
+
+    /// Converts a spreadsheet style reference into a (row, column) pair
+    /// of 0 based indexes. Columns are lettered A to Z, then AA, AB and
+    /// so on, and rows are numbered from 1, so "A1" is (0, 0) and "AB10"
+    /// is (9, 27). Letters may be upper or lower case.
+    pub fn parse_reference(reference: &str) -> Result<(usize, usize), WSVDocumentError> {
+        let invalid = || WSVDocumentError::InvalidReference(reference.to_string());
+        let digits = reference
+            .find(|ch: char| ch.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let (letters, digits) = reference.split_at(digits);
+        if letters.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        let mut col = 0usize;
+        for letter in letters.chars() {
+            if !letter.is_ascii_alphabetic() {
+                return Err(invalid());
+            }
+            let value = (letter.to_ascii_uppercase() as u8 - b'A') as usize + 1;
+            col = col
+                .checked_mul(26)
+                .and_then(|col| col.checked_add(value))
+                .ok_or_else(invalid)?;
+        }
+        let row = digits.parse::<usize>().map_err(|_| invalid())?;
+        if row == 0 {
+            return Err(invalid());
+        }
+        Ok((row - 1, col - 1))
+    }
+
+    /// The spreadsheet style reference for a cell, the reverse of
+    /// parse_reference.
+    pub fn reference(row: usize, col: usize) -> String {
+        let mut letters = Vec::new();
+        let mut col = col + 1;
+        while col > 0 {
+            col -= 1;
+            letters.push((b'A' + (col % 26) as u8) as char);
+            col /= 26;
+        }
+        letters.iter().rev().collect::<String>() + &(row + 1).to_string()
+    }
+
+    /// Takes back the most recent edit that hasn't been undone yet.
+    /// Returns false if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let Some(edit) = self.undo.pop() else {
+            return false;
+        };
+        self.apply(&edit, false);
+        self.redo.push(edit);
+        true
+    }
+
+    /// Makes the most recently undone edit again. Returns false if there
+    /// was nothing to redo. Making a new edit after undoing clears the
+    /// edits that could have been redone.
+    pub fn redo(&mut self) -> bool {
+        let Some(edit) = self.redo.pop() else {
+            return false;
+        };
+        self.apply(&edit, true);
+        self.undo.push(edit);
+        true
+    }
+
+    /// Whether there is an edit to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Whether there is an edit to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Forgets every recorded edit, for example after the document has
+    /// been saved, to free the memory they hold.
+    pub fn clear_history(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// Makes a new edit and records it.
+    fn edit(&mut self, edit: DocumentEdit) {
+        self.apply(&edit, true);
+        self.undo.push(edit);
+        self.redo.clear();
+    }
+
+    /// Makes an edit (forward) or takes it back. Edits are only ever
+    /// applied to the document they were made on, in order, so they
+    /// always fit.
+    fn apply(&mut self, edit: &DocumentEdit, forward: bool) {
+        match (edit, forward) {
+            (
+                DocumentEdit::InsertRow {
+                    index,
+                    row,
+                    column_counts: (_, after),
+                },
+                true,
+            ) => {
+                self.resize_columns(*after);
+                self.rows.insert(*index, row.clone());
+                self.row_comments.insert(*index, WSVRowComments::default());
+            }
+            (
+                DocumentEdit::InsertRow {
+                    index,
+                    column_counts: (before, _),
+                    ..
+                },
+                false,
+            ) => {
+                self.rows.remove(*index);
+                self.row_comments.remove(*index);
+                self.resize_columns(*before);
+            }
+            (DocumentEdit::RemoveRow { index, .. }, true) => {
+                self.rows.remove(*index);
+                self.row_comments.remove(*index);
+            }
+            (
+                DocumentEdit::RemoveRow {
+                    index,
+                    row,
+                    comments,
+                },
+                false,
+            ) => {
+                self.rows.insert(*index, row.clone());
+                self.row_comments.insert(*index, comments.clone());
+            }
+            (DocumentEdit::InsertColumn { index, default }, true) => {
+                for row in self.rows.iter_mut() {
+                    row.insert(*index, default.clone());
+                }
+                self.column_count += 1;
+            }
+            (DocumentEdit::InsertColumn { index, .. }, false)
+            | (DocumentEdit::RemoveColumn { index, .. }, true) => {
+                for row in self.rows.iter_mut() {
+                    row.remove(*index);
+                }
+                self.column_count -= 1;
+            }
+            (DocumentEdit::RemoveColumn { index, values }, false) => {
+                for (row, value) in self.rows.iter_mut().zip(values) {
+                    row.insert(*index, value.clone());
+                }
+                self.column_count += 1;
+            }
+            (
+                DocumentEdit::Set {
+                    row,
+                    col,
+                    before,
+                    after,
+                },
+                forward,
+            ) => {
+                self.rows[*row][*col] = if forward { after } else { before }.clone();
+            }
+            (DocumentEdit::ReorderColumns { order }, forward) => {
+                let order = if forward {
+                    order.clone()
+                } else {
+                    let mut inverse = vec![0; order.len()];
+                    for (index, &column) in order.iter().enumerate() {
+                        inverse[column] = index;
+                    }
+                    inverse
+                };
+                for row in &mut self.rows {
+                    reorder_values(row, &order);
+                }
+            }
+            (DocumentEdit::SetRowComments { row, before, after }, forward) => {
+                self.row_comments[*row] = if forward { after } else { before }.clone();
+            }
+            (DocumentEdit::SetComments { end, before, after }, forward) => {
+                let comments = if *end {
+                    &mut self.end_comments
+                } else {
+                    &mut self.comments
+                };
+                *comments = if forward { after } else { before }.clone();
+            }
+        }
+    }
+
+    fn resize_columns(&mut self, column_count: usize) {
+        self.column_count = column_count;
+        for row in self.rows.iter_mut() {
+            row.resize(column_count, None);
+        }
+    }
+
+    fn out_of_range(&self, row: usize, col: usize) -> WSVDocumentError {
+        WSVDocumentError::OutOfRange {
+            row,
+            col,
+            row_count: self.rows.len(),
+            column_count: self.column_count,
+        }
+    }
+
+    /// Writes the document as packed WSV.
+    pub fn to_wsv(&self) -> String {
+        self.to_wsv_with_options(WriterOptions::default())
+    }
+
+    /// Writes the document and its comments as WSV with the given writer
+    /// options.
+    pub fn to_wsv_with_options(&self, options: WriterOptions) -> String {
+        let line_ending = options.line_ending;
+        let trailing_line_ending = options.trailing_line_ending;
+        let body = WSVWriter::new(
+            self.rows
+                .iter()
+                .map(|row| row.iter().map(|value| value.as_deref())),
+        )
+        .with_options(options.trailing_line_ending(false))
+        .to_string();
+        // Values can't contain line breaks once written, so there is
+        // exactly one line per row.
+        let row_lines = body.split(line_ending.as_str()).take(self.rows.len());
+
+        let mut lines = Vec::new();
+        for comment in &self.comments {
+            lines.push(format!("#{}", comment));
+        }
+        if !self.comments.is_empty() {
+            lines.push(String::new());
+        }
+        for (line, comments) in row_lines.zip(&self.row_comments) {
+            for comment in &comments.leading {
+                lines.push(format!("#{}", comment));
+            }
+            match &comments.trailing {
+                None => lines.push(line.trim_end().to_string()),
+                Some(comment) => lines.push(format!("{} #{}", line.trim_end(), comment)),
+            }
+        }
+        for comment in &self.end_comments {
+            lines.push(format!("#{}", comment));
+        }
+
+        let mut result = lines.join(line_ending.as_str());
+        if trailing_line_ending && !lines.is_empty() {
+            result.push_str(line_ending.as_str());
+        }
+        result
+    }
+
+    /// Returns the rows, giving up the document.
+    pub fn into_rows(self) -> Vec<Vec<Option<String>>> {
+        self.rows
+    }
+}
+
+/// Panics if any of the comments can't be written on a single line.
+fn check_comments<'a>(comments: impl IntoIterator<Item = &'a String>) {
+    for comment in comments {
+        assert!(!comment.contains('\n'), "invalid comment {:?}", comment);
+    }
+}
+
+impl PartialEq for WSVDocument {
+    fn eq(&self, other: &Self) -> bool {
+        self.column_count == other.column_count
+            && self.rows == other.rows
+            && self.row_comments == other.row_comments
+            && self.comments == other.comments
+            && self.end_comments == other.end_comments
+    }
+}
+
+impl Eq for WSVDocument {}
+
+/// An error from addressing a cell of a WSVDocument.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WSVDocumentError {
+    /// The cell at the given (0 based) row and column is outside of the
+    /// document, which has the given dimensions.
+    OutOfRange {
+        row: usize,
+        col: usize,
+        row_count: usize,
+        column_count: usize,
+    },
+    /// The text isn't a spreadsheet style cell reference like "B3".
+    InvalidReference(String),
+}
+
+impl Display for WSVDocumentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WSVDocumentError::OutOfRange {
+                row,
+                col,
+                row_count,
+                column_count,
+            } => write!(
+                f,
+                "(line: {}, value: {}) Cell Out Of Range ({} rows, {} columns)",
+                row + 1,
+                col + 1,
+                row_count,
+                column_count
+            ),
+            WSVDocumentError::InvalidReference(reference) => {
+                write!(f, "Invalid Cell Reference ({:?})", reference)
+            }
+        }
+    }
+}
+
+impl Error for WSVDocumentError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        measure_columns, parse, ColumnAlignment, WSVDocument, WSVDocumentError, WSVRowComments,
+        WriterOptions,
+    };
+
+    #[test]
+    fn edits_keep_documents_rectangular() {
+        let mut document = WSVDocument::parse("id name\n1 ann\n2").unwrap();
+        assert_eq!((3, 2), (document.row_count(), document.column_count()));
+        assert_eq!(None, document.rows()[2][1]);
+
+        document.insert_row(1, vec![Some("0".to_string())]);
+        document.push_row(vec![
+            Some("3".to_string()),
+            Some("cy".to_string()),
+            Some("extra".to_string()),
+        ]);
+        assert_eq!(3, document.column_count());
+        assert!(document.rows().iter().all(|row| row.len() == 3));
+
+        document.insert_column(1, Some("x".to_string()));
+        assert_eq!(Some("x"), document.rows()[3][1].as_deref());
+        assert_eq!(
+            vec![
+                Some("name".to_string()),
+                None,
+                Some("ann".to_string()),
+                None,
+                Some("cy".to_string())
+            ],
+            document.remove_column(2)
+        );
+        assert_eq!(
+            Ok(Some("x".to_string())),
+            document.set(0, 1, Some("flag".to_string()))
+        );
+        assert_eq!(Some("0".to_string()), document.remove_row(1)[0]);
+
+        assert_eq!(Some("extra"), document.rows()[3][2].as_deref());
+        assert_eq!(document, WSVDocument::parse(&document.to_wsv()).unwrap());
+        let aligned =
+            document.to_wsv_with_options(WriterOptions::new().alignment(ColumnAlignment::Left));
+        assert_eq!(document.rows().len(), parse(&aligned).unwrap().len());
+    }
+
+    #[test]
+    fn undoes_and_redoes_edits() {
+        let original = WSVDocument::parse("a b\nc d").unwrap();
+        let mut document = original.clone();
+        assert!(!document.undo());
+
+        document.set(0, 0, Some("x".to_string())).unwrap();
+        document.push_row(vec![None, None, Some("wide".to_string())]);
+        document.remove_column(1);
+        document.insert_column(0, Some("new".to_string()));
+        document.remove_row(0);
+        let edited = document.clone();
+        assert_eq!(
+            vec![
+                vec![Some("new".to_string()), Some("c".to_string()), None],
+                vec![Some("new".to_string()), None, Some("wide".to_string())],
+            ],
+            edited.rows()
+        );
+
+        while document.undo() {}
+        assert_eq!(original, document);
+        assert_eq!(2, document.column_count());
+        assert!(!document.can_undo());
+
+        while document.redo() {}
+        assert_eq!(edited, document);
+
+        document.undo();
+        document.set(0, 0, None).unwrap();
+        assert!(!document.can_redo());
+        document.clear_history();
+        assert!(!document.can_undo());
+    }
+
+    #[test]
+    fn keeps_comments_with_their_rows() {
+        let input = "# people.wsv\n# owned by ops\n\n# the header\nid name # names are unique\n\n1 ann\n# retired\n2 bob # on leave\n# end of file";
+        let mut document = WSVDocument::parse(input).unwrap();
+        assert_eq!(3, document.row_count());
+        assert_eq!([" people.wsv", " owned by ops"], document.comments());
+        assert_eq!([" end of file"], document.end_comments());
+        assert_eq!(
+            &WSVRowComments {
+                leading: vec![" the header".to_string()],
+                trailing: Some(" names are unique".to_string()),
+            },
+            document.row_comments(0).unwrap()
+        );
+
+        document.remove_row(1);
+        document.insert_row(1, vec![Some("0".to_string()), None]);
+        assert_eq!(
+            Some(" on leave"),
+            document.row_comments(2).unwrap().trailing.as_deref()
+        );
+        assert_eq!(
+            "# people.wsv\n# owned by ops\n\n# the header\nid name # names are unique\n0 -\n# retired\n2 bob # on leave\n# end of file",
+            document.to_wsv()
+        );
+        assert_eq!(document, WSVDocument::parse(&document.to_wsv()).unwrap());
+
+        document.set_row_comments(
+            1,
+            WSVRowComments {
+                leading: vec!["added".to_string()],
+                trailing: None,
+            },
+        );
+        document.set_comments(Vec::new());
+        assert!(document.to_wsv().starts_with("# the header\n"));
+        document.undo();
+        document.undo();
+        document.undo();
+        document.undo();
+        assert_eq!(
+            WSVDocument::parse(input).unwrap().to_wsv(),
+            document.to_wsv()
+        );
+    }
+
+    #[test]
+    fn reorders_columns() {
+        let mut document = WSVDocument::parse("id name age\n1 ann 30\n2 \"bob b\" 4").unwrap();
+        let options = WriterOptions::new()
+            .alignment(ColumnAlignment::Left)
+            .column_widths(measure_columns(document.rows(), &WriterOptions::default()));
+
+        document.reorder_columns(&[1, 2, 0]);
+        assert_eq!(Some("ann"), document.get(1, 0).unwrap());
+        let aligned = document.to_wsv_with_options(options.clone().reorder_columns(&[1, 2, 0]));
+        assert_eq!("name    age id\nann     30  1\n\"bob b\" 4   2", aligned);
+
+        document.move_column(2, 0);
+        assert_eq!("id name age", document.to_wsv().lines().next().unwrap());
+        document.move_column(0, 1);
+        assert_eq!("name id age", document.to_wsv().lines().next().unwrap());
+        document.undo();
+        document.undo();
+        document.undo();
+        assert_eq!("id name age", document.to_wsv().lines().next().unwrap());
+        document.redo();
+        assert_eq!(Some("30"), document.get(1, 1).unwrap());
+    }
+
+    #[test]
+    fn addresses_cells() {
+        let mut document = WSVDocument::parse("a b\nc -").unwrap();
+        assert_eq!(Ok(Some("b")), document.get(0, 1));
+        assert_eq!(Ok(None), document.get_ref("B2"));
+        assert_eq!(Ok(Some("c".to_string())), document.set_ref("a2", None));
+
+        let err = document.set(2, 0, None).unwrap_err();
+        assert_eq!(
+            WSVDocumentError::OutOfRange {
+                row: 2,
+                col: 0,
+                row_count: 2,
+                column_count: 2,
+            },
+            err
+        );
+        assert_eq!(
+            "(line: 3, value: 1) Cell Out Of Range (2 rows, 2 columns)",
+            err.to_string()
+        );
+        assert!(document.get_ref("C1").is_err());
+
+        for (reference, cell) in [("A1", (0, 0)), ("Z3", (2, 25)), ("AB10", (9, 27))] {
+            assert_eq!(Ok(cell), WSVDocument::parse_reference(reference));
+            assert_eq!(reference, WSVDocument::reference(cell.0, cell.1));
+        }
+        assert_eq!("ZZ1", WSVDocument::reference(0, 701));
+        assert_eq!("AAA1", WSVDocument::reference(0, 702));
+        for invalid in ["", "A", "1", "A0", "1A", "A1B", "Ä1"] {
+            assert_eq!(
+                Err(WSVDocumentError::InvalidReference(invalid.to_string())),
+                WSVDocument::parse_reference(invalid)
+            );
+        }
+    }
+}
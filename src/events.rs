@@ -0,0 +1,231 @@
+use std::collections::VecDeque;
+
+use crate::scanner::Scanner;
+use crate::{WSVError, WSVErrorType, WSVTokenizer, NEWLINE};
+
+/// Creates an iterator over the low-level token events of a .wsv
+/// (whitespace separated value) file. This is intended for building
+/// other parsers (for example an SML parser or a syntax tree builder)
+/// on top of this crate without re-tokenizing the input. Every event
+/// carries byte positions into the source text, so the raw text of any
+/// value or comment can be sliced out of the source directly.
+///
+/// For example, `a "b""c" # note` produces the events:
+/// ValueStart, ValueEnd, ValueStart, EscapedQuote, ValueEnd, CommentStart, CommentEnd
+pub fn token_events(source_text: &str) -> WSVTokenEvents<'_> {
+    WSVTokenEvents {
+        scanner: Scanner::new(source_text),
+        in_string: false,
+        pending: VecDeque::new(),
+        errored: false,
+    }
+}
+
+/// A single low-level event from a WSV source. All positions are
+/// byte indexes into the source text. End positions are exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum WSVTokenEvent {
+    /// The start of a value. For quoted values, the position is that of
+    /// the opening quote.
+    ValueStart { position: usize, quoted: bool },
+    /// The end of a value. For quoted values, this is just after the
+    /// closing quote.
+    ValueEnd { position: usize },
+    /// A `""` escape sequence inside a quoted value.
+    EscapedQuote { position: usize },
+    /// A `"/"` escape sequence inside a quoted value.
+    EscapedNewline { position: usize },
+    /// A null value ('-').
+    Null { position: usize },
+    /// The '#' that starts a comment.
+    CommentStart { position: usize },
+    /// The end of a comment.
+    CommentEnd { position: usize },
+    /// A line feed character.
+    LineFeed { position: usize },
+}
+
+/// An iterator over the token events of a WSV source.
+/// See token_events for details.
+pub struct WSVTokenEvents<'wsv> {
+    scanner: Scanner<'wsv>,
+    in_string: bool,
+    pending: VecDeque<Result<WSVTokenEvent, WSVError>>,
+    errored: bool,
+}
+
+impl<'wsv> WSVTokenEvents<'wsv> {
+    fn next_in_string(&mut self) -> Result<WSVTokenEvent, WSVError> {
+        loop {
+            match self.scanner.peek() {
+                None | Some(NEWLINE) => return Err(self.scanner.string_not_closed()),
+                Some('"') => {
+                    let position = self.scanner.position();
+                    self.scanner.next();
+                    match self.scanner.peek() {
+                        Some('"') => {
+                            self.scanner.next();
+                            return Ok(WSVTokenEvent::EscapedQuote { position });
+                        }
+                        Some('/') => {
+                            self.scanner.next();
+                            if let Some('"') = self.scanner.peek() {
+                                self.scanner.next();
+                                return Ok(WSVTokenEvent::EscapedNewline { position });
+                            }
+                            return Err(self.scanner.error(WSVErrorType::InvalidStringLineBreak));
+                        }
+                        lookahead => {
+                            self.in_string = false;
+                            let ch = lookahead.unwrap_or(NEWLINE);
+                            if ch != NEWLINE && ch != '#' && !WSVTokenizer::is_whitespace(ch) {
+                                self.pending.push_back(Err(self
+                                    .scanner
+                                    .error(WSVErrorType::InvalidCharacterAfterString)));
+                            }
+                            return Ok(WSVTokenEvent::ValueEnd {
+                                position: self.scanner.position(),
+                            });
+                        }
+                    }
+                }
+                Some(_) => {
+                    self.scanner.next();
+                }
+            }
+        }
+    }
+
+    fn next_outside_string(&mut self) -> Option<Result<WSVTokenEvent, WSVError>> {
+        self.scanner.skip_while(WSVTokenizer::is_whitespace);
+        let position = self.scanner.position();
+        match self.scanner.peek()? {
+            NEWLINE => {
+                self.scanner.next();
+                Some(Ok(WSVTokenEvent::LineFeed { position }))
+            }
+            '#' => {
+                self.scanner.skip_while(|ch| ch != NEWLINE);
+                self.pending.push_back(Ok(WSVTokenEvent::CommentEnd {
+                    position: self.scanner.position(),
+                }));
+                Some(Ok(WSVTokenEvent::CommentStart { position }))
+            }
+            '"' => {
+                self.scanner.next();
+                self.in_string = true;
+                Some(Ok(WSVTokenEvent::ValueStart {
+                    position,
+                    quoted: true,
+                }))
+            }
+            _ => {
+                self.scanner.skip_while(|ch| {
+                    ch != NEWLINE && ch != '"' && ch != '#' && !WSVTokenizer::is_whitespace(ch)
+                });
+                let end = self.scanner.position();
+                if &self.scanner.source()[position..end] == "-" {
+                    return Some(Ok(WSVTokenEvent::Null { position }));
+                }
+
+                self.pending
+                    .push_back(Ok(WSVTokenEvent::ValueEnd { position: end }));
+                if let Some('"') = self.scanner.peek() {
+                    self.pending.push_back(Err(self
+                        .scanner
+                        .error(WSVErrorType::InvalidDoubleQuoteAfterValue)));
+                }
+                Some(Ok(WSVTokenEvent::ValueStart {
+                    position,
+                    quoted: false,
+                }))
+            }
+        }
+    }
+}
+
+impl<'wsv> Iterator for WSVTokenEvents<'wsv> {
+    type Item = Result<WSVTokenEvent, WSVError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.errored {
+            return None;
+        }
+
+        let event = match self.pending.pop_front() {
+            Some(event) => event,
+            None if self.in_string => self.next_in_string(),
+            None => self.next_outside_string()?,
+        };
+        if event.is_err() {
+            self.errored = true;
+        }
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{token_events, WSVErrorType, WSVTokenEvent, WSVTokenizer};
+
+    #[test]
+    fn emits_events_with_positions() {
+        let input = "a \"b\"\"c\"/\"\" - # note\n";
+        let events = token_events(input)
+            .map(|event| event.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                WSVTokenEvent::ValueStart {
+                    position: 0,
+                    quoted: false
+                },
+                WSVTokenEvent::ValueEnd { position: 1 },
+                WSVTokenEvent::ValueStart {
+                    position: 2,
+                    quoted: true
+                },
+                WSVTokenEvent::EscapedQuote { position: 4 },
+                WSVTokenEvent::EscapedNewline { position: 7 },
+                WSVTokenEvent::ValueEnd { position: 11 },
+                WSVTokenEvent::Null { position: 12 },
+                WSVTokenEvent::CommentStart { position: 14 },
+                WSVTokenEvent::CommentEnd { position: 20 },
+                WSVTokenEvent::LineFeed { position: 20 },
+            ],
+            events
+        );
+        assert_eq!("\"b\"\"c\"/\"\"", &input[2..11]);
+    }
+
+    #[test]
+    fn event_errors_match_tokenizer() {
+        for input in [
+            "value\"",
+            "\"unclosed",
+            "1\n\"bad\"/x\"",
+            "\"string\"trailing",
+        ] {
+            let expected = WSVTokenizer::new(input)
+                .find_map(|token| token.err())
+                .unwrap();
+            let events = token_events(input).collect::<Vec<_>>();
+            let actual = events.last().unwrap().as_ref().unwrap_err();
+            assert_eq!(expected.err_type(), actual.err_type());
+            assert_eq!(expected.location().line(), actual.location().line());
+            assert_eq!(expected.location().col(), actual.location().col());
+            assert_eq!(1, events.iter().filter(|event| event.is_err()).count());
+        }
+
+        let events = token_events("\"a\"b").collect::<Vec<_>>();
+        assert!(matches!(
+            events[1],
+            Ok(WSVTokenEvent::ValueEnd { position: 3 })
+        ));
+        assert_eq!(
+            WSVErrorType::InvalidCharacterAfterString,
+            events[2].as_ref().unwrap_err().err_type()
+        );
+    }
+}
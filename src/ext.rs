@@ -0,0 +1,47 @@
+use crate::{ColumnAlignment, WSVWriter};
+
+/// Adds to_wsv and to_wsv_aligned to anything that can be passed to
+/// WSVWriter::new, so writing rows doesn't require naming the writer's
+/// generics.
+pub trait ToWsv {
+    /// Writes the rows as packed WSV. Same as
+    /// WSVWriter::new(rows).to_string().
+    fn to_wsv(self) -> String;
+
+    /// Writes the rows as WSV with the given column alignment.
+    fn to_wsv_aligned(self, alignment: ColumnAlignment) -> String;
+}
+
+impl<Outer, Inner, BorrowStr> ToWsv for Outer
+where
+    Outer: IntoIterator<Item = Inner>,
+    Inner: IntoIterator<Item = Option<BorrowStr>>,
+    BorrowStr: AsRef<str>,
+{
+    fn to_wsv(self) -> String {
+        WSVWriter::new(self).to_string()
+    }
+
+    fn to_wsv_aligned(self, alignment: ColumnAlignment) -> String {
+        WSVWriter::new(self).align_columns(alignment).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, ColumnAlignment, ToWsv};
+
+    #[test]
+    fn writes_iterators_directly() {
+        let wsv = (1..=3)
+            .map(|row| (0..row).map(|value| Some(value.to_string())))
+            .to_wsv();
+        assert_eq!(3, parse(&wsv).unwrap()[2].len());
+
+        let rows = vec![vec![Some("a"), None], vec![Some("bcd"), Some("e f")]];
+        assert_eq!(
+            "a   -    \nbcd \"e f\"",
+            rows.to_wsv_aligned(ColumnAlignment::Left)
+        );
+    }
+}
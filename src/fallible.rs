@@ -0,0 +1,196 @@
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt::Display;
+use std::rc::Rc;
+
+use crate::{OwnedWSVToken, WSVError, WSVLazyTokenizer, WSVLineIterator};
+
+/// Adapts an iterator of fallible chars into one of plain chars. The
+/// first error ends the iteration and is stashed for the wrapper to
+/// report.
+struct StashErrors<Chars, E> {
+    chars: Chars,
+    error: Rc<RefCell<Option<E>>>,
+}
+
+impl<Chars, E> Iterator for StashErrors<Chars, E>
+where
+    Chars: Iterator<Item = Result<char, E>>,
+{
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.chars.next()? {
+            Ok(ch) => Some(ch),
+            Err(err) => {
+                *self.error.borrow_mut() = Some(err);
+                None
+            }
+        }
+    }
+}
+
+/// Same as WSVLazyTokenizer, but reads from an iterator of fallible chars
+/// (such as the chars of a reader from the utf8-chars crate). An error
+/// from the source is returned in place of the token that was being
+/// read when it happened, and ends the iteration.
+pub struct WSVFallibleTokenizer<Chars, E>
+where
+    Chars: Iterator<Item = Result<char, E>>,
+{
+    tokenizer: WSVLazyTokenizer<StashErrors<Chars, E>>,
+    error: Rc<RefCell<Option<E>>>,
+    errored: bool,
+}
+
+impl<Chars, E> WSVFallibleTokenizer<Chars, E>
+where
+    Chars: Iterator<Item = Result<char, E>>,
+{
+    pub fn new<Source>(source_text: Source) -> Self
+    where
+        Source: IntoIterator<Item = Result<char, E>, IntoIter = Chars>,
+    {
+        let error = Rc::new(RefCell::new(None));
+        Self {
+            tokenizer: WSVLazyTokenizer::new(StashErrors {
+                chars: source_text.into_iter(),
+                error: error.clone(),
+            }),
+            error,
+            errored: false,
+        }
+    }
+}
+
+impl<Chars, E> Iterator for WSVFallibleTokenizer<Chars, E>
+where
+    Chars: Iterator<Item = Result<char, E>>,
+{
+    type Item = Result<OwnedWSVToken, WSVSourceError<E>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.errored {
+            return None;
+        }
+        let token = self.tokenizer.next();
+        next_or_source_error(token, &self.error, &mut self.errored)
+    }
+}
+
+/// Same as parse_lazy, but reads from an iterator of fallible chars
+/// (such as the chars of a reader from the utf8-chars crate). An error
+/// from the source is returned in place of the line that was being
+/// read when it happened, and ends the iteration.
+pub fn parse_lazy_fallible<Source, E>(source_text: Source) -> WSVFallibleLines<Source::IntoIter, E>
+where
+    Source: IntoIterator<Item = Result<char, E>>,
+{
+    let error = Rc::new(RefCell::new(None));
+    WSVFallibleLines {
+        lines: WSVLineIterator::new(StashErrors {
+            chars: source_text.into_iter(),
+            error: error.clone(),
+        }),
+        error,
+        errored: false,
+    }
+}
+
+/// An iterator over the lines of a fallible char source. See
+/// parse_lazy_fallible for details.
+pub struct WSVFallibleLines<Chars, E>
+where
+    Chars: Iterator<Item = Result<char, E>>,
+{
+    lines: WSVLineIterator<StashErrors<Chars, E>>,
+    error: Rc<RefCell<Option<E>>>,
+    errored: bool,
+}
+
+impl<Chars, E> Iterator for WSVFallibleLines<Chars, E>
+where
+    Chars: Iterator<Item = Result<char, E>>,
+{
+    type Item = Result<Vec<Option<String>>, WSVSourceError<E>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.errored {
+            return None;
+        }
+        let line = self.lines.next();
+        next_or_source_error(line, &self.error, &mut self.errored)
+    }
+}
+
+/// Prefers a stashed source error over whatever the WSV parser produced,
+/// since the parser saw the error as the end of the input.
+fn next_or_source_error<T, E>(
+    next: Option<Result<T, WSVError>>,
+    error: &RefCell<Option<E>>,
+    errored: &mut bool,
+) -> Option<Result<T, WSVSourceError<E>>> {
+    if let Some(err) = error.borrow_mut().take() {
+        *errored = true;
+        return Some(Err(WSVSourceError::Source(err)));
+    }
+    let next = next?;
+    *errored = next.is_err();
+    Some(next.map_err(WSVSourceError::WSV))
+}
+
+/// An error from a fallible char source: either the source's own error
+/// (usually an io::Error) or a WSVError from parsing what was read.
+#[derive(Debug, Clone)]
+pub enum WSVSourceError<E> {
+    Source(E),
+    WSV(WSVError),
+}
+
+impl<E: Display> Display for WSVSourceError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WSVSourceError::Source(err) => write!(f, "{}", err),
+            WSVSourceError::WSV(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for WSVSourceError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WSVSourceError::Source(err) => Some(err),
+            WSVSourceError::WSV(err) => Some(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufReader, Cursor};
+
+    use utf8_chars::BufReadCharsExt;
+
+    use crate::{parse_lazy_fallible, OwnedWSVToken, WSVFallibleTokenizer, WSVSourceError};
+
+    #[test]
+    fn surfaces_source_errors() {
+        let mut reader = BufReader::new(Cursor::new("a -\n\"b\" c\n"));
+        let lines = parse_lazy_fallible(reader.chars())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(vec![Some("b".to_string()), Some("c".to_string())], lines[1]);
+
+        let mut reader = BufReader::new(Cursor::new(b"a b\nc \xFF d".to_vec()));
+        let mut lines = parse_lazy_fallible(reader.chars());
+        assert_eq!(2, lines.next().unwrap().unwrap().len());
+        assert!(matches!(lines.next(), Some(Err(WSVSourceError::Source(_)))));
+        assert!(lines.next().is_none());
+
+        let chars = "x \"y".chars().map(Ok::<_, ()>);
+        let mut tokens = WSVFallibleTokenizer::new(chars);
+        assert!(matches!(tokens.next(), Some(Ok(OwnedWSVToken::Value(v))) if v == "x"));
+        assert!(matches!(tokens.next(), Some(Err(WSVSourceError::WSV(_)))));
+        assert!(tokens.next().is_none());
+    }
+}
@@ -0,0 +1,303 @@
+//! A C ABI for embedding the parser in non-Rust applications. The
+//! matching C header is include/whitespacesv.h. To build a shared or
+//! static library, run
+//! `cargo rustc --release --features ffi --crate-type cdylib` (or
+//! `staticlib`).
+//!
+//! Everything returned by these functions is owned by this library and
+//! must be given back to the matching free function.
+
+use std::ffi::{c_char, CStr};
+use std::ptr::null_mut;
+
+use crate::{parse, WSVError, WSVErrorType, WSVWriter};
+
+/// Returned when the call succeeded.
+pub const WSV_OK: i32 = 0;
+/// Returned when the input is not valid WSV. The error is filled in.
+pub const WSV_PARSE_ERROR: i32 = 1;
+/// Returned when a required pointer argument is null.
+pub const WSV_NULL_ARGUMENT: i32 = 2;
+/// Returned when the input is not valid UTF-8.
+pub const WSV_INVALID_UTF8: i32 = 3;
+
+/// A single value. Null values ('-') have a null data pointer. Other
+/// values point to len bytes of UTF-8, followed by a NUL terminator so
+/// they can be used as C strings (as long as they don't contain NULs
+/// themselves).
+#[repr(C)]
+pub struct WSVFfiValue {
+    pub data: *const c_char,
+    pub len: usize,
+}
+
+/// A row of values.
+#[repr(C)]
+pub struct WSVFfiRow {
+    pub values: *const WSVFfiValue,
+    pub len: usize,
+}
+
+/// A parsed document. Free it with wsv_free.
+#[repr(C)]
+pub struct WSVFfiTable {
+    pub rows: *const WSVFfiRow,
+    pub len: usize,
+}
+
+/// Where and why parsing failed. Lines and columns are 1 based.
+/// error_type is one of the WSV_ERROR_* constants.
+#[repr(C)]
+#[derive(Default)]
+pub struct WSVFfiError {
+    pub error_type: i32,
+    pub line: usize,
+    pub column: usize,
+    pub byte_index: usize,
+}
+
+/// The error_type values of WSVFfiError, one per WSVErrorType.
+pub const WSV_ERROR_STRING_NOT_CLOSED: i32 = 1;
+pub const WSV_ERROR_INVALID_DOUBLE_QUOTE_AFTER_VALUE: i32 = 2;
+pub const WSV_ERROR_INVALID_CHARACTER_AFTER_STRING: i32 = 3;
+pub const WSV_ERROR_INVALID_STRING_LINE_BREAK: i32 = 4;
+pub const WSV_ERROR_TOO_MANY_COLUMNS: i32 = 5;
+pub const WSV_ERROR_UNEXPECTED_LINE_BREAK: i32 = 6;
+pub const WSV_ERROR_INVALID_UTF8: i32 = 7;
+pub const WSV_ERROR_CELL_TOO_LARGE: i32 = 8;
+
+impl From<&WSVError> for WSVFfiError {
+    fn from(err: &WSVError) -> Self {
+        Self {
+            error_type: match err.err_type() {
+                WSVErrorType::StringNotClosed => WSV_ERROR_STRING_NOT_CLOSED,
+                WSVErrorType::InvalidDoubleQuoteAfterValue => {
+                    WSV_ERROR_INVALID_DOUBLE_QUOTE_AFTER_VALUE
+                }
+                WSVErrorType::InvalidCharacterAfterString => {
+                    WSV_ERROR_INVALID_CHARACTER_AFTER_STRING
+                }
+                WSVErrorType::InvalidStringLineBreak => WSV_ERROR_INVALID_STRING_LINE_BREAK,
+                WSVErrorType::TooManyColumns => WSV_ERROR_TOO_MANY_COLUMNS,
+                WSVErrorType::UnexpectedLineBreak => WSV_ERROR_UNEXPECTED_LINE_BREAK,
+                WSVErrorType::InvalidUtf8 => WSV_ERROR_INVALID_UTF8,
+                WSVErrorType::CellTooLarge => WSV_ERROR_CELL_TOO_LARGE,
+            },
+            line: err.location().line(),
+            column: err.location().col(),
+            byte_index: err.location.byte_index,
+        }
+    }
+}
+
+/// Parses len bytes of UTF-8 WSV. On success, *out is set to a table
+/// that must be freed with wsv_free. On a parse error, *error is filled
+/// in (if error is not null) and *out is set to null.
+///
+/// # Safety
+/// source must point to len readable bytes and out must be a valid
+/// pointer to write to. error must be null or valid to write to.
+#[no_mangle]
+pub unsafe extern "C" fn wsv_parse(
+    source: *const u8,
+    len: usize,
+    out: *mut *mut WSVFfiTable,
+    error: *mut WSVFfiError,
+) -> i32 {
+    if out.is_null() || (source.is_null() && len != 0) {
+        return WSV_NULL_ARGUMENT;
+    }
+    *out = null_mut();
+    let source = if len == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(source, len)
+    };
+    let Ok(source) = std::str::from_utf8(source) else {
+        return WSV_INVALID_UTF8;
+    };
+
+    let rows = match parse(source) {
+        Ok(rows) => rows,
+        Err(err) => {
+            if !error.is_null() {
+                *error = WSVFfiError::from(&err);
+            }
+            return WSV_PARSE_ERROR;
+        }
+    };
+
+    let rows = rows
+        .into_iter()
+        .map(|row| {
+            let values = row
+                .into_iter()
+                .map(|value| match value {
+                    None => WSVFfiValue {
+                        data: std::ptr::null(),
+                        len: 0,
+                    },
+                    Some(value) => {
+                        let len = value.len();
+                        let mut bytes = value.into_owned().into_bytes();
+                        bytes.push(0);
+                        WSVFfiValue {
+                            data: Box::into_raw(bytes.into_boxed_slice()) as *const c_char,
+                            len,
+                        }
+                    }
+                })
+                .collect::<Box<[_]>>();
+            WSVFfiRow {
+                len: values.len(),
+                values: Box::into_raw(values) as *const WSVFfiValue,
+            }
+        })
+        .collect::<Box<[_]>>();
+    *out = Box::into_raw(Box::new(WSVFfiTable {
+        len: rows.len(),
+        rows: Box::into_raw(rows) as *const WSVFfiRow,
+    }));
+    WSV_OK
+}
+
+/// Frees a table returned by wsv_parse. Does nothing if table is null.
+///
+/// # Safety
+/// table must be null or a table returned by wsv_parse that hasn't
+/// been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn wsv_free(table: *mut WSVFfiTable) {
+    if table.is_null() {
+        return;
+    }
+    let table = Box::from_raw(table);
+    let rows = Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+        table.rows as *mut WSVFfiRow,
+        table.len,
+    ));
+    for row in rows.iter() {
+        let values = Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+            row.values as *mut WSVFfiValue,
+            row.len,
+        ));
+        for value in values.iter() {
+            if !value.data.is_null() {
+                drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+                    value.data as *mut u8,
+                    value.len + 1,
+                )));
+            }
+        }
+    }
+}
+
+/// Writes a table (either one from wsv_parse or one built by the caller)
+/// as packed WSV. Returns a NUL terminated string that must be freed
+/// with wsv_string_free, or null if table is null, a value isn't valid
+/// UTF-8 or a value contains a NUL (which can't be in a C string).
+///
+/// # Safety
+/// table must be null or point to a valid table whose pointers are all
+/// valid for the given lengths.
+#[no_mangle]
+pub unsafe extern "C" fn wsv_write(table: *const WSVFfiTable) -> *mut c_char {
+    if table.is_null() {
+        return null_mut();
+    }
+    let table = &*table;
+    let mut rows = Vec::with_capacity(table.len);
+    for row in slice(table.rows, table.len) {
+        let mut values = Vec::with_capacity(row.len);
+        for value in slice(row.values, row.len) {
+            if value.data.is_null() {
+                values.push(None);
+                continue;
+            }
+            let bytes = slice(value.data as *const u8, value.len);
+            let Ok(value) = std::str::from_utf8(bytes) else {
+                return null_mut();
+            };
+            values.push(Some(value));
+        }
+        rows.push(values);
+    }
+
+    let mut bytes = WSVWriter::new(rows).to_string().into_bytes();
+    if bytes.contains(&0) {
+        return null_mut();
+    }
+    bytes.push(0);
+    Box::into_raw(bytes.into_boxed_slice()) as *mut c_char
+}
+
+/// Frees a string returned by wsv_write. Does nothing if string is null.
+///
+/// # Safety
+/// string must be null or a string returned by wsv_write that hasn't
+/// been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn wsv_string_free(string: *mut c_char) {
+    if string.is_null() {
+        return;
+    }
+    let len = CStr::from_ptr(string).to_bytes_with_nul().len();
+    drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+        string as *mut u8,
+        len,
+    )));
+}
+
+/// Like slice::from_raw_parts, but allows a null pointer when len is 0.
+unsafe fn slice<'a, T>(data: *const T, len: usize) -> &'a [T] {
+    if len == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(data, len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CStr;
+    use std::ptr::null_mut;
+
+    use super::*;
+
+    #[test]
+    fn parses_writes_and_frees() {
+        let source = "a -\n\"b c\" \"\"";
+        let mut table = null_mut();
+        let mut error = WSVFfiError::default();
+        unsafe {
+            assert_eq!(
+                WSV_OK,
+                wsv_parse(source.as_ptr(), source.len(), &mut table, &mut error)
+            );
+            let rows = slice((*table).rows, (*table).len);
+            assert_eq!(2, rows.len());
+            let first = slice(rows[0].values, rows[0].len);
+            assert!(first[1].data.is_null());
+            let second = slice(rows[1].values, rows[1].len);
+            assert_eq!(c"b c", CStr::from_ptr(second[0].data));
+            assert_eq!(0, second[1].len);
+
+            let written = wsv_write(table);
+            assert_eq!(
+                "a - \n\"b c\" \"\" ",
+                CStr::from_ptr(written).to_str().unwrap()
+            );
+            wsv_string_free(written);
+            wsv_free(table);
+
+            let source = "a \"b";
+            assert_eq!(
+                WSV_PARSE_ERROR,
+                wsv_parse(source.as_ptr(), source.len(), &mut table, &mut error)
+            );
+            assert!(table.is_null());
+        }
+        assert_eq!(WSV_ERROR_STRING_NOT_CLOSED, error.error_type);
+        assert_eq!((1, 5, 4), (error.line, error.column, error.byte_index));
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Display;
+
+use crate::WSVError;
+
+/// Groups a stream of rows (such as the ones from parse_lazy) by the
+/// value in the key column, so they can be rolled up with aggregate.
+/// Rows are read one at a time and only the running totals of each
+/// group are kept in memory.
+/// ```
+/// use whitespacesv::{group_by, parse_lazy, Aggregate};
+///
+/// let sales = "north 10\nsouth 5\nnorth 2.5";
+/// let totals = group_by(parse_lazy(sales.chars()), 0)
+///     .aggregate(&[Aggregate::Count, Aggregate::Sum(1)])
+///     .unwrap();
+/// assert_eq!(Some("12.5"), totals[0][2].as_deref());
+/// ```
+pub fn group_by<Lines>(lines: Lines, key_column: usize) -> WSVGroupBy<Lines>
+where
+    Lines: IntoIterator<Item = Result<Vec<Option<String>>, WSVError>>,
+{
+    WSVGroupBy { lines, key_column }
+}
+
+/// Rows grouped by a key column. See group_by.
+pub struct WSVGroupBy<Lines> {
+    lines: Lines,
+    key_column: usize,
+}
+
+/// A value computed for each group by WSVGroupBy::aggregate. Nulls and
+/// missing values are ignored by everything but Count, and the numeric
+/// aggregates fail on values that aren't numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    /// The number of rows in the group.
+    Count,
+    /// The sum of the values in the given column.
+    Sum(usize),
+    /// The smallest number in the given column.
+    Min(usize),
+    /// The largest number in the given column.
+    Max(usize),
+}
+
+impl<Lines> WSVGroupBy<Lines>
+where
+    Lines: IntoIterator<Item = Result<Vec<Option<String>>, WSVError>>,
+{
+    /// Reads all of the rows and returns a table with one row per group,
+    /// in the order the keys are first seen. Each row starts with the
+    /// key followed by one value per aggregate. Rows without a key are
+    /// grouped under null, and rows without any values (blank or
+    /// comment-only lines) are skipped. Min and Max are null for groups
+    /// without any numbers in their column.
+    pub fn aggregate(
+        self,
+        aggregates: &[Aggregate],
+    ) -> Result<Vec<Vec<Option<String>>>, WSVGroupError> {
+        let mut group_indexes = HashMap::new();
+        let mut groups: Vec<(Option<String>, Vec<Option<f64>>)> = Vec::new();
+        for (line_index, row) in self.lines.into_iter().enumerate() {
+            let row = row?;
+            if row.is_empty() {
+                continue;
+            }
+            let key = row.get(self.key_column).cloned().flatten();
+            let group = *group_indexes.entry(key.clone()).or_insert_with(|| {
+                groups.push((key, vec![None; aggregates.len()]));
+                groups.len() - 1
+            });
+            let totals = &mut groups[group].1;
+
+            for (total, aggregate) in totals.iter_mut().zip(aggregates) {
+                let column = match aggregate {
+                    Aggregate::Count => {
+                        *total = Some(total.unwrap_or(0.0) + 1.0);
+                        continue;
+                    }
+                    Aggregate::Sum(column) | Aggregate::Min(column) | Aggregate::Max(column) => {
+                        *column
+                    }
+                };
+                let Some(value) = row.get(column).and_then(Option::as_deref) else {
+                    continue;
+                };
+                let Ok(number) = value.parse::<f64>() else {
+                    return Err(WSVGroupError::NotANumber {
+                        line: line_index + 1,
+                        column,
+                        value: value.to_string(),
+                    });
+                };
+                *total = Some(match (*total, aggregate) {
+                    (None, _) => number,
+                    (Some(total), Aggregate::Min(_)) => total.min(number),
+                    (Some(total), Aggregate::Max(_)) => total.max(number),
+                    (Some(total), _) => total + number,
+                });
+            }
+        }
+
+        Ok(groups
+            .into_iter()
+            .map(|(key, totals)| {
+                let mut row = Vec::with_capacity(totals.len() + 1);
+                row.push(key);
+                for (total, aggregate) in totals.into_iter().zip(aggregates) {
+                    row.push(match (total, aggregate) {
+                        (None, Aggregate::Min(_) | Aggregate::Max(_)) => None,
+                        (total, _) => Some(total.unwrap_or(0.0).to_string()),
+                    });
+                }
+                row
+            })
+            .collect())
+    }
+}
+
+/// An error from WSVGroupBy::aggregate.
+#[derive(Debug)]
+pub enum WSVGroupError {
+    /// The rows were WSV and they were invalid.
+    WSV(WSVError),
+    /// A value in a column being summed or compared wasn't a number.
+    /// Lines are counted from 1 and columns from 0.
+    NotANumber {
+        line: usize,
+        column: usize,
+        value: String,
+    },
+}
+
+impl Display for WSVGroupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WSVGroupError::WSV(err) => write!(f, "{}", err),
+            WSVGroupError::NotANumber {
+                line,
+                column,
+                value,
+            } => write!(
+                f,
+                "(line: {}, value: {}) Not A Number ({:?})",
+                line,
+                column + 1,
+                value
+            ),
+        }
+    }
+}
+
+impl Error for WSVGroupError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WSVGroupError::WSV(err) => Some(err),
+            WSVGroupError::NotANumber { .. } => None,
+        }
+    }
+}
+
+impl From<WSVError> for WSVGroupError {
+    fn from(value: WSVError) -> Self {
+        WSVGroupError::WSV(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{group_by, parse_lazy, Aggregate, WSVGroupError};
+
+    #[test]
+    fn aggregates_groups() {
+        let input = "north 10 a\nsouth - b\n\nnorth 2.5 c\n-\nsouth 4\nnorth -1";
+        let aggregates = [
+            Aggregate::Count,
+            Aggregate::Sum(1),
+            Aggregate::Min(1),
+            Aggregate::Max(1),
+        ];
+        let table = group_by(parse_lazy(input.chars()), 0)
+            .aggregate(&aggregates)
+            .unwrap();
+        assert_eq!(
+            vec![
+                vec![
+                    Some("north"),
+                    Some("3"),
+                    Some("11.5"),
+                    Some("-1"),
+                    Some("10")
+                ],
+                vec![Some("south"), Some("2"), Some("4"), Some("4"), Some("4")],
+                vec![None, Some("1"), Some("0"), None, None],
+            ],
+            table
+                .iter()
+                .map(|row| row.iter().map(|v| v.as_deref()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+
+        let err = group_by(parse_lazy(input.chars()), 1)
+            .aggregate(&[Aggregate::Sum(2)])
+            .unwrap_err();
+        assert!(matches!(
+            &err,
+            WSVGroupError::NotANumber { line: 1, column: 2, value } if value == "a"
+        ));
+        assert_eq!("(line: 1, value: 3) Not A Number (\"a\")", err.to_string());
+    }
+}
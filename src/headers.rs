@@ -0,0 +1,186 @@
+use std::error::Error;
+use std::fmt::Display;
+
+/// The names of the columns of a table, read from its header row. Use
+/// WSVTable::headers or WSVHeaders::new to detect duplicate names, or
+/// disambiguated to rename them instead.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WSVHeaders {
+    names: Vec<String>,
+}
+
+impl WSVHeaders {
+    /// Reads the names from a header row. Null names and names used by
+    /// more than one column are errors.
+    pub fn new<S: AsRef<str>>(header: &[Option<S>]) -> Result<Self, WSVHeaderError> {
+        Self::from_row(header, 1, false)
+    }
+
+    /// Same as new, but renames columns that repeat an earlier name by
+    /// adding the lowest free "_2", "_3", etc. suffix, so "id name name"
+    /// has the columns "id", "name" and "name_2". Null names are still
+    /// errors.
+    pub fn disambiguated<S: AsRef<str>>(header: &[Option<S>]) -> Result<Self, WSVHeaderError> {
+        Self::from_row(header, 1, true)
+    }
+
+    pub(crate) fn from_row<S: AsRef<str>>(
+        header: &[Option<S>],
+        line: usize,
+        disambiguate: bool,
+    ) -> Result<Self, WSVHeaderError> {
+        let mut names: Vec<String> = Vec::with_capacity(header.len());
+        for (column, name) in header.iter().enumerate() {
+            let Some(name) = name.as_ref().map(|name| name.as_ref()) else {
+                return Err(WSVHeaderError::NullHeader { line, column });
+            };
+            let Some(first_column) = names.iter().position(|existing| existing == name) else {
+                names.push(name.to_string());
+                continue;
+            };
+            if !disambiguate {
+                return Err(WSVHeaderError::DuplicateHeader {
+                    line,
+                    name: name.to_string(),
+                    first_column,
+                    column,
+                });
+            }
+            // Skip suffixes that are taken by a later column too.
+            let taken = |candidate: &str| {
+                names.iter().any(|existing| existing == candidate)
+                    || header[column + 1..].iter().any(|later| {
+                        later
+                            .as_ref()
+                            .is_some_and(|later| later.as_ref() == candidate)
+                    })
+            };
+            let renamed = (2..)
+                .map(|suffix| format!("{}_{}", name, suffix))
+                .find(|candidate| !taken(candidate))
+                .unwrap();
+            names.push(renamed);
+        }
+        Ok(Self { names })
+    }
+
+    /// The column names, in column order.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// The number of columns.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Whether the header row has no columns.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// The index of the column with the given name.
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|existing| existing == name)
+    }
+
+    /// The indexes of the columns with the given names, in the order of
+    /// the names, or None if any of them isn't a column. This can be
+    /// passed to WSVLineIterator::select_columns.
+    pub fn indexes<S: AsRef<str>>(&self, names: &[S]) -> Option<Vec<usize>> {
+        names.iter().map(|name| self.index(name.as_ref())).collect()
+    }
+
+    /// The value in the named column of a row. Returns None if there is
+    /// no such column, or if the value is null or missing from the row.
+    pub fn get<'row, S: AsRef<str>>(
+        &self,
+        row: &'row [Option<S>],
+        name: &str,
+    ) -> Option<&'row str> {
+        row.get(self.index(name)?)?
+            .as_ref()
+            .map(|value| value.as_ref())
+    }
+}
+
+/// An error from reading a header row. Line numbers are 1 based and
+/// columns are 0 based indexes into the row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WSVHeaderError {
+    /// A value in the header row was null, so it can't name a column.
+    NullHeader { line: usize, column: usize },
+    /// The same name is used by more than one column.
+    DuplicateHeader {
+        line: usize,
+        name: String,
+        first_column: usize,
+        column: usize,
+    },
+    /// No column has the given name.
+    UnknownHeader { line: usize, name: String },
+}
+
+impl Display for WSVHeaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WSVHeaderError::NullHeader { line, column } => {
+                write!(f, "(line: {}, value: {}) Null Header", line, column + 1)
+            }
+            WSVHeaderError::DuplicateHeader {
+                line,
+                name,
+                first_column,
+                column,
+            } => write!(
+                f,
+                "(line: {}, value: {}) Duplicate Header ({:?}, first used by value {})",
+                line,
+                column + 1,
+                name,
+                first_column + 1
+            ),
+            WSVHeaderError::UnknownHeader { line, name } => {
+                write!(f, "(line: {}) Unknown Header ({:?})", line, name)
+            }
+        }
+    }
+}
+
+impl Error for WSVHeaderError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_tables, WSVHeaderError, WSVHeaders};
+
+    #[test]
+    fn detects_duplicate_headers() {
+        let tables = parse_tables("a b\n1 2\n\nid name name name_2\n1 x y z").unwrap();
+        assert_eq!(2, tables[0].headers().unwrap().len());
+
+        let err = tables[1].headers().unwrap_err();
+        assert_eq!(
+            WSVHeaderError::DuplicateHeader {
+                line: 4,
+                name: "name".to_string(),
+                first_column: 1,
+                column: 2,
+            },
+            err
+        );
+        assert_eq!(
+            "(line: 4, value: 3) Duplicate Header (\"name\", first used by value 2)",
+            err.to_string()
+        );
+
+        let headers = tables[1].disambiguated_headers().unwrap();
+        assert_eq!(["id", "name", "name_3", "name_2"], headers.names());
+        assert_eq!(Some("y"), headers.get(&tables[1][1], "name_3"));
+        assert_eq!(None, headers.get(&tables[1][1], "missing"));
+
+        assert_eq!(
+            Err(WSVHeaderError::NullHeader { line: 1, column: 1 }),
+            WSVHeaders::new(&[Some("a"), None])
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{WSVError, WSVToken, WSVTokenizer};
+
+/// The comment that splices another file in, as in `#!include other.wsv`.
+const INCLUDE_DIRECTIVE: &str = "!include";
+
+/// A row read by read_with_includes, along with where it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WSVSourceRow {
+    pub values: Vec<Option<String>>,
+    /// The file the row was read from.
+    pub path: PathBuf,
+    /// The 1 based line number of the row in that file.
+    pub line: usize,
+}
+
+/// Reads the WSV file at the given path, replacing every `#!include
+/// <path>` line with the rows of the file it names, so tables split over
+/// several files can be read as one. Included paths are relative to the
+/// directory of the file that includes them, and included files can
+/// include others in turn. The directive has to be on a line of its own.
+///
+/// Each row comes with the file and line it was read from. Like parse,
+/// blank and comment-only lines are empty rows, but directive lines
+/// aren't rows at all.
+///
+/// Fails if a file can't be read or parsed, or if a file ends up
+/// including itself.
+pub fn read_with_includes<P: AsRef<Path>>(path: P) -> Result<Vec<WSVSourceRow>, WSVIncludeError> {
+    let mut rows = Vec::new();
+    read_into(path.as_ref(), &mut Vec::new(), &mut rows)?;
+    Ok(rows)
+}
+
+/// Reads the file at the path into rows. including holds the files that
+/// are being read further up, to find cycles.
+fn read_into(
+    path: &Path,
+    including: &mut Vec<PathBuf>,
+    rows: &mut Vec<WSVSourceRow>,
+) -> Result<(), WSVIncludeError> {
+    let io_error = |error| WSVIncludeError::Io {
+        path: path.to_path_buf(),
+        error,
+    };
+    let canonical = fs::canonicalize(path).map_err(io_error)?;
+    let source_text = fs::read_to_string(path).map_err(io_error)?;
+    including.push(canonical);
+
+    let mut values = Vec::new();
+    let mut comment = None;
+    let mut line = 1;
+    let mut tokens = WSVTokenizer::new(&source_text);
+    loop {
+        let token = tokens
+            .next()
+            .transpose()
+            .map_err(|error| WSVIncludeError::WSV {
+                path: path.to_path_buf(),
+                error,
+            })?;
+        match token {
+            Some(WSVToken::Null) => values.push(None),
+            Some(WSVToken::Value(value)) => values.push(Some(value.into_owned())),
+            Some(WSVToken::Comment(text)) => comment = Some(text),
+            Some(WSVToken::LF) | None => {
+                let include = comment
+                    .take()
+                    .filter(|_| values.is_empty())
+                    .and_then(|comment| comment.strip_prefix(INCLUDE_DIRECTIVE))
+                    .filter(|rest| rest.starts_with(WSVTokenizer::is_whitespace))
+                    .map(|rest| rest.trim_matches(WSVTokenizer::is_whitespace));
+                if let Some(include) = include {
+                    let included = path.parent().unwrap_or(Path::new("")).join(include);
+                    let is_cycle = fs::canonicalize(&included)
+                        .is_ok_and(|included| including.contains(&included));
+                    if is_cycle {
+                        return Err(WSVIncludeError::Cycle {
+                            path: path.to_path_buf(),
+                            line,
+                            included,
+                        });
+                    }
+                    read_into(&included, including, rows)?;
+                } else if token.is_some() || !values.is_empty() {
+                    // Like parse, there is no empty row after a trailing
+                    // line feed.
+                    rows.push(WSVSourceRow {
+                        values: std::mem::take(&mut values),
+                        path: path.to_path_buf(),
+                        line,
+                    });
+                }
+                if token.is_none() {
+                    break;
+                }
+                line += 1;
+            }
+        }
+    }
+
+    including.pop();
+    Ok(())
+}
+
+/// An error from read_with_includes. Paths are the ones the files were
+/// named by, and line numbers are 1 based.
+#[derive(Debug)]
+pub enum WSVIncludeError {
+    /// A file couldn't be read.
+    Io {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    /// A file couldn't be parsed.
+    WSV { path: PathBuf, error: WSVError },
+    /// The directive at the given line includes a file that is already
+    /// being read, which would never end.
+    Cycle {
+        path: PathBuf,
+        line: usize,
+        included: PathBuf,
+    },
+}
+
+impl Display for WSVIncludeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WSVIncludeError::Io { path, error } => {
+                write!(f, "(file: {}) {}", path.display(), error)
+            }
+            WSVIncludeError::WSV { path, error } => {
+                write!(f, "(file: {}) {}", path.display(), error)
+            }
+            WSVIncludeError::Cycle {
+                path,
+                line,
+                included,
+            } => write!(
+                f,
+                "(file: {}, line: {}) Include Cycle ({})",
+                path.display(),
+                line,
+                included.display()
+            ),
+        }
+    }
+}
+
+impl Error for WSVIncludeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WSVIncludeError::Io { error, .. } => Some(error),
+            WSVIncludeError::WSV { error, .. } => Some(error),
+            WSVIncludeError::Cycle { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{read_with_includes, WSVIncludeError};
+
+    #[test]
+    fn splices_included_files() {
+        let dir = std::env::temp_dir().join(format!("whitespacesv_include_{}", std::process::id()));
+        fs::create_dir_all(dir.join("more")).unwrap();
+        fs::write(
+            dir.join("main.wsv"),
+            "key value\n#!include more/limits.wsv\nname main # not a directive\n#!include   more/empty.wsv  \n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("more/limits.wsv"),
+            "max 10\n\n#!include empty.wsv\nmin 1",
+        )
+        .unwrap();
+        fs::write(dir.join("more/empty.wsv"), "# nothing here\n").unwrap();
+
+        let rows = read_with_includes(dir.join("main.wsv")).unwrap();
+        assert_eq!(
+            vec![
+                (dir.join("main.wsv"), 1),
+                (dir.join("more/limits.wsv"), 1),
+                (dir.join("more/limits.wsv"), 2),
+                (dir.join("more/empty.wsv"), 1),
+                (dir.join("more/limits.wsv"), 4),
+                (dir.join("main.wsv"), 3),
+                (dir.join("more/empty.wsv"), 1),
+            ],
+            rows.iter()
+                .map(|row| (row.path.clone(), row.line))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![Some("min".to_string()), Some("1".to_string())],
+            rows[4].values
+        );
+
+        fs::write(dir.join("more/empty.wsv"), "#!include ../main.wsv").unwrap();
+        let err = read_with_includes(dir.join("main.wsv")).unwrap_err();
+        assert!(matches!(
+            err,
+            WSVIncludeError::Cycle { line: 1, ref path, .. } if *path == dir.join("more/empty.wsv")
+        ));
+
+        fs::write(dir.join("more/empty.wsv"), "\"open").unwrap();
+        let err = read_with_includes(dir.join("main.wsv")).unwrap_err();
+        assert!(matches!(err, WSVIncludeError::WSV { .. }));
+        assert!(err.to_string().starts_with(&format!(
+            "(file: {}) ",
+            dir.join("more/empty.wsv").display()
+        )));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
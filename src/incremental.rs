@@ -0,0 +1,217 @@
+use std::ops::Range;
+
+use crate::{parse, WSVError, NEWLINE};
+
+/// A parse that can be kept up to date as its source is edited, for
+/// editors and other tools that reparse on every keystroke. Since a WSV
+/// row can never span multiple lines, an edit only needs the lines it
+/// touches to be reparsed. Every other row is kept as is.
+///
+/// Each line is parsed on its own, so unlike parse, an error on one line
+/// does not hide the rows after it.
+#[derive(Debug, Clone)]
+pub struct WSVIncrementalParse {
+    source: String,
+    lines: Vec<ParsedLine>,
+}
+
+#[derive(Debug, Clone)]
+struct ParsedLine {
+    start: usize,
+    /// Errors are stored relative to the start of the line, so that
+    /// edits to earlier lines don't have to update them.
+    row: Result<Vec<Option<String>>, WSVError>,
+}
+
+impl WSVIncrementalParse {
+    /// Parses the given source.
+    pub fn new(source: String) -> Self {
+        let lines = parse_lines(&source, 0);
+        Self { source, lines }
+    }
+
+    /// The current source text.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The number of rows. Same as parse, a trailing line without any
+    /// values is not counted.
+    pub fn len(&self) -> usize {
+        match self.lines.last() {
+            Some(ParsedLine { row: Ok(row), .. }) if row.is_empty() => self.lines.len() - 1,
+            _ => self.lines.len(),
+        }
+    }
+
+    /// Whether there are no rows.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The row at the given index (0 based), or the error on that row.
+    pub fn row(&self, index: usize) -> Option<Result<&[Option<String>], WSVError>> {
+        if index >= self.len() {
+            return None;
+        }
+        let line = &self.lines[index];
+        Some(match &line.row {
+            Ok(row) => Ok(row.as_slice()),
+            Err(err) => Err(err.clone().shifted(index, line.start)),
+        })
+    }
+
+    /// The byte range of the given row (0 based) in the source,
+    /// not including its line feed.
+    pub fn row_span(&self, index: usize) -> Option<Range<usize>> {
+        let start = self.lines.get(index)?.start;
+        let end = match self.lines.get(index + 1) {
+            Some(next) => next.start - 1,
+            None => self.source.len(),
+        };
+        Some(start..end)
+    }
+
+    /// An iterator over all of the errors in the source, in order.
+    pub fn errors(&self) -> impl Iterator<Item = WSVError> + '_ {
+        self.lines.iter().enumerate().filter_map(|(index, line)| {
+            line.row
+                .as_ref()
+                .err()
+                .map(|err| err.clone().shifted(index, line.start))
+        })
+    }
+
+    /// All of the rows, or the first error. This gives the same
+    /// result as calling parse on the current source.
+    pub fn rows(&self) -> Result<Vec<&[Option<String>]>, WSVError> {
+        (0..self.len())
+            .map(|index| self.row(index).unwrap())
+            .collect()
+    }
+
+    /// Replaces the given byte range of the source with the replacement
+    /// text and reparses only the lines touched by the edit. Returns the
+    /// range of rows that were reparsed, in terms of the edited source.
+    ///
+    /// Panics if the range is out of bounds or does not lie on char
+    /// boundaries, same as String::replace_range.
+    pub fn edit(&mut self, range: Range<usize>, replacement: &str) -> Range<usize> {
+        let first_line = self.line_at(range.start);
+        let last_line = self.line_at(range.end);
+        let span_start = self.lines[first_line].start;
+        let old_span_end = self.row_span(last_line).unwrap().end;
+
+        self.source.replace_range(range.clone(), replacement);
+        let new_span_end = old_span_end + replacement.len() - range.len();
+        let new_lines = parse_lines(&self.source[span_start..new_span_end], span_start);
+        let new_line_count = new_lines.len();
+
+        let shift = new_span_end as isize - old_span_end as isize;
+        for line in self.lines[last_line + 1..].iter_mut() {
+            line.start = line.start.wrapping_add_signed(shift);
+        }
+        self.lines.splice(first_line..=last_line, new_lines);
+
+        first_line..first_line + new_line_count
+    }
+
+    /// The index of the line containing the given byte offset.
+    fn line_at(&self, byte_offset: usize) -> usize {
+        self.lines
+            .partition_point(|line| line.start <= byte_offset)
+            .saturating_sub(1)
+    }
+}
+
+/// Parses every line of the text on its own. The starts of the
+/// lines are offset by the given amount.
+fn parse_lines(text: &str, offset: usize) -> Vec<ParsedLine> {
+    let mut start = offset;
+    text.split(NEWLINE)
+        .map(|line| {
+            let parsed = ParsedLine {
+                start,
+                row: parse(line).map(|mut rows| {
+                    rows.pop()
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|value| value.map(|value| value.into_owned()))
+                        .collect()
+                }),
+            };
+            start += line.len() + 1;
+            parsed
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, WSVErrorType, WSVIncrementalParse};
+
+    fn assert_matches_parse(incremental: &WSVIncrementalParse) {
+        let expected = parse(incremental.source());
+        let actual = incremental.rows();
+        match (expected, actual) {
+            (Ok(expected), Ok(actual)) => {
+                assert_eq!(expected.len(), actual.len());
+                for (expected, actual) in expected.iter().zip(actual) {
+                    assert_eq!(
+                        expected.iter().map(|v| v.as_deref()).collect::<Vec<_>>(),
+                        actual.iter().map(|v| v.as_deref()).collect::<Vec<_>>()
+                    );
+                }
+            }
+            (Err(expected), Err(actual)) => {
+                assert_eq!(expected.err_type(), actual.err_type());
+                assert_eq!(expected.location().line(), actual.location().line());
+                assert_eq!(expected.location().col(), actual.location().col());
+            }
+            (expected, actual) => panic!("{:?} != {:?}", expected, actual),
+        }
+    }
+
+    #[test]
+    fn edits_match_full_reparse() {
+        let mut incremental =
+            WSVIncrementalParse::new(include_str!("../tests/1_stenway.com").to_string());
+        assert_matches_parse(&incremental);
+
+        let edits: [(usize, usize, &str); 8] = [
+            (0, 0, "new first line\n"),
+            (4, 9, "\"x y\""),
+            (20, 40, ""),
+            (10, 10, "\n\n\"unclosed"),
+            (12, 22, "fixed -"),
+            (0, 0, "# comment\n"),
+            (usize::MAX, usize::MAX, "\ntrailing # comment"),
+            (usize::MAX, usize::MAX, "\n"),
+        ];
+        for (start, end, replacement) in edits {
+            let len = incremental.source().len();
+            let (start, end) = (start.min(len), end.min(len));
+            let reparsed = incremental.edit(start..end, replacement);
+            assert!(reparsed.len() <= replacement.matches('\n').count() + 1);
+            assert_matches_parse(&incremental);
+        }
+    }
+
+    #[test]
+    fn errors_do_not_hide_later_rows() {
+        let mut incremental = WSVIncrementalParse::new("a\n\"b\nc".to_string());
+        assert_eq!(3, incremental.len());
+        let errors = incremental.errors().collect::<Vec<_>>();
+        assert_eq!(1, errors.len());
+        assert_eq!(WSVErrorType::StringNotClosed, errors[0].err_type());
+        assert_eq!(2, errors[0].location().line());
+        assert_eq!(
+            Some("c"),
+            incremental.row(2).unwrap().unwrap()[0].as_deref()
+        );
+
+        assert_eq!(1..2, incremental.edit(4..4, "\""));
+        assert_eq!(0, incremental.errors().count());
+        assert_eq!(Some(2..5), incremental.row_span(1));
+    }
+}
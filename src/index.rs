@@ -0,0 +1,351 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::Path;
+
+use crate::{drop_empty_last_row, parse, WSVReadError};
+
+/// The bytes every serialized index starts with.
+const MAGIC: &[u8; 8] = b"WSVIDX01";
+
+/// An index of the byte offset of every row in a .wsv file. Since line
+/// feeds inside strings must be escaped as "/", every line feed in the
+/// file is a row boundary, so building the index is a single pass over
+/// the raw bytes without any tokenizing. Rows line up with the rows
+/// returned by parse (a trailing empty line is not counted as a row).
+///
+/// The index can be saved next to a huge file and loaded later to jump
+/// straight to any row without rescanning the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WSVRowIndex {
+    /// The start of every row, followed by where the row after the
+    /// last one would start (one past its line feed, real or not).
+    offsets: Vec<u64>,
+    source_len: u64,
+}
+
+impl WSVRowIndex {
+    /// Builds an index by scanning the given reader once.
+    pub fn build<R: Read>(reader: R) -> Result<Self, std::io::Error> {
+        let mut reader = BufReader::new(reader);
+        let mut offsets = vec![0];
+        let mut position = 0_u64;
+        loop {
+            let buffer = reader.fill_buf()?;
+            if buffer.is_empty() {
+                break;
+            }
+            for (i, byte) in buffer.iter().enumerate() {
+                if *byte == b'\n' {
+                    offsets.push(position + i as u64 + 1);
+                }
+            }
+            let len = buffer.len();
+            position += len as u64;
+            reader.consume(len);
+        }
+
+        // The last row ends at the end of the source, or one past it if
+        // it has no line feed of its own.
+        if drop_empty_last_row(&mut offsets, |&start| start == position) {
+            offsets.push(position);
+        } else {
+            offsets.push(position + 1);
+        }
+
+        Ok(Self {
+            offsets,
+            source_len: position,
+        })
+    }
+
+    /// Builds an index of the file at the given path.
+    pub fn build_from_file<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
+        Self::build(File::open(path)?)
+    }
+
+    /// The number of rows in the indexed source.
+    pub fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    /// Whether the indexed source has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The length in bytes of the indexed source.
+    pub fn source_len(&self) -> u64 {
+        self.source_len
+    }
+
+    /// The byte offset of the start of the given row (0 based).
+    pub fn offset(&self, row: usize) -> Option<u64> {
+        if row < self.len() {
+            Some(self.offsets[row])
+        } else {
+            None
+        }
+    }
+
+    /// The byte range of the given row (0 based), not including
+    /// its trailing line feed.
+    pub fn row_range(&self, row: usize) -> Option<Range<u64>> {
+        let start = self.offset(row)?;
+        Some(start..self.offsets[row + 1] - 1)
+    }
+
+    /// The row (0 based) that contains the given byte offset.
+    pub fn row_at(&self, byte_offset: u64) -> Option<usize> {
+        if byte_offset >= self.source_len {
+            return None;
+        }
+        match self.offsets[..self.len()].binary_search(&byte_offset) {
+            Ok(row) => Some(row),
+            Err(next_row) => Some(next_row - 1),
+        }
+    }
+
+    /// Writes the index in a compact binary format. The offsets are stored
+    /// as the distances between rows, encoded as variable length integers,
+    /// so an index of a file with short rows is only a byte or two per row.
+    pub fn write_to<W: Write>(&self, writer: W) -> Result<(), std::io::Error> {
+        let mut writer = BufWriter::new(writer);
+        writer.write_all(MAGIC)?;
+        write_varint(&mut writer, self.source_len)?;
+        write_varint(&mut writer, self.len() as u64)?;
+        let mut previous = 0;
+        for offset in self.offsets.iter() {
+            write_varint(&mut writer, offset - previous)?;
+            previous = *offset;
+        }
+        writer.flush()
+    }
+
+    /// Reads an index written by write_to.
+    pub fn read_from<R: Read>(reader: R) -> Result<Self, std::io::Error> {
+        let mut reader = BufReader::new(reader);
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a WSV row index"));
+        }
+
+        let source_len = read_varint(&mut reader)?;
+        let len = read_varint(&mut reader)?;
+        let mut offsets = Vec::with_capacity(len.min(1 << 20) as usize + 1);
+        // The first row always starts at the start of the source.
+        if read_varint(&mut reader)? != 0 {
+            return Err(invalid_data("first row offset is not 0"));
+        }
+        offsets.push(0);
+        let mut previous = 0_u64;
+        for _ in 0..len {
+            let offset = previous
+                .checked_add(read_varint(&mut reader)?)
+                .filter(|offset| *offset > previous)
+                .filter(|offset| *offset <= source_len + 1)
+                .ok_or_else(|| invalid_data("row offset is out of range"))?;
+            offsets.push(offset);
+            previous = offset;
+        }
+
+        Ok(Self {
+            offsets,
+            source_len,
+        })
+    }
+
+    /// Saves the index to the file at the given path.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), std::io::Error> {
+        self.write_to(File::create(path)?)
+    }
+
+    /// Loads an index saved with save.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
+        Self::read_from(File::open(path)?)
+    }
+}
+
+/// A random access reader over a .wsv source. The row index is used to
+/// seek straight to the requested rows, so reading rows from the end of
+/// a huge file costs the same as reading them from the start.
+pub struct WSVIndexedReader<R> {
+    reader: R,
+    index: WSVRowIndex,
+}
+
+impl WSVIndexedReader<File> {
+    /// Opens the file at the given path and indexes it. To skip the
+    /// indexing pass, load a saved index and use with_index instead.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
+        let index = WSVRowIndex::build_from_file(&path)?;
+        Ok(Self::with_index(File::open(path)?, index))
+    }
+}
+
+impl<R: Read + Seek> WSVIndexedReader<R> {
+    /// Creates a reader from a source and an index that was built from
+    /// that same source. Reading with a stale index gives garbage rows.
+    pub fn with_index(reader: R, index: WSVRowIndex) -> Self {
+        Self { reader, index }
+    }
+
+    /// The index used by this reader.
+    pub fn index(&self) -> &WSVRowIndex {
+        &self.index
+    }
+
+    /// Reads the row at the given index (0 based), or None if it is out of range.
+    pub fn get_row(&mut self, row: usize) -> Result<Option<Vec<Option<String>>>, WSVReadError> {
+        Ok(self.rows(row..row + 1)?.pop())
+    }
+
+    /// Reads the rows in the given range (0 based). The range is clamped
+    /// to the number of rows in the index. The rows are read from the
+    /// source in one go, so this is much faster than calling get_row for
+    /// each row. Error locations are relative to the start of the source.
+    pub fn rows(&mut self, rows: Range<usize>) -> Result<Vec<Vec<Option<String>>>, WSVReadError> {
+        let end = rows.end.min(self.index.len());
+        if rows.start >= end {
+            return Ok(Vec::new());
+        }
+
+        let start_offset = self.index.offsets[rows.start];
+        let end_offset = self.index.offsets[end] - 1;
+        let mut bytes = vec![0; (end_offset - start_offset) as usize];
+        self.reader.seek(SeekFrom::Start(start_offset))?;
+        self.reader.read_exact(&mut bytes)?;
+        let text = String::from_utf8(bytes)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+
+        let mut result = parse(&text)
+            .map_err(|err| err.shifted(rows.start, start_offset as usize))?
+            .into_iter()
+            .map(|row| row.into_iter().map(|v| v.map(|v| v.into_owned())).collect())
+            .collect::<Vec<_>>();
+        // parse drops a trailing empty row, but the index doesn't.
+        result.resize(end - rows.start, Vec::new());
+        Ok(result)
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+fn write_varint<W: Write>(writer: &mut W, mut value: u64) -> Result<(), std::io::Error> {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            return writer.write_all(&[byte]);
+        }
+        writer.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn read_varint<R: Read>(reader: &mut R) -> Result<u64, std::io::Error> {
+    let mut value = 0_u64;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0];
+        reader.read_exact(&mut byte)?;
+        value |= ((byte[0] & 0x7F) as u64) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid_data("row offset is too large"))
+}
+
+fn invalid_data(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::{parse, WSVErrorType, WSVIndexedReader, WSVReadError, WSVRowIndex};
+
+    #[test]
+    fn index_matches_parse() {
+        for input in [
+            include_str!("../tests/1_stenway.com"),
+            "",
+            "a",
+            "a\n",
+            "a\n\n",
+            "\"x\"/\"y\" b\n\nc",
+        ] {
+            let index = WSVRowIndex::build(Cursor::new(input)).unwrap();
+            let rows = parse(input).unwrap();
+            assert_eq!(rows.len(), index.len());
+            for (i, row) in rows.iter().enumerate() {
+                let range = index.row_range(i).unwrap();
+                let line = &input[range.start as usize..range.end as usize];
+                assert_eq!(*row, parse(line).unwrap().pop().unwrap_or_default());
+            }
+        }
+
+        let index = WSVRowIndex::build(Cursor::new("ab\ncd\n\nef")).unwrap();
+        assert_eq!(Some(0), index.row_at(2));
+        assert_eq!(Some(1), index.row_at(3));
+        assert_eq!(Some(3), index.row_at(8));
+        assert_eq!(None, index.row_at(9));
+    }
+
+    #[test]
+    fn index_round_trips() {
+        let input = include_str!("../tests/1_stenway.com").repeat(100);
+        let index = WSVRowIndex::build(Cursor::new(&input)).unwrap();
+        let mut bytes = Vec::new();
+        index.write_to(&mut bytes).unwrap();
+        assert!(bytes.len() < index.len() * 2 + 32);
+        assert_eq!(index, WSVRowIndex::read_from(Cursor::new(&bytes)).unwrap());
+
+        bytes[0] = b'X';
+        assert!(WSVRowIndex::read_from(Cursor::new(&bytes)).is_err());
+
+        // The magic, a source length of 2, 1 row and a first offset of 1.
+        let mut bytes = super::MAGIC.to_vec();
+        bytes.extend([2, 1, 1, 2]);
+        let err = WSVRowIndex::read_from(Cursor::new(&bytes)).unwrap_err();
+        assert_eq!("first row offset is not 0", err.to_string());
+    }
+
+    #[test]
+    fn indexed_reader_reads_any_row() {
+        let input = (0..10_000)
+            .map(|i| format!("{} \"row {}\" - # comment", i, i))
+            .collect::<Vec<_>>()
+            .join("\n")
+            + "\n\nlast \"bad\"x\n";
+        let index = WSVRowIndex::build(Cursor::new(&input)).unwrap();
+        let mut reader = WSVIndexedReader::with_index(Cursor::new(&input), index);
+
+        assert_eq!(
+            Some(vec![
+                Some("7000".to_string()),
+                Some("row 7000".to_string()),
+                None
+            ]),
+            reader.get_row(7000).unwrap()
+        );
+        let rows = reader.rows(9_998..10_001).unwrap();
+        assert_eq!(3, rows.len());
+        assert_eq!(Some("9999"), rows[1][0].as_deref());
+        assert!(rows[2].is_empty());
+        assert_eq!(None, reader.get_row(10_002).unwrap());
+        assert!(reader.rows(20_000..30_000).unwrap().is_empty());
+
+        match reader.get_row(10_001) {
+            Err(WSVReadError::WSV(err)) => {
+                assert_eq!(WSVErrorType::InvalidCharacterAfterString, err.err_type());
+                assert_eq!(10_002, err.location().line());
+            }
+            _ => panic!("Expected an error on the last row"),
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use crate::{parse, WSVError};
+
+/// A value with its type inferred by infer or infer_value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WSVValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+impl WSVValue {
+    /// The type of this value.
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            WSVValue::Null => ValueType::Null,
+            WSVValue::Bool(_) => ValueType::Bool,
+            WSVValue::Int(_) => ValueType::Int,
+            WSVValue::Float(_) => ValueType::Float,
+            WSVValue::String(_) => ValueType::String,
+        }
+    }
+}
+
+/// The types a WSVValue can have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueType {
+    Null,
+    Bool,
+    Int,
+    Float,
+    String,
+}
+
+/// The rules used to infer the type of each value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InferOptions {
+    /// Values that become Bool(true). Defaults to "true".
+    pub true_values: Vec<String>,
+    /// Values that become Bool(false). Defaults to "false".
+    pub false_values: Vec<String>,
+    /// Whether true_values and false_values are matched ignoring ASCII
+    /// case. Defaults to true.
+    pub ignore_case: bool,
+    /// Whether values that look like numbers become Int or Float.
+    /// Defaults to true.
+    pub numbers: bool,
+    /// Whether integers with leading zeros (ex. zip codes like 01234)
+    /// stay strings so the zeros aren't lost. Defaults to true.
+    pub keep_leading_zeros: bool,
+}
+
+impl Default for InferOptions {
+    fn default() -> Self {
+        Self {
+            true_values: vec!["true".to_string()],
+            false_values: vec!["false".to_string()],
+            ignore_case: true,
+            numbers: true,
+            keep_leading_zeros: true,
+        }
+    }
+}
+
+/// Parses the source text (see parse for details) and infers the type of
+/// every value with infer_value.
+pub fn infer(source_text: &str, options: &InferOptions) -> Result<Vec<Vec<WSVValue>>, WSVError> {
+    Ok(parse(source_text)?
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|value| infer_value(value.as_deref(), options))
+                .collect()
+        })
+        .collect())
+}
+
+/// Infers the type of a single value. Nulls ('-') become Null, then the
+/// value is checked against the bool values, then as an integer that
+/// fits in an i64 and then as a float. Anything else is a String.
+pub fn infer_value(value: Option<&str>, options: &InferOptions) -> WSVValue {
+    let Some(value) = value else {
+        return WSVValue::Null;
+    };
+    let matches = |candidates: &[String]| {
+        candidates.iter().any(|candidate| {
+            if options.ignore_case {
+                candidate.eq_ignore_ascii_case(value)
+            } else {
+                candidate == value
+            }
+        })
+    };
+    if matches(&options.true_values) {
+        return WSVValue::Bool(true);
+    }
+    if matches(&options.false_values) {
+        return WSVValue::Bool(false);
+    }
+
+    // Only plain decimal notation counts as a number, so values like
+    // "inf" and "NaN" that f64 would accept stay strings.
+    let is_numeric = value.chars().any(|ch| ch.is_ascii_digit())
+        && value
+            .chars()
+            .all(|ch| ch.is_ascii_digit() || matches!(ch, '+' | '-' | '.' | 'e' | 'E'));
+    if options.numbers && is_numeric {
+        if let Ok(int) = value.parse::<i64>() {
+            let digits = value.trim_start_matches(['+', '-']);
+            if !(options.keep_leading_zeros && digits.len() > 1 && digits.starts_with('0')) {
+                return WSVValue::Int(int);
+            }
+        } else if let Ok(float) = value.parse::<f64>() {
+            return WSVValue::Float(float);
+        }
+    }
+    WSVValue::String(value.to_string())
+}
+
+/// A summary of the types found in one column, from column_types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WSVColumnType {
+    /// The narrowest type that can hold every non-null value in the
+    /// column. Ints widen to Float when mixed with floats, and any other
+    /// mix of types is String. Columns of only nulls are Null.
+    pub value_type: ValueType,
+    pub nulls: usize,
+    pub bools: usize,
+    pub ints: usize,
+    pub floats: usize,
+    pub strings: usize,
+}
+
+impl WSVColumnType {
+    /// Whether the column contains any nulls (including values missing
+    /// from the end of short rows).
+    pub fn nullable(&self) -> bool {
+        self.nulls > 0
+    }
+}
+
+/// Reports the dominant type of each column of inferred rows. Rows that
+/// are shorter than the widest row count as having nulls in the
+/// missing columns.
+pub fn column_types(rows: &[Vec<WSVValue>]) -> Vec<WSVColumnType> {
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    (0..width)
+        .map(|column| {
+            let mut report = WSVColumnType {
+                value_type: ValueType::Null,
+                nulls: 0,
+                bools: 0,
+                ints: 0,
+                floats: 0,
+                strings: 0,
+            };
+            for row in rows {
+                let value_type = row
+                    .get(column)
+                    .map(WSVValue::value_type)
+                    .unwrap_or(ValueType::Null);
+                match value_type {
+                    ValueType::Null => report.nulls += 1,
+                    ValueType::Bool => report.bools += 1,
+                    ValueType::Int => report.ints += 1,
+                    ValueType::Float => report.floats += 1,
+                    ValueType::String => report.strings += 1,
+                }
+                report.value_type = widen(report.value_type, value_type);
+            }
+            report
+        })
+        .collect()
+}
+
+fn widen(current: ValueType, next: ValueType) -> ValueType {
+    match (current, next) {
+        (current, ValueType::Null) => current,
+        (ValueType::Null, next) => next,
+        (current, next) if current == next => current,
+        (ValueType::Int, ValueType::Float) | (ValueType::Float, ValueType::Int) => ValueType::Float,
+        _ => ValueType::String,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{column_types, infer, infer_value, InferOptions, ValueType, WSVValue};
+
+    #[test]
+    fn infers_values_and_column_types() {
+        let options = InferOptions::default();
+        let rows = infer(
+            "id price zip ok\n1 2.5 01234 TRUE\n-2 3 \"-\" false\n3",
+            &options,
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                WSVValue::Int(-2),
+                WSVValue::Int(3),
+                WSVValue::String("-".to_string()),
+                WSVValue::Bool(false),
+            ],
+            rows[2]
+        );
+        assert_eq!(WSVValue::String("01234".to_string()), rows[1][2]);
+
+        let types = column_types(&rows[1..]);
+        assert_eq!(
+            vec![
+                ValueType::Int,
+                ValueType::Float,
+                ValueType::String,
+                ValueType::Bool
+            ],
+            types.iter().map(|t| t.value_type).collect::<Vec<_>>()
+        );
+        assert!(!types[0].nullable());
+        assert!(types[1].nullable());
+        assert_eq!((1, 1), (types[1].ints, types[1].floats));
+
+        assert_eq!(
+            WSVValue::String("NaN".to_string()),
+            infer_value(Some("NaN"), &options)
+        );
+        let options = InferOptions {
+            true_values: vec!["yes".to_string()],
+            false_values: vec!["no".to_string()],
+            ignore_case: false,
+            numbers: false,
+            keep_leading_zeros: false,
+        };
+        assert_eq!(WSVValue::Bool(true), infer_value(Some("yes"), &options));
+        assert_eq!(
+            WSVValue::String("Yes".to_string()),
+            infer_value(Some("Yes"), &options)
+        );
+        assert_eq!(
+            WSVValue::String("7".to_string()),
+            infer_value(Some("7"), &options)
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::{WSVError, WSVToken, WSVTokenizer};
+
+/// An opt-in string interner for parse results. Every distinct value
+/// passed through the interner is stored once, and all later copies of
+/// that value share the same allocation. This is useful for large files
+/// with categorical columns (enum-like values, country codes, etc.) where
+/// the same handful of strings repeat millions of times.
+#[derive(Debug, Default, Clone)]
+pub struct WSVInterner {
+    values: HashSet<Arc<str>>,
+}
+
+impl WSVInterner {
+    /// Creates an empty interner.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the shared copy of the given value, storing it
+    /// first if this is the first time it has been seen.
+    pub fn intern(&mut self, value: &str) -> Arc<str> {
+        if let Some(existing) = self.values.get(value) {
+            return existing.clone();
+        }
+        let value: Arc<str> = Arc::from(value);
+        self.values.insert(value.clone());
+        value
+    }
+
+    /// The number of distinct values stored in the interner.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether the interner has no stored values.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Wraps a lazy row iterator (such as the one returned by parse_lazy or
+    /// a WSVReader) so that every value it yields is interned.
+    pub fn intern_rows<Rows, Row, Str, Err>(&mut self, rows: Rows) -> WSVInternedRows<'_, Rows>
+    where
+        Rows: Iterator<Item = Result<Row, Err>>,
+        Row: IntoIterator<Item = Option<Str>>,
+        Str: AsRef<str>,
+    {
+        WSVInternedRows {
+            rows,
+            interner: self,
+        }
+    }
+}
+
+/// Same as parse (see the documentation there for behavior details),
+/// but every value is passed through the given interner so repeated
+/// values share one allocation. The interner may be reused across
+/// multiple calls to share values between files.
+pub fn parse_interned(
+    source_text: &str,
+    interner: &mut WSVInterner,
+) -> Result<Vec<Vec<Option<Arc<str>>>>, WSVError> {
+    let mut result = vec![Vec::new()];
+    for fallible_token in WSVTokenizer::new(source_text) {
+        match fallible_token? {
+            WSVToken::LF => result.push(Vec::new()),
+            WSVToken::Null => result.last_mut().unwrap().push(None),
+            WSVToken::Value(value) => result
+                .last_mut()
+                .unwrap()
+                .push(Some(interner.intern(&value))),
+            WSVToken::Comment(_) => {}
+        }
+    }
+
+    if result.last().is_some_and(|line| line.is_empty()) {
+        result.pop();
+    }
+
+    Ok(result)
+}
+
+/// An iterator adapter that interns every value from the wrapped row
+/// iterator. See WSVInterner::intern_rows.
+pub struct WSVInternedRows<'interner, Rows> {
+    rows: Rows,
+    interner: &'interner mut WSVInterner,
+}
+
+impl<'interner, Rows, Row, Str, Err> Iterator for WSVInternedRows<'interner, Rows>
+where
+    Rows: Iterator<Item = Result<Row, Err>>,
+    Row: IntoIterator<Item = Option<Str>>,
+    Str: AsRef<str>,
+{
+    type Item = Result<Vec<Option<Arc<str>>>, Err>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let row = match self.rows.next()? {
+            Err(err) => return Some(Err(err)),
+            Ok(row) => row,
+        };
+        Some(Ok(row
+            .into_iter()
+            .map(|value| value.map(|value| self.interner.intern(value.as_ref())))
+            .collect()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{parse, parse_interned, parse_lazy, WSVInterner};
+
+    #[test]
+    fn interned_values_share_allocations() {
+        let input = "US active\nDE inactive\nUS active\n-  active";
+        let mut interner = WSVInterner::new();
+        let rows = parse_interned(input, &mut interner).unwrap();
+        assert_eq!(4, interner.len());
+        assert!(Arc::ptr_eq(
+            rows[0][0].as_ref().unwrap(),
+            rows[2][0].as_ref().unwrap()
+        ));
+        assert!(Arc::ptr_eq(
+            rows[0][1].as_ref().unwrap(),
+            rows[3][1].as_ref().unwrap()
+        ));
+        assert_eq!(None, rows[3][0]);
+
+        let expected = parse(input).unwrap();
+        for (expected, actual) in expected.iter().zip(rows.iter()) {
+            assert_eq!(
+                expected.iter().map(|v| v.as_deref()).collect::<Vec<_>>(),
+                actual.iter().map(|v| v.as_deref()).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn interns_lazy_rows() {
+        let mut interner = WSVInterner::new();
+        let rows = interner
+            .intern_rows(parse_lazy("a b\nb a\na a".chars()))
+            .map(|row| row.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(3, rows.len());
+        assert!(Arc::ptr_eq(
+            rows[0][0].as_ref().unwrap(),
+            rows[2][1].as_ref().unwrap()
+        ));
+        assert_eq!(2, interner.len());
+    }
+}
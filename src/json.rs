@@ -0,0 +1,320 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::io::Write;
+
+use serde_json::{Map, Value};
+
+use crate::{WSVConvertError, WSVError};
+
+/// Converts a table into a JSON array with one object per data row. The
+/// first row is used as the header row and its values become the keys
+/// of every object. Values are always JSON strings and nulls ('-') are
+/// JSON nulls. Rows that are shorter than the header leave the missing
+/// keys out of their object.
+pub fn to_json<Row, Str>(rows: &[Row]) -> Result<Value, WSVJsonError>
+where
+    Row: AsRef<[Option<Str>]>,
+    Str: AsRef<str>,
+{
+    let Some((header, rows)) = rows.split_first() else {
+        return Ok(Value::Array(Vec::new()));
+    };
+
+    let mut keys = Vec::with_capacity(header.as_ref().len());
+    for (column, key) in header.as_ref().iter().enumerate() {
+        let Some(key) = key.as_ref().map(|key| key.as_ref()) else {
+            return Err(WSVJsonError::NullHeader { column });
+        };
+        if keys.contains(&key) {
+            return Err(WSVJsonError::DuplicateHeader {
+                name: key.to_string(),
+            });
+        }
+        keys.push(key);
+    }
+
+    let mut objects = Vec::with_capacity(rows.len());
+    for (index, row) in rows.iter().enumerate() {
+        let row = row.as_ref();
+        if row.len() > keys.len() {
+            return Err(WSVJsonError::ExtraValue {
+                row: index + 1,
+                column: keys.len(),
+            });
+        }
+        let object = keys
+            .iter()
+            .zip(row)
+            .map(|(key, value)| {
+                let value = match value {
+                    None => Value::Null,
+                    Some(value) => Value::String(value.as_ref().to_string()),
+                };
+                (key.to_string(), value)
+            })
+            .collect::<Map<_, _>>();
+        objects.push(Value::Object(object));
+    }
+    Ok(Value::Array(objects))
+}
+
+/// Converts a JSON array of objects (as written by to_json) back into
+/// rows, starting with a header row. The header is made up of every key
+/// used by any of the objects, in the order they are first seen. Keys
+/// missing from an object become nulls, as do JSON nulls. Numbers and
+/// booleans are written out as their JSON text, while nested arrays and
+/// objects are an error.
+pub fn from_json(json: &Value) -> Result<Vec<Vec<Option<String>>>, WSVJsonError> {
+    let Value::Array(objects) = json else {
+        return Err(WSVJsonError::NotAnArray);
+    };
+
+    let mut objects_checked = Vec::with_capacity(objects.len());
+    let mut keys = Vec::<&str>::new();
+    for (index, object) in objects.iter().enumerate() {
+        let Value::Object(object) = object else {
+            return Err(WSVJsonError::NotAnObject { index });
+        };
+        for key in object.keys() {
+            if !keys.contains(&key.as_str()) {
+                keys.push(key);
+            }
+        }
+        objects_checked.push((index, object));
+    }
+
+    let mut rows = Vec::with_capacity(objects.len() + 1);
+    if keys.is_empty() {
+        return Ok(rows);
+    }
+    rows.push(keys.iter().map(|key| Some(key.to_string())).collect());
+    for (index, object) in objects_checked {
+        let row = keys
+            .iter()
+            .map(|key| match object.get(*key) {
+                None | Some(Value::Null) => Ok(None),
+                Some(Value::String(value)) => Ok(Some(value.clone())),
+                Some(value @ (Value::Bool(_) | Value::Number(_))) => Ok(Some(value.to_string())),
+                Some(Value::Array(_) | Value::Object(_)) => Err(WSVJsonError::NestedValue {
+                    index,
+                    key: key.to_string(),
+                }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// Converts a stream of rows (such as the ones from parse_lazy) into JSON
+/// Lines, writing one JSON object per row to the writer as the rows come
+/// in, so the whole file never has to be in memory. The given headers are
+/// used as the keys of every object and should be unique. Each value is
+/// mapped the same way as to_json. Rows without any values (blank or
+/// comment-only lines) are skipped.
+pub fn to_jsonl<Lines, Header, W>(
+    lines: Lines,
+    headers: &[Header],
+    mut writer: W,
+) -> Result<(), WSVConvertError>
+where
+    Lines: IntoIterator<Item = Result<Vec<Option<String>>, WSVError>>,
+    Header: AsRef<str>,
+    W: Write,
+{
+    let keys = headers
+        .iter()
+        .map(|header| serde_json::to_string(header.as_ref()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(std::io::Error::from)?;
+
+    let mut buffer = Vec::new();
+    for (line_index, row) in lines.into_iter().enumerate() {
+        let row = row?;
+        if row.is_empty() {
+            continue;
+        }
+        if row.len() > keys.len() {
+            return Err(WSVConvertError::InvalidInput {
+                line: line_index + 1,
+                message: "Value Without Header",
+            });
+        }
+
+        buffer.clear();
+        buffer.push(b'{');
+        for (i, (key, value)) in keys.iter().zip(row.iter()).enumerate() {
+            if i != 0 {
+                buffer.push(b',');
+            }
+            buffer.extend_from_slice(key.as_bytes());
+            buffer.push(b':');
+            serde_json::to_writer(&mut buffer, value).map_err(std::io::Error::from)?;
+        }
+        buffer.extend_from_slice(b"}\n");
+        writer.write_all(&buffer)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// An error from to_json or from_json.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WSVJsonError {
+    /// A value in the header row was null, so it can't be used as a key.
+    NullHeader { column: usize },
+    /// The same name is used by more than one column of the header row.
+    DuplicateHeader { name: String },
+    /// A data row has more values than the header row. The row number
+    /// is 0 based and counts the header row.
+    ExtraValue { row: usize, column: usize },
+    /// The JSON value passed to from_json is not an array.
+    NotAnArray,
+    /// The item at the given index of the array is not an object.
+    NotAnObject { index: usize },
+    /// The object at the given index of the array has an array or
+    /// object as the value of the given key.
+    NestedValue { index: usize, key: String },
+}
+
+impl Display for WSVJsonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WSVJsonError::NullHeader { column } => {
+                write!(f, "(value: {}) Null Header", column + 1)
+            }
+            WSVJsonError::DuplicateHeader { name } => write!(f, "Duplicate Header ({:?})", name),
+            WSVJsonError::ExtraValue { row, column } => write!(
+                f,
+                "(line: {}, value: {}) Value Without Header",
+                row + 1,
+                column + 1
+            ),
+            WSVJsonError::NotAnArray => write!(f, "Expected A JSON Array"),
+            WSVJsonError::NotAnObject { index } => {
+                write!(f, "(index: {}) Expected A JSON Object", index)
+            }
+            WSVJsonError::NestedValue { index, key } => {
+                write!(f, "(index: {}) Nested Value For Key {:?}", index, key)
+            }
+        }
+    }
+}
+
+impl Error for WSVJsonError {}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{from_json, parse, parse_lazy, to_json, to_jsonl, WSVConvertError, WSVJsonError};
+
+    #[test]
+    fn converts_to_and_from_json() {
+        let rows = parse("id name note\n1 ann -\n2 \"bob b\"").unwrap();
+        let json = to_json(&rows).unwrap();
+        assert_eq!(
+            json!([
+                { "id": "1", "name": "ann", "note": null },
+                { "id": "2", "name": "bob b" },
+            ]),
+            json
+        );
+
+        let back = from_json(&json).unwrap();
+        assert_eq!(3, back.len());
+        assert_eq!(None, back[2][2]);
+        assert_eq!(
+            rows[..2]
+                .iter()
+                .map(|row| row.iter().map(|v| v.as_deref()).collect::<Vec<_>>())
+                .collect::<Vec<_>>(),
+            back[..2]
+                .iter()
+                .map(|row| row.iter().map(|v| v.as_deref()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+
+        let mixed = from_json(&json!([{ "a": 1 }, { "b": true, "a": "x" }])).unwrap();
+        assert_eq!(
+            vec![
+                vec![Some("a".to_string()), Some("b".to_string())],
+                vec![Some("1".to_string()), None],
+                vec![Some("x".to_string()), Some("true".to_string())],
+            ],
+            mixed
+        );
+    }
+
+    #[test]
+    fn keeps_column_order() {
+        let rows = parse("name id\nann 1\nbob -").unwrap();
+        let json = to_json(&rows).unwrap();
+        assert_eq!(
+            r#"[{"name":"ann","id":"1"},{"name":"bob","id":null}]"#,
+            json.to_string()
+        );
+        let back = from_json(&json).unwrap();
+        assert_eq!(
+            vec![Some("name".to_string()), Some("id".to_string())],
+            back[0]
+        );
+
+        let json = serde_json::from_str(r#"[{"z":1,"a":2},{"m":3}]"#).unwrap();
+        let back = from_json(&json).unwrap();
+        assert_eq!(
+            vec![
+                Some("z".to_string()),
+                Some("a".to_string()),
+                Some("m".to_string())
+            ],
+            back[0]
+        );
+    }
+
+    #[test]
+    fn rejects_unmappable_values() {
+        let rows = parse("a a\n1 2").unwrap();
+        assert_eq!(
+            Err(WSVJsonError::DuplicateHeader {
+                name: "a".to_string()
+            }),
+            to_json(&rows)
+        );
+        let rows = parse("a b\n1 2 3").unwrap();
+        assert_eq!(
+            Err(WSVJsonError::ExtraValue { row: 1, column: 2 }),
+            to_json(&rows)
+        );
+        assert_eq!(
+            Err(WSVJsonError::NestedValue {
+                index: 1,
+                key: "a".to_string()
+            }),
+            from_json(&json!([{ "a": 1 }, { "a": [1] }]))
+        );
+        assert_eq!(Err(WSVJsonError::NotAnArray), from_json(&json!({})));
+    }
+
+    #[test]
+    fn streams_json_lines() {
+        let input = "1 \"a \"\"b\"\"\" -\n\n# comment\n2 c";
+        let mut output = Vec::new();
+        to_jsonl(
+            parse_lazy(input.chars()),
+            &["id", "name", "note"],
+            &mut output,
+        )
+        .unwrap();
+        assert_eq!(
+            "{\"id\":\"1\",\"name\":\"a \\\"b\\\"\",\"note\":null}\n{\"id\":\"2\",\"name\":\"c\"}\n",
+            String::from_utf8(output).unwrap()
+        );
+
+        let result = to_jsonl(parse_lazy("1\n1 2".chars()), &["id"], std::io::sink());
+        assert!(matches!(
+            result,
+            Err(WSVConvertError::InvalidInput { line: 2, .. })
+        ));
+    }
+}
@@ -0,0 +1,337 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use crate::{parse, WSVTokenizer, NEWLINE};
+
+/// Text values that are commonly used to mean "no value" in place of '-'.
+const NULL_LIKE_LITERALS: [&str; 6] = ["", "null", "nil", "none", "na", "n/a"];
+
+/// Checks a .wsv (whitespace separated value) file for things that are
+/// legal but suspicious, such as rows with a different number of values
+/// than the header or numeric columns with a stray non-numeric value.
+/// This is intended for CI checks on hand-edited data files.
+///
+/// The first non-empty row is treated as the header row. Lines with
+/// syntax errors are skipped, use validate to find those.
+pub fn lint(source_text: &str) -> Vec<WSVLintWarning> {
+    let mut warnings = Vec::new();
+    let mut rows = Vec::new();
+
+    for (line_index, line) in source_text.split(NEWLINE).enumerate() {
+        let line_number = line_index + 1;
+        // The '\r' of a "\r\n" line ending isn't trailing whitespace.
+        let content = line.strip_suffix('\r').unwrap_or(line);
+        if content.ends_with(WSVTokenizer::is_whitespace) {
+            warnings.push(WSVLintWarning::new(
+                LintKind::TrailingWhitespace,
+                line_number,
+                None,
+            ));
+        }
+        if let Ok(Some(row)) = parse(line).map(|mut rows| rows.pop()) {
+            if !row.is_empty() {
+                rows.push((line_number, row));
+            }
+        }
+    }
+
+    let Some((header_line, header)) = rows.first() else {
+        warnings.sort_by_key(|warning| (warning.line, warning.column));
+        return warnings;
+    };
+
+    let mut seen_names = HashMap::new();
+    for (column, name) in header.iter().enumerate() {
+        let Some(name) = name else { continue };
+        if let Some(first_column) = seen_names.insert(name.as_ref(), column) {
+            warnings.push(WSVLintWarning::new(
+                LintKind::DuplicateHeader {
+                    name: name.to_string(),
+                    first_column,
+                },
+                *header_line,
+                Some(column),
+            ));
+            seen_names.insert(name.as_ref(), first_column);
+        }
+    }
+
+    for (line, row) in rows.iter().skip(1) {
+        if row.len() != header.len() {
+            warnings.push(WSVLintWarning::new(
+                LintKind::InconsistentColumnCount {
+                    expected: header.len(),
+                    actual: row.len(),
+                },
+                *line,
+                None,
+            ));
+        }
+    }
+
+    // Numeric columns are columns where most of the data values are numbers.
+    // NaN and infinities parse as f64, but aren't numbers for this.
+    let column_count = rows.iter().map(|(_, row)| row.len()).max().unwrap_or(0);
+    for column in 0..column_count {
+        let values = rows
+            .iter()
+            .skip(1)
+            .filter_map(|(line, row)| Some((*line, row.get(column)?.as_deref()?)));
+        let (numeric, other): (Vec<_>, Vec<_>) =
+            values.partition(|(_, value)| value.parse::<f64>().is_ok_and(f64::is_finite));
+        if numeric.len() >= 2 && numeric.len() > other.len() {
+            for (line, value) in other {
+                warnings.push(WSVLintWarning::new(
+                    LintKind::NonNumericValue {
+                        value: value.to_string(),
+                    },
+                    line,
+                    Some(column),
+                ));
+            }
+        }
+    }
+
+    // Null literals are mixed if more than one way of writing "no value"
+    // is used. Everything but the most common one is flagged.
+    let mut null_literals = Vec::new();
+    for (line, row) in rows.iter() {
+        for (column, value) in row.iter().enumerate() {
+            let literal = match value.as_deref() {
+                None => "-",
+                Some(value) if is_null_like(value) => value,
+                Some(_) => continue,
+            };
+            null_literals.push((*line, column, literal));
+        }
+    }
+    let mut counts = Vec::<(&str, usize)>::new();
+    for (_, _, literal) in null_literals.iter() {
+        match counts.iter_mut().find(|(seen, _)| seen == literal) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((literal, 1)),
+        }
+    }
+    if counts.len() > 1 {
+        // max_by_key returns the last max, so reverse to let ties go
+        // to the literal that was seen first.
+        let preferred = counts
+            .iter()
+            .rev()
+            .max_by_key(|(_, count)| *count)
+            .map(|(literal, _)| *literal);
+        for (line, column, literal) in null_literals {
+            if Some(literal) != preferred {
+                warnings.push(WSVLintWarning::new(
+                    LintKind::MixedNullLiteral {
+                        literal: literal.to_string(),
+                    },
+                    line,
+                    Some(column),
+                ));
+            }
+        }
+    }
+
+    warnings.sort_by_key(|warning| (warning.line, warning.column));
+    warnings
+}
+
+fn is_null_like(value: &str) -> bool {
+    NULL_LIKE_LITERALS
+        .iter()
+        .any(|literal| literal.eq_ignore_ascii_case(value))
+}
+
+/// A single warning from lint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct WSVLintWarning {
+    kind: LintKind,
+    line: usize,
+    column: Option<usize>,
+}
+
+impl WSVLintWarning {
+    fn new(kind: LintKind, line: usize, column: Option<usize>) -> Self {
+        Self { kind, line, column }
+    }
+
+    /// What was found.
+    pub fn kind(&self) -> &LintKind {
+        &self.kind
+    }
+
+    /// The line number (1 based) of the warning.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The index (0 based) of the value the warning is about,
+    /// or None if the warning is about the whole line.
+    pub fn column(&self) -> Option<usize> {
+        self.column
+    }
+}
+
+impl Display for WSVLintWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "(line: {}", self.line)?;
+        if let Some(column) = self.column {
+            write!(f, ", value: {}", column + 1)?;
+        }
+        write!(f, ") ")?;
+
+        match &self.kind {
+            LintKind::InconsistentColumnCount { expected, actual } => write!(
+                f,
+                "Inconsistent Column Count (expected {}, found {})",
+                expected, actual
+            ),
+            LintKind::MixedNullLiteral { literal } => {
+                write!(f, "Mixed Null Literal ({:?})", literal)
+            }
+            LintKind::NonNumericValue { value } => {
+                write!(f, "Non-Numeric Value In Numeric Column ({:?})", value)
+            }
+            LintKind::TrailingWhitespace => write!(f, "Trailing Whitespace"),
+            LintKind::DuplicateHeader { name, first_column } => write!(
+                f,
+                "Duplicate Header ({:?}, first used by value {})",
+                name,
+                first_column + 1
+            ),
+        }
+    }
+}
+
+/// The kinds of suspicious things lint looks for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum LintKind {
+    /// A row has a different number of values than the header row.
+    InconsistentColumnCount { expected: usize, actual: usize },
+    /// A value like "null", "N/A" or "" is used to mean "no value" in a
+    /// file that also uses a different literal (such as '-') for that.
+    MixedNullLiteral { literal: String },
+    /// A value in a column where most of the other values are numbers.
+    /// NaN and infinite values count as non-numeric.
+    NonNumericValue { value: String },
+    /// A line ends with whitespace.
+    TrailingWhitespace,
+    /// A header name that was already used by an earlier column.
+    DuplicateHeader { name: String, first_column: usize },
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lint, LintKind};
+
+    #[test]
+    fn flags_suspicious_values() {
+        let input = "id name price id\n\
+                     1 apple 1.50 -\n\
+                     2 pear \"N/A\" - \n\
+                     3 plum 2 \"null\"\n\
+                     4 fig 3 - extra";
+        let warnings = lint(input);
+        let found = warnings
+            .iter()
+            .map(|warning| (warning.line(), warning.column(), warning.kind().clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (
+                    1,
+                    Some(3),
+                    LintKind::DuplicateHeader {
+                        name: "id".to_string(),
+                        first_column: 0
+                    }
+                ),
+                (3, None, LintKind::TrailingWhitespace),
+                (
+                    3,
+                    Some(2),
+                    LintKind::NonNumericValue {
+                        value: "N/A".to_string()
+                    }
+                ),
+                (
+                    3,
+                    Some(2),
+                    LintKind::MixedNullLiteral {
+                        literal: "N/A".to_string()
+                    }
+                ),
+                (
+                    4,
+                    Some(3),
+                    LintKind::MixedNullLiteral {
+                        literal: "null".to_string()
+                    }
+                ),
+                (
+                    5,
+                    None,
+                    LintKind::InconsistentColumnCount {
+                        expected: 4,
+                        actual: 5
+                    }
+                ),
+            ],
+            found
+        );
+        assert_eq!(
+            "(line: 3, value: 3) Non-Numeric Value In Numeric Column (\"N/A\")",
+            warnings[2].to_string()
+        );
+    }
+
+    #[test]
+    fn flags_non_finite_numbers() {
+        let warnings = lint("n\n1\nNaN\n2.5\ninf\n3");
+        assert_eq!(
+            vec![
+                (
+                    3,
+                    LintKind::NonNumericValue {
+                        value: "NaN".to_string()
+                    }
+                ),
+                (
+                    5,
+                    LintKind::NonNumericValue {
+                        value: "inf".to_string()
+                    }
+                ),
+            ],
+            warnings
+                .iter()
+                .map(|warning| (warning.line(), warning.kind().clone()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn clean_files_have_no_warnings() {
+        assert!(lint("").is_empty());
+        assert!(lint("a b\n1 2\n\n# comment\n3 -\n").is_empty());
+        assert!(lint("a b\r\n1 2\r\n\r\n").is_empty());
+    }
+
+    #[test]
+    fn flags_trailing_whitespace_before_crlf() {
+        let warnings = lint("a b \r\n1 2\r\n3 4\t\r\n");
+        assert_eq!(
+            vec![
+                (1, LintKind::TrailingWhitespace),
+                (3, LintKind::TrailingWhitespace)
+            ],
+            warnings
+                .iter()
+                .map(|warning| (warning.line(), warning.kind().clone()))
+                .collect::<Vec<_>>()
+        );
+    }
+}
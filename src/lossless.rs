@@ -0,0 +1,121 @@
+use std::borrow::Cow;
+use std::collections::VecDeque;
+
+use crate::{WSVError, WSVToken, WSVTokenizer};
+
+/// A token from WSVLosslessTokenizer. Unlike WSVToken, the raw source
+/// text of every token is kept, so concatenating the raw text of all
+/// tokens reproduces the input byte for byte.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WSVLosslessToken<'wsv> {
+    /// A run of whitespace between other tokens.
+    Whitespace(&'wsv str),
+    /// A value. raw is the text as written in the source, including any
+    /// quotes and escape sequences, and value is the unescaped value.
+    Value {
+        raw: &'wsv str,
+        value: Cow<'wsv, str>,
+    },
+    /// A null value ('-').
+    Null,
+    /// A comment, including the leading '#'.
+    Comment(&'wsv str),
+    /// A line feed.
+    LF,
+}
+
+impl<'wsv> WSVLosslessToken<'wsv> {
+    /// The text of this token as it appears in the source.
+    pub fn raw(&self) -> &'wsv str {
+        match self {
+            WSVLosslessToken::Whitespace(raw) => raw,
+            WSVLosslessToken::Value { raw, .. } => raw,
+            WSVLosslessToken::Null => "-",
+            WSVLosslessToken::Comment(raw) => raw,
+            WSVLosslessToken::LF => "\n",
+        }
+    }
+}
+
+/// A tokenizer that also emits whitespace and keeps the raw text of
+/// values, for formatters and other tools that need to reproduce the
+/// input exactly. It reports the same errors as WSVTokenizer.
+pub struct WSVLosslessTokenizer<'wsv> {
+    tokenizer: WSVTokenizer<'wsv>,
+    pending: VecDeque<Result<WSVLosslessToken<'wsv>, WSVError>>,
+}
+
+impl<'wsv> WSVLosslessTokenizer<'wsv> {
+    /// Creates a lossless tokenizer from .wsv source text.
+    pub fn new(source_text: &'wsv str) -> Self {
+        Self {
+            tokenizer: WSVTokenizer::new(source_text),
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+impl<'wsv> Iterator for WSVLosslessTokenizer<'wsv> {
+    type Item = Result<WSVLosslessToken<'wsv>, WSVError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(token) = self.pending.pop_front() {
+            return Some(token);
+        }
+
+        let before = self.tokenizer.remainder();
+        let token = self.tokenizer.next();
+        let after = self.tokenizer.remainder();
+        let consumed = &before[..before.len() - after.len()];
+        let text_start = consumed
+            .find(|ch| !WSVTokenizer::is_whitespace(ch))
+            .unwrap_or(consumed.len());
+        let (whitespace, text) = consumed.split_at(text_start);
+
+        if !whitespace.is_empty() {
+            self.pending
+                .push_back(Ok(WSVLosslessToken::Whitespace(whitespace)));
+        }
+        match token {
+            None => {}
+            Some(Err(err)) => self.pending.push_back(Err(err)),
+            Some(Ok(token)) => self.pending.push_back(Ok(match token {
+                WSVToken::LF => WSVLosslessToken::LF,
+                WSVToken::Null => WSVLosslessToken::Null,
+                WSVToken::Comment(_) => WSVLosslessToken::Comment(text),
+                WSVToken::Value(value) => WSVLosslessToken::Value { raw: text, value },
+            })),
+        }
+        self.pending.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{WSVLosslessToken, WSVLosslessTokenizer};
+
+    #[test]
+    fn reconstructs_input_exactly() {
+        for input in [
+            include_str!("../tests/1_stenway.com"),
+            include_str!("../tests/my_test.txt"),
+            "",
+            "  \t",
+            "a  \"b\"\"c\"\t- #note  \n\n  \"x\"/\"y\"  ",
+        ] {
+            let raw = WSVLosslessTokenizer::new(input)
+                .map(|token| token.unwrap().raw())
+                .collect::<String>();
+            assert_eq!(input, raw);
+        }
+
+        let tokens = WSVLosslessTokenizer::new(" \"a\"\"b\"")
+            .map(Result::unwrap)
+            .collect::<Vec<_>>();
+        assert_eq!(WSVLosslessToken::Whitespace(" "), tokens[0]);
+        assert!(matches!(
+            &tokens[1],
+            WSVLosslessToken::Value { raw: "\"a\"\"b\"", value } if value == "a\"b"
+        ));
+    }
+}
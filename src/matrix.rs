@@ -0,0 +1,141 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use nalgebra::{DMatrix, Scalar};
+
+/// Parses every value of the given rows into a matrix with one matrix
+/// row per WSV row. All rows must have the same number of values and
+/// every value must parse as T. Nulls are not allowed. If the data has a
+/// header row, pass in the rows after it.
+pub fn to_matrix<T, Row, Str>(rows: &[Row]) -> Result<DMatrix<T>, WSVMatrixError>
+where
+    T: Scalar + FromStr,
+    Row: AsRef<[Option<Str>]>,
+    Str: AsRef<str>,
+{
+    let column_count = rows.first().map(|row| row.as_ref().len()).unwrap_or(0);
+    let mut values = Vec::with_capacity(rows.len() * column_count);
+    for (row_index, row) in rows.iter().enumerate() {
+        let row = row.as_ref();
+        if row.len() != column_count {
+            return Err(WSVMatrixError::InconsistentColumnCount {
+                row: row_index,
+                expected: column_count,
+                actual: row.len(),
+            });
+        }
+        for (column, value) in row.iter().enumerate() {
+            let Some(value) = value else {
+                return Err(WSVMatrixError::Null {
+                    row: row_index,
+                    column,
+                });
+            };
+            let Ok(value) = value.as_ref().parse() else {
+                return Err(WSVMatrixError::InvalidValue {
+                    row: row_index,
+                    column,
+                    value: value.as_ref().to_string(),
+                });
+            };
+            values.push(value);
+        }
+    }
+    Ok(DMatrix::from_row_iterator(rows.len(), column_count, values))
+}
+
+/// Converts a matrix into rows of values that can be written out with
+/// WSVWriter, with one WSV row per matrix row.
+pub fn from_matrix<T>(matrix: &DMatrix<T>) -> Vec<Vec<Option<String>>>
+where
+    T: Scalar + Display,
+{
+    matrix
+        .row_iter()
+        .map(|row| row.iter().map(|value| Some(value.to_string())).collect())
+        .collect()
+}
+
+/// An error from to_matrix. Rows and columns are 0 based indexes into
+/// the rows that were passed in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WSVMatrixError {
+    /// A row has a different number of values than the first row.
+    InconsistentColumnCount {
+        row: usize,
+        expected: usize,
+        actual: usize,
+    },
+    /// A value is null ('-').
+    Null { row: usize, column: usize },
+    /// A value could not be parsed into the matrix's element type.
+    InvalidValue {
+        row: usize,
+        column: usize,
+        value: String,
+    },
+}
+
+impl Display for WSVMatrixError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WSVMatrixError::InconsistentColumnCount {
+                row,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "(row: {}) Inconsistent Column Count (expected {}, found {})",
+                row + 1,
+                expected,
+                actual
+            ),
+            WSVMatrixError::Null { row, column } => {
+                write!(f, "(row: {}, value: {}) Null Value", row + 1, column + 1)
+            }
+            WSVMatrixError::InvalidValue { row, column, value } => write!(
+                f,
+                "(row: {}, value: {}) Invalid Value ({:?})",
+                row + 1,
+                column + 1,
+                value
+            ),
+        }
+    }
+}
+
+impl Error for WSVMatrixError {}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::DMatrix;
+
+    use crate::{from_matrix, parse, to_matrix, WSVMatrixError, WSVWriter};
+
+    #[test]
+    fn converts_to_and_from_matrix() {
+        let rows = parse("x y z\n1 2 3\n4.5 5 -6").unwrap();
+        let matrix = to_matrix::<f64, _, _>(&rows[1..]).unwrap();
+        assert_eq!(
+            DMatrix::from_row_slice(2, 3, &[1.0, 2.0, 3.0, 4.5, 5.0, -6.0]),
+            matrix
+        );
+
+        let written = WSVWriter::new(from_matrix(&matrix)).to_string();
+        assert_eq!(rows[1..], parse(&written).unwrap());
+
+        assert_eq!(
+            Err(WSVMatrixError::InvalidValue {
+                row: 0,
+                column: 0,
+                value: "x".to_string()
+            }),
+            to_matrix::<f64, _, _>(&rows)
+        );
+        assert_eq!(
+            Err(WSVMatrixError::Null { row: 1, column: 1 }),
+            to_matrix::<i32, _, _>(&parse("1 2\n3 -").unwrap())
+        );
+    }
+}
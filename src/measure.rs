@@ -0,0 +1,109 @@
+use crate::{measure_value, WriterOptions};
+
+/// Measures the width of each column the same way the aligned writer
+/// does, so widths can be computed once (ex. over a sample of a large
+/// file) and handed to WriterOptions::column_widths to write the rest of
+/// the rows lazily. The widths account for quotes and escape sequences
+/// and are measured with the given options.
+pub fn measure_columns<Row, Str>(rows: &[Row], options: &WriterOptions) -> Vec<usize>
+where
+    Row: AsRef<[Option<Str>]>,
+    Str: AsRef<str>,
+{
+    let mut widths = Vec::new();
+    for row in rows {
+        for (column, value) in row.as_ref().iter().enumerate() {
+            let (_, value_len) = measure_value(value.as_ref().map(|value| value.as_ref()), options);
+            match widths.get_mut(column) {
+                None => widths.push(value_len),
+                Some(width) => *width = value_len.max(*width),
+            }
+        }
+    }
+    widths
+}
+
+/// Same as measure_columns, but each width only fits the given fraction
+/// (between 0 and 1) of the values in its column, so a few very long
+/// values don't pad out every other row. A percentile of 0.95 gives
+/// widths that fit 95% of the values. Longer values are written without
+/// padding.
+///
+/// Panics if the percentile is not between 0 and 1.
+pub fn measure_columns_percentile<Row, Str>(
+    rows: &[Row],
+    options: &WriterOptions,
+    percentile: f64,
+) -> Vec<usize>
+where
+    Row: AsRef<[Option<Str>]>,
+    Str: AsRef<str>,
+{
+    assert!(
+        (0.0..=1.0).contains(&percentile),
+        "invalid percentile {}",
+        percentile
+    );
+    let mut histograms = Vec::<Vec<usize>>::new();
+    for row in rows {
+        for (column, value) in row.as_ref().iter().enumerate() {
+            let (_, value_len) = measure_value(value.as_ref().map(|value| value.as_ref()), options);
+            if histograms.len() <= column {
+                histograms.resize_with(column + 1, Vec::new);
+            }
+            histograms[column].push(value_len);
+        }
+    }
+    histograms
+        .into_iter()
+        .map(|mut widths| {
+            widths.sort_unstable();
+            let fitting = (widths.len() as f64 * percentile).ceil() as usize;
+            widths[fitting.clamp(1, widths.len()) - 1]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        measure_columns, measure_columns_percentile, parse, ColumnAlignment, WSVWriter,
+        WriterOptions,
+    };
+
+    #[test]
+    fn measures_columns() {
+        let rows = parse("a \"b c\" -\nlonger x\n1 \"a very long value\" 2").unwrap();
+        let options = WriterOptions::new().alignment(ColumnAlignment::Left);
+        let widths = measure_columns(&rows, &options);
+        assert_eq!(vec![6, 19, 1], widths);
+        assert_eq!(
+            vec![1, 5, 1],
+            measure_columns_percentile(&rows, &options, 0.6)
+        );
+
+        // With known widths the aligned writer doesn't need to measure,
+        // and gives the same output as measuring everything.
+        let lazy = WSVWriter::new(rows.clone())
+            .with_options(options.clone().column_widths(widths))
+            .collect::<String>();
+        assert_eq!(
+            WSVWriter::new(rows.clone())
+                .with_options(options)
+                .to_string(),
+            lazy
+        );
+
+        let narrow = WSVWriter::new(rows)
+            .with_options(
+                WriterOptions::new()
+                    .alignment(ColumnAlignment::Right)
+                    .column_widths(vec![2, 5]),
+            )
+            .to_string();
+        assert_eq!(
+            " a \"b c\" -\nlonger     x\n 1 \"a very long value\" 2",
+            narrow
+        );
+    }
+}
@@ -0,0 +1,325 @@
+use std::collections::{HashMap, VecDeque};
+
+/// The result of a three-way merge. The rows always contain a complete
+/// merged document. Wherever both sides changed the same thing in
+/// different ways, our side wins and a conflict is recorded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WSVMergeResult {
+    pub rows: Vec<Vec<Option<String>>>,
+    pub conflicts: Vec<WSVConflict>,
+}
+
+impl WSVMergeResult {
+    /// Whether the merge finished without any conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// A conflict found by merge or merge_by_key. Row numbers are 0 based
+/// indexes into the merged rows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WSVConflict {
+    /// Both sides changed a cell to different values. The outer Option
+    /// is None if the cell was not in that version of the row at all.
+    /// The merged row has our value.
+    Cell {
+        row: usize,
+        column: usize,
+        base: Option<Option<String>>,
+        ours: Option<Option<String>>,
+        theirs: Option<Option<String>>,
+    },
+    /// One side deleted a row that the other side changed. The changed
+    /// row is kept in the merged rows.
+    DeletedRow { row: usize, deleted_by_ours: bool },
+}
+
+/// Merges two documents (ours and theirs) that were both changed from the
+/// same base document, matching rows up by their position. Changes are
+/// merged cell by cell, so both sides may change different cells of the
+/// same row without a conflict. Use merge_by_key if either side may have
+/// inserted, deleted or reordered rows.
+pub fn merge<RowA, StrA, RowB, StrB, RowC, StrC>(
+    base: &[RowA],
+    ours: &[RowB],
+    theirs: &[RowC],
+) -> WSVMergeResult
+where
+    RowA: AsRef<[Option<StrA>]>,
+    StrA: AsRef<str>,
+    RowB: AsRef<[Option<StrB>]>,
+    StrB: AsRef<str>,
+    RowC: AsRef<[Option<StrC>]>,
+    StrC: AsRef<str>,
+{
+    let len = base.len().max(ours.len()).max(theirs.len());
+    let aligned = (0..len).map(|row| {
+        (
+            base.get(row).map(borrow_row),
+            ours.get(row).map(borrow_row),
+            theirs.get(row).map(borrow_row),
+        )
+    });
+    merge_aligned(aligned)
+}
+
+/// Same as merge, but matches rows up by the value in the given key column
+/// (see diff_by_key for how keys are matched). The merged rows are in our
+/// order, followed by the rows that only theirs added. Rows without a key
+/// can't be matched, so they are treated as new rows on each side.
+pub fn merge_by_key<RowA, StrA, RowB, StrB, RowC, StrC>(
+    base: &[RowA],
+    ours: &[RowB],
+    theirs: &[RowC],
+    key_column: usize,
+) -> WSVMergeResult
+where
+    RowA: AsRef<[Option<StrA>]>,
+    StrA: AsRef<str>,
+    RowB: AsRef<[Option<StrB>]>,
+    StrB: AsRef<str>,
+    RowC: AsRef<[Option<StrC>]>,
+    StrC: AsRef<str>,
+{
+    let base = base.iter().map(borrow_row).collect::<Vec<_>>();
+    let ours = ours.iter().map(borrow_row).collect::<Vec<_>>();
+    let theirs = theirs.iter().map(borrow_row).collect::<Vec<_>>();
+
+    let mut base_by_key = index_by_key(&base, key_column);
+    let mut theirs_by_key = index_by_key(&theirs, key_column);
+    let mut theirs_matched = vec![false; theirs.len()];
+    let mut aligned = Vec::new();
+
+    for row in ours.iter() {
+        let key = row.get(key_column).copied().flatten();
+        let base_row = key.and_then(|key| base_by_key.get_mut(key)?.pop_front());
+        let their_row = key.and_then(|key| theirs_by_key.get_mut(key)?.pop_front());
+        if let Some(their_row) = their_row {
+            theirs_matched[their_row] = true;
+        }
+        aligned.push((
+            base_row.map(|row| base[row].clone()),
+            Some(row.clone()),
+            their_row.map(|row| theirs[row].clone()),
+        ));
+    }
+    for (row, matched) in theirs.iter().zip(theirs_matched) {
+        if !matched {
+            let key = row.get(key_column).copied().flatten();
+            let base_row = key.and_then(|key| base_by_key.get_mut(key)?.pop_front());
+            aligned.push((
+                base_row.map(|row| base[row].clone()),
+                None,
+                Some(row.clone()),
+            ));
+        }
+    }
+
+    merge_aligned(aligned.into_iter())
+}
+
+type BorrowedRow<'a> = Vec<Option<&'a str>>;
+
+fn borrow_row<'a, Row, Str>(row: &'a Row) -> BorrowedRow<'a>
+where
+    Row: AsRef<[Option<Str>]>,
+    Str: AsRef<str> + 'a,
+{
+    row.as_ref()
+        .iter()
+        .map(|value| value.as_ref().map(|value| value.as_ref()))
+        .collect()
+}
+
+fn index_by_key<'a>(
+    rows: &[BorrowedRow<'a>],
+    key_column: usize,
+) -> HashMap<&'a str, VecDeque<usize>> {
+    let mut by_key = HashMap::<&str, VecDeque<usize>>::new();
+    for (index, row) in rows.iter().enumerate() {
+        if let Some(Some(key)) = row.get(key_column) {
+            by_key.entry(*key).or_default().push_back(index);
+        }
+    }
+    by_key
+}
+
+/// Merges (base, ours, theirs) versions of the same rows. None means
+/// the row is not in that version.
+fn merge_aligned<'a, Rows>(aligned: Rows) -> WSVMergeResult
+where
+    Rows: Iterator<
+        Item = (
+            Option<BorrowedRow<'a>>,
+            Option<BorrowedRow<'a>>,
+            Option<BorrowedRow<'a>>,
+        ),
+    >,
+{
+    let mut result = WSVMergeResult::default();
+    for (base, ours, theirs) in aligned {
+        let row = result.rows.len();
+        let merged = if ours == theirs || theirs == base {
+            ours
+        } else if ours == base {
+            theirs
+        } else {
+            match (&ours, &theirs) {
+                (None, Some(_)) | (Some(_), None) => {
+                    result.conflicts.push(WSVConflict::DeletedRow {
+                        row,
+                        deleted_by_ours: ours.is_none(),
+                    });
+                    ours.or(theirs)
+                }
+                (Some(ours), Some(theirs)) => Some(merge_cells(
+                    row,
+                    base.as_deref().unwrap_or_default(),
+                    ours,
+                    theirs,
+                    &mut result.conflicts,
+                )),
+                // ours == theirs was already handled above.
+                (None, None) => None,
+            }
+        };
+
+        if let Some(merged) = merged {
+            result
+                .rows
+                .push(merged.into_iter().map(|v| v.map(str::to_string)).collect());
+        }
+    }
+    result
+}
+
+fn merge_cells<'a>(
+    row: usize,
+    base: &[Option<&'a str>],
+    ours: &[Option<&'a str>],
+    theirs: &[Option<&'a str>],
+    conflicts: &mut Vec<WSVConflict>,
+) -> BorrowedRow<'a> {
+    let mut merged = Vec::new();
+    for column in 0..base.len().max(ours.len()).max(theirs.len()) {
+        let base = base.get(column).copied();
+        let ours = ours.get(column).copied();
+        let theirs = theirs.get(column).copied();
+        let value = if ours == theirs || theirs == base {
+            ours
+        } else if ours == base {
+            theirs
+        } else {
+            conflicts.push(WSVConflict::Cell {
+                row,
+                column,
+                base: base.map(|v| v.map(str::to_string)),
+                ours: ours.map(|v| v.map(str::to_string)),
+                theirs: theirs.map(|v| v.map(str::to_string)),
+            });
+            ours
+        };
+
+        // A cell can only be missing from the end of a row, so if an
+        // earlier cell was dropped while a later one is kept, the gap
+        // is filled with nulls.
+        if let Some(value) = value {
+            merged.resize(column, None);
+            merged.push(value);
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{merge, merge_by_key, parse, WSVConflict};
+
+    fn rows(result: &[Vec<Option<String>>]) -> Vec<Vec<Option<&str>>> {
+        result
+            .iter()
+            .map(|row| row.iter().map(|v| v.as_deref()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn merges_cells_by_position() {
+        let base = parse("a b c\n1 2 3\nx y").unwrap();
+        let ours = parse("A b c\n1 2 3 4\nx y").unwrap();
+        let theirs = parse("a b C\n1 two 3\nx z").unwrap();
+        let result = merge(&base, &ours, &theirs);
+        assert!(result.is_clean());
+        assert_eq!(
+            vec![
+                vec![Some("A"), Some("b"), Some("C")],
+                vec![Some("1"), Some("two"), Some("3"), Some("4")],
+                vec![Some("x"), Some("z")],
+            ],
+            rows(&result.rows)
+        );
+
+        let theirs = parse("B b c\n1 2 3 5\n").unwrap();
+        let result = merge(&base, &ours, &theirs);
+        assert_eq!(
+            vec![
+                WSVConflict::Cell {
+                    row: 0,
+                    column: 0,
+                    base: Some(Some("a".to_string())),
+                    ours: Some(Some("A".to_string())),
+                    theirs: Some(Some("B".to_string())),
+                },
+                WSVConflict::Cell {
+                    row: 1,
+                    column: 3,
+                    base: None,
+                    ours: Some(Some("4".to_string())),
+                    theirs: Some(Some("5".to_string())),
+                },
+            ],
+            result.conflicts
+        );
+        // theirs deleted the unchanged last row.
+        assert_eq!(2, result.rows.len());
+        assert_eq!(Some("A"), result.rows[0][0].as_deref());
+    }
+
+    #[test]
+    fn merges_rows_by_key() {
+        let base = parse("id name\n1 ann\n2 bob\n3 cy").unwrap();
+        let ours = parse("id name\n3 cy\n1 anne\n2 bob\n4 dee").unwrap();
+        let theirs = parse("id name\n1 ann\n3 cyd\n5 eve").unwrap();
+        let result = merge_by_key(&base, &ours, &theirs, 0);
+        assert!(result.is_clean());
+        assert_eq!(
+            vec![
+                vec![Some("id"), Some("name")],
+                vec![Some("3"), Some("cyd")],
+                vec![Some("1"), Some("anne")],
+                vec![Some("4"), Some("dee")],
+                vec![Some("5"), Some("eve")],
+            ],
+            rows(&result.rows)
+        );
+
+        let theirs = parse("id name\n1 ann\n3 cy").unwrap();
+        let ours = parse("id name\n1 ann\n2 bobby\n").unwrap();
+        let result = merge_by_key(&base, &ours, &theirs, 0);
+        assert_eq!(
+            vec![WSVConflict::DeletedRow {
+                row: 2,
+                deleted_by_ours: false
+            }],
+            result.conflicts
+        );
+        assert_eq!(
+            vec![
+                vec![Some("id"), Some("name")],
+                vec![Some("1"), Some("ann")],
+                vec![Some("2"), Some("bobby")],
+            ],
+            rows(&result.rows)
+        );
+    }
+}
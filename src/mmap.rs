@@ -0,0 +1,69 @@
+use std::borrow::Cow;
+use std::fs::File;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::{parse, WSVReadError};
+
+/// A .wsv file mapped into memory. Parsing a mapped file returns values
+/// that borrow directly from the map, so the file never has to be read
+/// into a String first and only values with escape sequences allocate.
+pub struct WSVMappedFile {
+    map: Mmap,
+}
+
+impl WSVMappedFile {
+    /// Maps the file at the given path into memory and checks that
+    /// it is valid UTF-8.
+    ///
+    /// # Safety
+    /// The file must not be modified (by this or any other process) while
+    /// it is mapped. See memmap2::Mmap::map for details.
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
+        let file = File::open(path)?;
+        let map = Mmap::map(&file)?;
+        if let Err(err) = std::str::from_utf8(&map) {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, err));
+        }
+        Ok(Self { map })
+    }
+
+    /// The contents of the mapped file.
+    pub fn as_str(&self) -> &str {
+        // Safety: the contents were checked when the map was opened, and
+        // the caller of open promised that the file won't change.
+        unsafe { std::str::from_utf8_unchecked(&self.map) }
+    }
+
+    /// Parses the mapped file. See parse for behavior details.
+    /// The values borrow from the map, so they can't outlive it.
+    pub fn parse(&self) -> Result<Vec<Vec<Option<Cow<'_, str>>>>, WSVReadError> {
+        Ok(parse(self.as_str())?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use std::fs;
+
+    use crate::{parse, WSVMappedFile};
+
+    #[test]
+    fn parses_mapped_file() {
+        let path =
+            std::env::temp_dir().join(format!("whitespacesv_mmap_{}.wsv", std::process::id()));
+        let contents = include_str!("../tests/1_stenway.com");
+        fs::write(&path, contents).unwrap();
+
+        let mapped = unsafe { WSVMappedFile::open(&path) }.unwrap();
+        let rows = mapped.parse().unwrap();
+        assert_eq!(parse(contents).unwrap(), rows);
+        assert!(matches!(rows[1][0], Some(Cow::Borrowed("~"))));
+
+        drop(rows);
+        drop(mapped);
+        fs::remove_file(&path).unwrap();
+    }
+}
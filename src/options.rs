@@ -0,0 +1,877 @@
+use std::borrow::Cow;
+
+use crate::columns::push_capped;
+use crate::{
+    check_column_order, reorder_values, ColumnAlignment, ColumnOverflow, WSVError, WSVErrorType,
+    WSVToken, WSVTokenizer,
+};
+
+/// When the WSVWriter puts quotes around a value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ValueQuoting {
+    /// Only quote values that would not read back the same without
+    /// quotes (ex. values with whitespace, '"' or '#', empty strings and
+    /// values that look like nulls).
+    #[default]
+    Necessary,
+    /// Quote every value except nulls.
+    Always,
+}
+
+/// The line ending the WSVWriter puts between rows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    /// "\r\n". The carriage return is whitespace in WSV, so files with
+    /// these line endings parse the same as files with plain line feeds.
+    CrLf,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+/// The encoding the writers use for their byte output, one of the ones
+/// ReliableTXT allows. Both UTF-16 encodings start with a byte order mark
+/// so readers can tell them apart, which the Stenway tools on Windows
+/// expect. UTF-8 is written without one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputEncoding {
+    #[default]
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl OutputEncoding {
+    /// The byte order mark written at the start of the output.
+    pub fn bom(&self) -> &'static [u8] {
+        match self {
+            OutputEncoding::Utf8 => &[],
+            OutputEncoding::Utf16Le => &[0xFF, 0xFE],
+            OutputEncoding::Utf16Be => &[0xFE, 0xFF],
+        }
+    }
+
+    /// Encodes a single char into the buffer, returning its bytes.
+    pub(crate) fn encode_char<'buf>(&self, ch: char, buffer: &'buf mut [u8; 4]) -> &'buf [u8] {
+        let to_bytes = match self {
+            OutputEncoding::Utf8 => return ch.encode_utf8(buffer).as_bytes(),
+            OutputEncoding::Utf16Le => u16::to_le_bytes,
+            OutputEncoding::Utf16Be => u16::to_be_bytes,
+        };
+        let mut units = [0; 2];
+        let units = ch.encode_utf16(&mut units);
+        for (index, unit) in units.iter().enumerate() {
+            buffer[index * 2..index * 2 + 2].copy_from_slice(&to_bytes(*unit));
+        }
+        &buffer[..units.len() * 2]
+    }
+
+    /// Encodes the text, appending it to the bytes.
+    pub(crate) fn encode_str(&self, text: &str, bytes: &mut Vec<u8>) {
+        if *self == OutputEncoding::Utf8 {
+            bytes.extend_from_slice(text.as_bytes());
+            return;
+        }
+        let mut buffer = [0; 4];
+        for ch in text.chars() {
+            bytes.extend_from_slice(self.encode_char(ch, &mut buffer));
+        }
+    }
+}
+
+/// All of the settings of a WSVWriter, passed to WSVWriter::with_options.
+/// For example, to write tab separated, right aligned rows with "\r\n"
+/// line endings, use
+///
+/// ```rust
+/// use whitespacesv::{ColumnAlignment, LineEnding, WSVWriter, WriterOptions};
+///
+/// let options = WriterOptions::new()
+///     .alignment(ColumnAlignment::Right)
+///     .separator("\t")
+///     .line_ending(LineEnding::CrLf);
+/// let wsv = WSVWriter::new([[Some("a"), Some("bb")], [Some("ccc"), None]])
+///     .with_options(options)
+///     .to_string();
+/// assert_eq!("  a\tbb\r\nccc\t -", wsv);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriterOptions {
+    pub(crate) alignment: ColumnAlignment,
+    pub(crate) quoting: ValueQuoting,
+    pub(crate) null_token: String,
+    pub(crate) separator: String,
+    pub(crate) line_ending: LineEnding,
+    pub(crate) trailing_line_ending: bool,
+    pub(crate) column_widths: Option<Vec<usize>>,
+    pub(crate) encoding: OutputEncoding,
+    #[cfg(feature = "unicode-normalization")]
+    pub(crate) nfc: bool,
+}
+
+impl WriterOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the column alignment. Defaults to ColumnAlignment::Packed.
+    /// Note: Left and Right alignments cannot use lazy evaluation unless
+    /// column_widths is set.
+    pub fn alignment(mut self, alignment: ColumnAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Sets when values are quoted. Defaults to ValueQuoting::Necessary.
+    pub fn quoting(mut self, quoting: ValueQuoting) -> Self {
+        self.quoting = quoting;
+        self
+    }
+
+    /// Sets the text written for nulls. Defaults to "-", which is the
+    /// only null the WSV format itself knows about, so other tokens only
+    /// read back as nulls with a parser configured to match. Non-null
+    /// values equal to the token are quoted so they stay strings.
+    ///
+    /// Panics if the token is empty or contains whitespace, '"' or '#',
+    /// since it could not be read back as a single value.
+    pub fn null_token(mut self, null_token: impl Into<String>) -> Self {
+        let null_token = null_token.into();
+        assert!(
+            !null_token.is_empty()
+                && !null_token
+                    .chars()
+                    .any(|ch| ch == '"' || ch == '#' || WSVTokenizer::is_whitespace(ch)),
+            "invalid null token {:?}",
+            null_token
+        );
+        self.null_token = null_token;
+        self
+    }
+
+    /// Sets the whitespace written between values. Defaults to a single
+    /// space. With Left or Right alignment, padding is still written with
+    /// spaces.
+    ///
+    /// Panics if the separator is empty or contains anything other than
+    /// WSV whitespace (line feeds included).
+    pub fn separator(mut self, separator: impl Into<String>) -> Self {
+        let separator = separator.into();
+        assert!(
+            !separator.is_empty()
+                && separator
+                    .chars()
+                    .all(|ch| ch != '\n' && WSVTokenizer::is_whitespace(ch)),
+            "invalid separator {:?}",
+            separator
+        );
+        self.separator = separator;
+        self
+    }
+
+    /// Sets the line ending between rows. Defaults to LineEnding::Lf.
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Sets whether the last row is followed by a line ending too.
+    /// Defaults to false. Parsing keeps blank lines as empty rows (unless
+    /// ParserOptions::skip_blank_lines is set) but drops the empty line
+    /// after a trailing line feed, so without this empty rows at the end
+    /// are lost when the output is parsed again. With it, writing parsed
+    /// rows and parsing them again always gives back the same rows,
+    /// blank lines included.
+    pub fn trailing_line_ending(mut self, trailing_line_ending: bool) -> Self {
+        self.trailing_line_ending = trailing_line_ending;
+        self
+    }
+
+    /// Sets the width of each column for Left and Right alignment, so
+    /// the rows don't all have to be measured first and the output can
+    /// be written lazily. The widths can come from measure_columns run
+    /// over a sample of the rows. Values that are wider than their column,
+    /// and columns past the end of the widths, are written without any
+    /// padding.
+    pub fn column_widths(mut self, column_widths: Vec<usize>) -> Self {
+        self.column_widths = Some(column_widths);
+        self
+    }
+
+    /// Reorders the column widths to match rows whose columns were put in
+    /// the given order, as with WSVDocument::reorder_columns. order[i] is
+    /// the index the column at i had before. Does nothing if no widths are
+    /// set.
+    ///
+    /// Panics unless the order names each column exactly once.
+    pub fn reorder_columns(mut self, order: &[usize]) -> Self {
+        check_column_order(order, order.len());
+        if let Some(column_widths) = &mut self.column_widths {
+            reorder_values(column_widths, order);
+        }
+        self
+    }
+
+    /// Sets the encoding of the byte output: reading from a WSVWriter,
+    /// WSVWriter::into_bytes_iter, WSVPushWriter, WSVAppender and the
+    /// files written by rewrite_file and split_with_options. Strings and
+    /// the char iterator are unaffected. Defaults to OutputEncoding::Utf8.
+    pub fn encoding(mut self, encoding: OutputEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Sets whether values are converted to Unicode Normalization Form C
+    /// before they are written, so text mixing composed and decomposed
+    /// characters (ex. "é" as one or two code points) is written the
+    /// same way. Defaults to false.
+    #[cfg(feature = "unicode-normalization")]
+    pub fn nfc(mut self, nfc: bool) -> Self {
+        self.nfc = nfc;
+        self
+    }
+
+    /// Applies the value normalization settings to a value that is
+    /// about to be written.
+    pub(crate) fn normalize<'value>(&self, value: &'value str) -> Cow<'value, str> {
+        #[cfg(feature = "unicode-normalization")]
+        if self.nfc {
+            return nfc(Cow::Borrowed(value));
+        }
+        Cow::Borrowed(value)
+    }
+}
+
+impl Default for WriterOptions {
+    fn default() -> Self {
+        Self {
+            alignment: ColumnAlignment::default(),
+            quoting: ValueQuoting::default(),
+            null_token: "-".to_string(),
+            separator: " ".to_string(),
+            line_ending: LineEnding::default(),
+            trailing_line_ending: false,
+            column_widths: None,
+            encoding: OutputEncoding::default(),
+            #[cfg(feature = "unicode-normalization")]
+            nfc: false,
+        }
+    }
+}
+
+/// The characters the parser treats as whitespace between values. Line
+/// feeds always end the row, whichever set is used.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum WhitespaceSet {
+    /// Every whitespace character in the WSV specification.
+    #[default]
+    Wsv,
+    /// Only ASCII whitespace (space, tab, vertical tab, form feed and
+    /// carriage return), so values may contain characters like the
+    /// non-breaking space.
+    Ascii,
+    /// The characters the function returns true for.
+    Custom(fn(char) -> bool),
+}
+
+impl WhitespaceSet {
+    /// Whether the character separates values.
+    pub fn contains(&self, ch: char) -> bool {
+        match self {
+            WhitespaceSet::Wsv => WSVTokenizer::is_whitespace(ch),
+            WhitespaceSet::Ascii => matches!(ch, ' ' | '\t' | '\u{000B}' | '\u{000C}' | '\r'),
+            WhitespaceSet::Custom(is_whitespace) => ch != '\n' && is_whitespace(ch),
+        }
+    }
+}
+
+/// Where this crate knowingly deviates from the Stenway WSV
+/// specification (https://dev.stenway.com/WSV/Specification.html), so
+/// the parser can match other consumers exactly. The defaults keep this
+/// crate's existing behavior, which is not fully compliant. Use
+/// SpecCompliance::STRICT to follow the specification in every case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpecCompliance {
+    /// Whether a carriage return (U+000D) separates values. The spec
+    /// lists it as whitespace, which is why "\r\n" line endings parse
+    /// like "\n", but consumers that only split on spaces and tabs keep
+    /// it as part of the value. Defaults to true.
+    pub carriage_return_is_whitespace: bool,
+    /// Whether a line without any characters is an empty row. The spec
+    /// makes every line a row. Consumers that drop empty lines before
+    /// parsing don't, though lines of only whitespace or comments are
+    /// still rows. Defaults to true.
+    pub empty_lines_are_rows: bool,
+    /// Whether the empty line after a trailing line feed is a row. The
+    /// spec splits the text on every line feed, so "a\n" is 2 rows, the
+    /// second empty, and an empty text is a single empty row. This crate
+    /// drops an empty last row instead, so that files ending in a line
+    /// feed read as they look. Defaults to false.
+    pub trailing_line_feed_is_row: bool,
+}
+
+impl SpecCompliance {
+    /// Follows the specification exactly.
+    pub const STRICT: SpecCompliance = SpecCompliance {
+        carriage_return_is_whitespace: true,
+        empty_lines_are_rows: true,
+        trailing_line_feed_is_row: true,
+    };
+}
+
+impl Default for SpecCompliance {
+    fn default() -> Self {
+        Self {
+            carriage_return_is_whitespace: true,
+            empty_lines_are_rows: true,
+            trailing_line_feed_is_row: false,
+        }
+    }
+}
+
+/// All of the settings of the parser, for parse_with_options and the
+/// WSVTokenizer and WSVLazyTokenizer with_options constructors. The
+/// defaults keep this crate's existing behavior, so
+/// `parse_with_options(text, &ParserOptions::default())` is the same as
+/// `parse(text)`. For strict spec compliance, set
+/// `.compliance(SpecCompliance::STRICT)`.
+#[derive(Debug, Clone)]
+pub struct ParserOptions {
+    pub(crate) null_literal: Cow<'static, str>,
+    pub(crate) comment_char: Option<char>,
+    pub(crate) whitespace: WhitespaceSet,
+    pub(crate) strict: bool,
+    pub(crate) max_columns: Option<(usize, ColumnOverflow)>,
+    pub(crate) max_cell_len: Option<usize>,
+    pub(crate) compliance: SpecCompliance,
+    pub(crate) line_ending: LineEnding,
+    pub(crate) normalize_line_breaks: bool,
+    pub(crate) skip_blank_lines: bool,
+    pub(crate) map_cells: Option<fn(&str) -> Cow<'_, str>>,
+    #[cfg(feature = "unicode-normalization")]
+    pub(crate) nfc: bool,
+}
+
+impl ParserOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the unquoted value that is read as a null. Defaults to "-".
+    /// Quoted values are never nulls.
+    ///
+    /// Panics if the literal is empty or contains whitespace, '"' or '#',
+    /// since it could never be read as a single unquoted value.
+    pub fn null_literal(mut self, null_literal: impl Into<String>) -> Self {
+        let null_literal = null_literal.into();
+        assert!(
+            !null_literal.is_empty()
+                && !null_literal
+                    .chars()
+                    .any(|ch| ch == '"' || ch == '#' || WSVTokenizer::is_whitespace(ch)),
+            "invalid null literal {:?}",
+            null_literal
+        );
+        self.null_literal = Cow::Owned(null_literal);
+        self
+    }
+
+    /// Sets the character that starts a comment, or None to read it as
+    /// part of values instead. Defaults to '#'.
+    pub fn comment_char(mut self, comment_char: Option<char>) -> Self {
+        self.comment_char = comment_char;
+        self
+    }
+
+    /// Sets the characters that separate values. Defaults to
+    /// WhitespaceSet::Wsv.
+    pub fn whitespace(mut self, whitespace: WhitespaceSet) -> Self {
+        self.whitespace = whitespace;
+        self
+    }
+
+    /// Sets whether quotes in the wrong place are errors. Defaults to
+    /// true. When false, a '"' inside an unquoted value is part of the
+    /// value instead of an InvalidDoubleQuoteAfterValue error, and a
+    /// value directly after a closing quote starts a new value instead
+    /// of being an InvalidCharacterAfterString error.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Caps the number of columns in each row (see parse_with_max_columns).
+    /// Defaults to no limit. Only parse_with_options applies this, since
+    /// the tokenizers don't know about rows.
+    pub fn max_columns(mut self, max_columns: usize, overflow: ColumnOverflow) -> Self {
+        self.max_columns = Some((max_columns, overflow));
+        self
+    }
+
+    /// Caps the length in bytes of a single value (after unescaping
+    /// quoted values). Longer values are a CellTooLarge error located at
+    /// the start of the value. Defaults to no limit. The WSVLazyTokenizer
+    /// stops reading as soon as a value passes the limit, so an unclosed
+    /// string can't pull the rest of the input into memory.
+    pub fn max_cell_len(mut self, max_cell_len: usize) -> Self {
+        self.max_cell_len = Some(max_cell_len);
+        self
+    }
+
+    /// Sets where the parser deviates from the WSV specification.
+    /// Defaults to SpecCompliance::default(). The tokenizers only use
+    /// carriage_return_is_whitespace, since the others are about rows.
+    pub fn compliance(mut self, compliance: SpecCompliance) -> Self {
+        self.compliance = compliance;
+        self
+    }
+
+    /// Sets which line breaks end a row. Defaults to LineEnding::Lf,
+    /// where only a line feed ends a row and the carriage return of a
+    /// "\r\n" is whitespace. With LineEnding::CrLf, "\r\n" is read as
+    /// a single line break, so the carriage return is never part of a
+    /// value or counted as a column, even when carriage returns aren't
+    /// whitespace (see SpecCompliance). Lone line feeds still end rows,
+    /// so files with mixed line endings read the same either way.
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Sets whether "\r\n" and lone "\r" inside quoted values are
+    /// replaced with "\n" when they are read, so values written on
+    /// different platforms compare equal. Defaults to false.
+    pub fn normalize_line_breaks(mut self, normalize_line_breaks: bool) -> Self {
+        self.normalize_line_breaks = normalize_line_breaks;
+        self
+    }
+
+    /// Sets whether rows without any values (blank, whitespace-only and
+    /// comment-only lines) are left out of the output of
+    /// parse_with_options. Defaults to false, where only an empty last
+    /// row is dropped (see SpecCompliance).
+    pub fn skip_blank_lines(mut self, skip_blank_lines: bool) -> Self {
+        self.skip_blank_lines = skip_blank_lines;
+        self
+    }
+
+    /// Sets a function that every non-null value is passed through as it
+    /// is read (ex. to trim, lowercase or strip stray byte order marks),
+    /// so values are normalized in the same pass as parsing. Returning
+    /// Cow::Borrowed keeps unchanged values from being copied. Nulls are
+    /// found before mapping, so mapping can't turn a value into a null.
+    /// Defaults to None.
+    pub fn map_cells(mut self, map_cells: fn(&str) -> Cow<'_, str>) -> Self {
+        self.map_cells = Some(map_cells);
+        self
+    }
+
+    /// Sets whether values are converted to Unicode Normalization Form C
+    /// as they are read, before map_cells, so that values mixing composed
+    /// and decomposed characters compare equal. Defaults to false.
+    #[cfg(feature = "unicode-normalization")]
+    pub fn nfc(mut self, nfc: bool) -> Self {
+        self.nfc = nfc;
+        self
+    }
+
+    /// Applies the value normalization settings to a value that was
+    /// just read.
+    pub(crate) fn finish_value<'wsv>(&self, value: Cow<'wsv, str>) -> Cow<'wsv, str> {
+        #[cfg(feature = "unicode-normalization")]
+        let value = if self.nfc { nfc(value) } else { value };
+        match (self.map_cells, value) {
+            (None, value) => value,
+            (Some(map_cells), Cow::Borrowed(value)) => map_cells(value),
+            (Some(map_cells), Cow::Owned(value)) => Cow::Owned(map_cells(&value).into_owned()),
+        }
+    }
+
+    /// The whitespace test the tokenizers use, combining the whitespace
+    /// set with the carriage return setting.
+    pub(crate) fn is_whitespace(&self) -> impl Fn(char) -> bool + Copy {
+        let whitespace = self.whitespace;
+        let carriage_return = self.compliance.carriage_return_is_whitespace;
+        move |ch| (carriage_return || ch != '\r') && whitespace.contains(ch)
+    }
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self {
+            null_literal: Cow::Borrowed("-"),
+            comment_char: Some('#'),
+            whitespace: WhitespaceSet::default(),
+            strict: true,
+            max_columns: None,
+            max_cell_len: None,
+            compliance: SpecCompliance::default(),
+            line_ending: LineEnding::default(),
+            normalize_line_breaks: false,
+            skip_blank_lines: false,
+            map_cells: None,
+            #[cfg(feature = "unicode-normalization")]
+            nfc: false,
+        }
+    }
+}
+
+/// Converts a value to Unicode Normalization Form C, only copying it if
+/// it isn't already normalized.
+#[cfg(feature = "unicode-normalization")]
+fn nfc(value: Cow<'_, str>) -> Cow<'_, str> {
+    use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+
+    match is_nfc_quick(value.chars()) {
+        IsNormalized::Yes => value,
+        _ => Cow::Owned(value.nfc().collect()),
+    }
+}
+
+/// Replaces "\r\n" and lone "\r" with "\n".
+pub(crate) fn normalize_line_breaks(value: &str) -> String {
+    value.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// Same as parse (see the documentation there for behavior details),
+/// but reads the source text with the given options. For example, to
+/// read a file that uses "NULL" for nulls and has no comments, use
+///
+/// ```rust
+/// use whitespacesv::{parse_with_options, ParserOptions};
+///
+/// let options = ParserOptions::new()
+///     .null_literal("NULL")
+///     .comment_char(None);
+/// let rows = parse_with_options("#1 NULL -", &options).unwrap();
+/// assert_eq!(vec![Some("#1".into()), None, Some("-".into())], rows[0]);
+/// ```
+pub fn parse_with_options<'wsv>(
+    source_text: &'wsv str,
+    options: &ParserOptions,
+) -> Result<Vec<Vec<Option<Cow<'wsv, str>>>>, WSVError> {
+    let compliance = options.compliance;
+    let mut result = vec![Vec::new()];
+    let mut tokenizer = WSVTokenizer::with_options(source_text, options.clone());
+    let mut line_start = 0;
+
+    while let Some(fallible_token) = tokenizer.next() {
+        let value = match fallible_token? {
+            WSVToken::LF => {
+                let line_end = tokenizer.current_location.byte_index;
+                // Reuse the row of an empty line for the next line.
+                if compliance.empty_lines_are_rows || line_end != line_start {
+                    result.push(Vec::new());
+                }
+                line_start = tokenizer.offset();
+                continue;
+            }
+            WSVToken::Comment(_) => continue,
+            WSVToken::Null => None,
+            WSVToken::Value(value) => Some(value),
+        };
+
+        let line = result.last_mut().unwrap();
+        match options.max_columns {
+            None => line.push(value),
+            Some((max_columns, overflow)) => {
+                if !push_capped(line, value, max_columns, overflow) {
+                    return Err(WSVError {
+                        err_type: WSVErrorType::TooManyColumns,
+                        location: tokenizer.current_location.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    let last_line_empty = line_start == source_text.len();
+    if result.last().is_some_and(|line| line.is_empty())
+        && (!compliance.trailing_line_feed_is_row
+            || (last_line_empty && !compliance.empty_lines_are_rows))
+    {
+        result.pop();
+    }
+
+    if options.skip_blank_lines {
+        result.retain(|row| !row.is_empty());
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::{
+        parse, parse_with_options, ColumnAlignment, ColumnOverflow, LineEnding, OwnedWSVToken,
+        ParserOptions, SpecCompliance, ValueQuoting, WSVErrorType, WSVLazyTokenizer, WSVWriter,
+        WhitespaceSet, WriterOptions,
+    };
+
+    #[test]
+    fn writes_with_options() {
+        let rows = vec![
+            vec![Some("NULL"), None, Some("a b")],
+            vec![Some("-"), Some("x"), Some("")],
+        ];
+        let options = WriterOptions::new()
+            .quoting(ValueQuoting::Always)
+            .null_token("NULL");
+        assert_eq!(
+            "\"NULL\" NULL \"a b\" \n\"-\" \"x\" \"\" ",
+            WSVWriter::new(rows.clone())
+                .with_options(options.clone())
+                .to_string()
+        );
+        assert_eq!(
+            "\"NULL\" NULL \"a b\"\n   \"-\"  \"x\"    \"\"",
+            WSVWriter::new(rows.clone())
+                .with_options(options.alignment(ColumnAlignment::Right))
+                .to_string()
+        );
+
+        let written = WSVWriter::new(rows.clone())
+            .with_options(WriterOptions::new().separator(" \t"))
+            .to_string();
+        assert_eq!("NULL \t- \t\"a b\" \t\n\"-\" \tx \t\"\" \t", written);
+        assert_eq!(
+            rows,
+            parse(&written)
+                .unwrap()
+                .iter()
+                .map(|row| row.iter().map(|value| value.as_deref()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_non_whitespace_separator() {
+        WriterOptions::new().separator(",");
+    }
+
+    #[test]
+    fn rejects_invalid_null_literals() {
+        for literal in ["", "a b", "a\tb", "\"a\"", "#"] {
+            let result = std::panic::catch_unwind(|| ParserOptions::new().null_literal(literal));
+            assert!(result.is_err(), "{:?} was accepted", literal);
+        }
+    }
+
+    #[test]
+    fn parses_with_options() {
+        let input = "a\u{00A0}b \"c\"d e\"f NULL # x\n1 2 3 4";
+        assert_eq!(
+            WSVErrorType::InvalidCharacterAfterString,
+            parse_with_options(input, &ParserOptions::new())
+                .unwrap_err()
+                .err_type()
+        );
+
+        let options = ParserOptions::new()
+            .null_literal("NULL")
+            .whitespace(WhitespaceSet::Ascii)
+            .strict(false)
+            .max_columns(3, ColumnOverflow::Collapse);
+        assert_eq!(
+            vec![
+                vec![
+                    Some("a\u{00A0}b".into()),
+                    Some("c".into()),
+                    Some("d e\"f -".into())
+                ],
+                vec![Some("1".into()), Some("2".into()), Some("3 4".into())],
+            ],
+            parse_with_options(input, &options).unwrap()
+        );
+
+        let tokens = WSVLazyTokenizer::with_options(
+            "a;b - ;".chars(),
+            ParserOptions::new()
+                .comment_char(Some(';'))
+                .whitespace(WhitespaceSet::Custom(|ch| ch == ' ')),
+        )
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+        assert!(matches!(
+            tokens.as_slice(),
+            [
+                OwnedWSVToken::Value(a),
+                OwnedWSVToken::Comment(b),
+            ] if a == "a" && b == "b - ;"
+        ));
+    }
+
+    #[test]
+    fn limits_cell_len() {
+        let options = ParserOptions::new().max_cell_len(4);
+        assert_eq!(
+            vec![vec![Some("abcd".into()), Some("a\nb".into())]],
+            parse_with_options("abcd \"a\"/\"b\"", &options).unwrap()
+        );
+
+        let err = parse_with_options("ok\nab \"a\"\"bcd\"", &options).unwrap_err();
+        assert_eq!(WSVErrorType::CellTooLarge, err.err_type());
+        assert_eq!((2, 4), (err.location().line(), err.location().col()));
+
+        // An unclosed string fails once it passes the limit instead of
+        // reading everything that follows.
+        let mut source = "ab \"unclosed".chars().chain(std::iter::repeat('x'));
+        let err = WSVLazyTokenizer::with_options(source.by_ref(), options)
+            .find_map(Result::err)
+            .unwrap();
+        assert_eq!(WSVErrorType::CellTooLarge, err.err_type());
+        assert_eq!((1, 4), (err.location().line(), err.location().col()));
+        assert_ne!(Some('x'), source.next());
+    }
+
+    #[test]
+    fn parses_with_spec_compliance() {
+        let input = "a\rb\n\n \n";
+        let parse_as = |compliance| {
+            parse_with_options(input, &ParserOptions::new().compliance(compliance)).unwrap()
+        };
+        assert_eq!(
+            vec![vec![Some("a".into()), Some("b".into())], vec![], vec![]],
+            parse_as(SpecCompliance::default())
+        );
+        assert_eq!(
+            vec![
+                vec![Some("a".into()), Some("b".into())],
+                vec![],
+                vec![],
+                vec![]
+            ],
+            parse_as(SpecCompliance::STRICT)
+        );
+        assert_eq!(
+            vec![vec![Some("a\rb".into())], vec![]],
+            parse_as(SpecCompliance {
+                carriage_return_is_whitespace: false,
+                empty_lines_are_rows: false,
+                trailing_line_feed_is_row: true,
+            })
+        );
+        assert_eq!(
+            vec![vec![Some("a".into()), Some("b".into())]],
+            parse_with_options(input, &ParserOptions::new().skip_blank_lines(true)).unwrap()
+        );
+        assert_eq!(
+            vec![Vec::<Option<Cow<str>>>::new()],
+            parse_with_options("", &ParserOptions::new().compliance(SpecCompliance::STRICT))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn reads_crlf_line_breaks() {
+        let input = "a\r\n\r\n\"b\r\"/\"c\" d\r\n";
+        let compliance = SpecCompliance {
+            carriage_return_is_whitespace: false,
+            ..SpecCompliance::default()
+        };
+        let options = ParserOptions::new()
+            .compliance(compliance)
+            .line_ending(LineEnding::CrLf)
+            .normalize_line_breaks(true);
+        let expected = vec![
+            vec![Some("a".into())],
+            vec![],
+            vec![Some("b\nc".into()), Some("d".into())],
+        ];
+        assert_eq!(expected, parse_with_options(input, &options).unwrap());
+        assert_eq!(
+            vec![Some("a\r".into())],
+            parse_with_options(input, &ParserOptions::new().compliance(compliance)).unwrap()[0]
+        );
+
+        let mut tokenizer = WSVLazyTokenizer::with_options(input.chars(), options);
+        let tokens = tokenizer.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+        assert!(matches!(
+            tokens.as_slice(),
+            [
+                OwnedWSVToken::Value(a),
+                OwnedWSVToken::LF,
+                OwnedWSVToken::LF,
+                OwnedWSVToken::Value(b),
+                OwnedWSVToken::Value(d),
+                OwnedWSVToken::LF,
+            ] if a == "a" && b == "b\nc" && d == "d"
+        ));
+    }
+
+    #[test]
+    fn preserves_blank_lines() {
+        let rows = parse("a\n\n# group\nb\n\n\n").unwrap();
+        assert_eq!(6, rows.len());
+        let written = WSVWriter::new(rows.clone()).to_string();
+        assert_eq!(5, parse(&written).unwrap().len());
+
+        for alignment in [ColumnAlignment::Packed, ColumnAlignment::Left] {
+            let options = WriterOptions::new()
+                .alignment(alignment)
+                .trailing_line_ending(true);
+            let written = WSVWriter::new(rows.clone())
+                .with_options(options)
+                .to_string();
+            assert_eq!(rows, parse(&written).unwrap());
+        }
+        let options = WriterOptions::new().trailing_line_ending(true);
+        let empty: Vec<Vec<Option<&str>>> = vec![vec![]];
+        assert_eq!(
+            "\n",
+            WSVWriter::new(empty).with_options(options).to_string()
+        );
+    }
+
+    #[test]
+    fn maps_cells_while_parsing() {
+        let options = ParserOptions::new().map_cells(|value| {
+            let value = value.trim_start_matches('\u{FEFF}');
+            if value.chars().any(|ch| ch.is_uppercase()) {
+                Cow::Owned(value.to_lowercase())
+            } else {
+                Cow::Borrowed(value)
+            }
+        });
+        let rows = parse_with_options("\u{FEFF}id NAME\n1 \"A \"\"B\"\"\" -", &options).unwrap();
+        assert!(matches!(rows[0][0], Some(Cow::Borrowed("id"))));
+        assert_eq!(
+            vec![Some("1".into()), Some("a \"b\"".into()), None],
+            rows[1]
+        );
+
+        let tokens = WSVLazyTokenizer::with_options("X".chars(), options)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(matches!(tokens.as_slice(), [OwnedWSVToken::Value(x)] if x == "x"));
+    }
+
+    #[cfg(feature = "unicode-normalization")]
+    #[test]
+    fn normalizes_to_nfc() {
+        let composed = "caf\u{E9}";
+        let decomposed = "cafe\u{301}";
+        let input = format!("{} \"{}\"", decomposed, composed);
+
+        let rows = parse_with_options(&input, &ParserOptions::new().nfc(true)).unwrap();
+        assert_eq!(rows[0][0], rows[0][1]);
+        assert!(matches!(&rows[0][1], Some(Cow::Borrowed(value)) if *value == composed));
+        assert_ne!(parse(&input).unwrap()[0][0], parse(&input).unwrap()[0][1]);
+
+        let written = WSVWriter::new([[Some(decomposed), Some(composed)]])
+            .with_options(WriterOptions::new().nfc(true))
+            .to_string();
+        assert_eq!(format!("{} {} ", composed, composed), written);
+    }
+}
@@ -0,0 +1,283 @@
+use std::borrow::Cow;
+
+use rayon::prelude::*;
+
+use crate::{
+    map_value, measure_value, parse, write_aligned_value, ColumnAlignment, WSVError, WSVWriter,
+};
+
+/// Inputs smaller than this are not worth splitting up.
+const MIN_CHUNK_LEN: usize = 64 * 1024;
+/// The fewest rows the parallel writer will hand to a single thread.
+const MIN_CHUNK_ROWS: usize = 1024;
+
+/// Same as parse (see the documentation there for behavior details),
+/// but splits the input into chunks and parses them on the rayon thread
+/// pool, merging the results back together in order. A WSV row can never
+/// span multiple lines (line feeds inside strings must be escaped as "/"),
+/// so every line feed in the source is a safe place to split.
+///
+/// If the input contains errors, the error closest to the start of the
+/// input is returned, just like parse would.
+pub fn parse_parallel(source_text: &str) -> Result<Vec<Vec<Option<Cow<'_, str>>>>, WSVError> {
+    let chunk_count = rayon::current_num_threads() * 4;
+    let target_len = (source_text.len() / chunk_count).max(MIN_CHUNK_LEN);
+    if target_len >= source_text.len() {
+        return parse(source_text);
+    }
+
+    let chunks = split_lines(source_text, target_len);
+    let parsed = chunks
+        .par_iter()
+        .map(|chunk| {
+            let line_count = chunk.text.bytes().filter(|byte| *byte == b'\n').count();
+            (line_count, parse(chunk.text))
+        })
+        .collect::<Vec<_>>();
+
+    let mut result = Vec::with_capacity(parsed.iter().map(|(lines, _)| lines + 1).sum());
+    let mut line_offset = 0;
+    for (chunk, (line_count, rows)) in chunks.iter().zip(parsed) {
+        match rows {
+            Err(err) => return Err(err.shifted(line_offset, chunk.byte_offset)),
+            Ok(rows) => result.extend(rows),
+        }
+        line_offset += line_count;
+    }
+    Ok(result)
+}
+
+pub(crate) struct LineChunk<'wsv> {
+    pub(crate) byte_offset: usize,
+    pub(crate) text: &'wsv str,
+}
+
+/// Splits the source into chunks of roughly target_len bytes, only ever
+/// splitting just after a line feed. Every chunk except possibly the last
+/// ends with a line feed.
+pub(crate) fn split_lines(source_text: &str, target_len: usize) -> Vec<LineChunk<'_>> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < source_text.len() {
+        let mut end = (start + target_len.max(1)).min(source_text.len());
+        while !source_text.is_char_boundary(end) {
+            end += 1;
+        }
+        end = match source_text[end..].find('\n') {
+            None => source_text.len(),
+            Some(newline) => end + newline + 1,
+        };
+        chunks.push(LineChunk {
+            byte_offset: start,
+            text: &source_text[start..end],
+        });
+        start = end;
+    }
+    chunks
+}
+
+impl<OuterIter, InnerIter, BorrowStr> WSVWriter<OuterIter, InnerIter, BorrowStr>
+where
+    OuterIter: Iterator<Item = InnerIter>,
+    InnerIter: IntoIterator<Item = Option<BorrowStr>>,
+    BorrowStr: AsRef<str> + Send + Sync,
+{
+    /// Same as to_string, but serializes ranges of rows on the rayon thread
+    /// pool and concatenates the results. For Left and Right alignment, the
+    /// column widths are measured in parallel as well. The input is collected
+    /// into memory first, so this is not lazy for any alignment.
+    pub fn to_string_parallel(self) -> String {
+        let options = self.options;
+        let map_values = self.map_values;
+        let values = self
+            .values
+            .map(|(_, inner)| inner.into_iter().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let rows = values
+            .par_iter()
+            .enumerate()
+            .map(|(row, values)| {
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(column, value)| {
+                        map_value(map_values, row, column, value.as_ref().map(AsRef::as_ref))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let measured = rows
+            .par_iter()
+            .map(|row| {
+                row.iter()
+                    .map(|value| {
+                        measure_value(value.as_ref().map(|value| value.as_ref()), &options)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let max_col_widths = match (&options.alignment, &options.column_widths) {
+            (ColumnAlignment::Packed, _) => Vec::new(),
+            (_, Some(column_widths)) => column_widths.clone(),
+            (ColumnAlignment::Left | ColumnAlignment::Right, None) => measured
+                .par_iter()
+                .fold(Vec::new, |widths, row| {
+                    merge_widths(widths, row.iter().map(|(_, len)| *len))
+                })
+                .reduce(Vec::new, |widths, other| {
+                    merge_widths(widths, other.into_iter())
+                }),
+        };
+
+        let chunk_len = (rows.len() / (rayon::current_num_threads() * 4)).max(MIN_CHUNK_ROWS);
+        let mut result = rows
+            .par_chunks(chunk_len)
+            .zip(measured.par_chunks(chunk_len))
+            .map(|(rows, measured)| {
+                let mut result = String::new();
+                for (line_num, (row, measured)) in rows.iter().zip(measured).enumerate() {
+                    if line_num != 0 {
+                        result.push_str(options.line_ending.as_str());
+                    }
+                    for (i, (value, (needs_quotes, value_len))) in
+                        row.iter().zip(measured).enumerate()
+                    {
+                        let value = value.as_ref().map(|value| value.as_ref());
+                        match options.alignment {
+                            // Match the output of the lazy packed writer exactly,
+                            // which puts a space after every value.
+                            ColumnAlignment::Packed => {
+                                write_aligned_value(&mut result, value, *needs_quotes, 0, &options);
+                                result.push_str(&options.separator);
+                            }
+                            ColumnAlignment::Left | ColumnAlignment::Right => {
+                                if i != 0 {
+                                    result.push_str(&options.separator);
+                                }
+                                write_aligned_value(
+                                    &mut result,
+                                    value,
+                                    *needs_quotes,
+                                    max_col_widths
+                                        .get(i)
+                                        .map_or(0, |width| width.saturating_sub(*value_len)),
+                                    &options,
+                                );
+                            }
+                        }
+                    }
+                }
+                result
+            })
+            .collect::<Vec<_>>()
+            .join(options.line_ending.as_str());
+        if options.trailing_line_ending && !rows.is_empty() {
+            result.push_str(options.line_ending.as_str());
+        }
+        result
+    }
+}
+
+fn merge_widths(mut widths: Vec<usize>, other: impl Iterator<Item = usize>) -> Vec<usize> {
+    for (i, len) in other.enumerate() {
+        match widths.get_mut(i) {
+            None => widths.push(len),
+            Some(longest_len) => *longest_len = len.max(*longest_len),
+        }
+    }
+    widths
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::{parse, parse_parallel, ColumnAlignment, WSVErrorType, WSVWriter};
+
+    fn large_input() -> String {
+        let mut input = String::new();
+        for i in 0..50_000 {
+            input.push_str(&format!("{} \"quoted \"\"{}\"\"\" - # comment\n", i, i));
+            if i % 997 == 0 {
+                input.push('\n');
+            }
+        }
+        input
+    }
+
+    #[test]
+    fn parallel_matches_parse() {
+        let input = large_input();
+        assert_eq!(parse(&input).unwrap(), parse_parallel(&input).unwrap());
+        assert_eq!(
+            parse("a b\n\n").unwrap(),
+            parse_parallel("a b\n\n").unwrap()
+        );
+    }
+
+    #[test]
+    fn parallel_reports_first_error() {
+        let mut input = large_input();
+        input.push_str("\"unclosed\n");
+        input.push_str(&large_input());
+        input.push_str("bad\"\n");
+
+        let expected = parse(&input).unwrap_err();
+        let actual = parse_parallel(&input).unwrap_err();
+        assert_eq!(WSVErrorType::StringNotClosed, actual.err_type());
+        assert_eq!(expected.location().line(), actual.location().line());
+        assert_eq!(expected.location().col(), actual.location().col());
+    }
+
+    #[test]
+    fn parallel_writer_matches_to_string() {
+        let input = large_input();
+        let mut rows = parse(&input)
+            .unwrap()
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|value| value.map(|value| value.into_owned()))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        rows[10].push(Some("a much wider value than the rest".to_string()));
+        rows[20_000].push(None);
+
+        for alignment in [
+            ColumnAlignment::Packed,
+            ColumnAlignment::Left,
+            ColumnAlignment::Right,
+        ] {
+            let expected = WSVWriter::new(rows.clone())
+                .align_columns(alignment.clone())
+                .to_string();
+            let actual = WSVWriter::new(rows.clone())
+                .align_columns(alignment)
+                .to_string_parallel();
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn parallel_writer_maps_values() {
+        fn upper(_row: usize, _column: usize, value: &str) -> Cow<'_, str> {
+            Cow::Owned(value.to_uppercase())
+        }
+        let rows = vec![vec![Some("a"), Some("b")], vec![Some("c d"), None]];
+        for alignment in [ColumnAlignment::Packed, ColumnAlignment::Left] {
+            let expected = WSVWriter::new(rows.clone())
+                .align_columns(alignment.clone())
+                .map_values(upper)
+                .to_string();
+            let actual = WSVWriter::new(rows.clone())
+                .align_columns(alignment)
+                .map_values(upper)
+                .to_string_parallel();
+            assert_eq!(expected, actual);
+            assert!(actual.starts_with("A"));
+        }
+    }
+}
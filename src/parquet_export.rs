@@ -0,0 +1,388 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::fs::{self, File, OpenOptions};
+use std::path::Path;
+use std::sync::Arc;
+
+use parquet::basic::{Repetition, Type as PhysicalType};
+use parquet::data_type::{
+    BoolType, ByteArray, ByteArrayType, DoubleType, FloatType, Int32Type, Int64Type,
+};
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::{SerializedColumnWriter, SerializedFileWriter};
+use parquet::schema::types::Type;
+
+use crate::rewrite::temp_path;
+use crate::WSVError;
+
+/// The number of rows buffered in memory before they are written out
+/// as a row group.
+const ROW_GROUP_SIZE: usize = 64 * 1024;
+
+/// Streams rows (such as the ones from parse_lazy) into a new Parquet
+/// file at the given path. Only one row group is held in memory at a
+/// time, so this works for files of any size. The rows are written to
+/// a temporary file next to the path, which is renamed over it once
+/// everything was written, so a failed export leaves no partial file.
+///
+/// The schema gives the name and type of each column, in the same order
+/// as the values of each row. Columns must be top level primitive
+/// columns of type BOOLEAN, INT32, INT64, FLOAT, DOUBLE or BYTE_ARRAY
+/// (which is written as the text of the value). Nulls are only allowed
+/// in optional columns, and rows with fewer values than the schema are
+/// filled up with nulls. Rows without any values (blank or comment-only
+/// lines) are skipped, and so is a header row if you skip it yourself.
+///
+/// The line in an error is the 1-based position of the row in the
+/// iterator. That is its line number when the rows come straight from
+/// parse_lazy, but rows skipped before this (such as a header) shift it.
+///
+/// Returns the number of rows written.
+pub fn to_parquet<P, Lines>(path: P, schema: &Type, lines: Lines) -> Result<usize, WSVParquetError>
+where
+    P: AsRef<Path>,
+    Lines: IntoIterator<Item = Result<Vec<Option<String>>, WSVError>>,
+{
+    let mut columns = Vec::with_capacity(schema.get_fields().len());
+    for field in schema.get_fields() {
+        columns.push(ColumnBuffer::new(field)?);
+    }
+
+    let path = path.as_ref();
+    let temp_path = temp_path(path);
+    let file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&temp_path)?;
+    let result = (|| {
+        let properties = Arc::new(WriterProperties::builder().build());
+        let mut writer = SerializedFileWriter::new(file, Arc::new(schema.clone()), properties)?;
+        let written = write_rows(&mut writer, &mut columns, lines)?;
+        let file = writer.into_inner()?;
+        file.sync_all()?;
+        fs::rename(&temp_path, path)?;
+        Ok(written)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+fn write_rows<Lines>(
+    writer: &mut SerializedFileWriter<File>,
+    columns: &mut [ColumnBuffer],
+    lines: Lines,
+) -> Result<usize, WSVParquetError>
+where
+    Lines: IntoIterator<Item = Result<Vec<Option<String>>, WSVError>>,
+{
+    let mut buffered = 0;
+    let mut written = 0;
+    for (line_index, row) in lines.into_iter().enumerate() {
+        let row = row?;
+        if row.is_empty() {
+            continue;
+        }
+        if row.len() > columns.len() {
+            return Err(WSVParquetError::ExtraValue {
+                line: line_index + 1,
+                column: columns.len(),
+            });
+        }
+
+        let mut values = row.into_iter();
+        for buffer in columns.iter_mut() {
+            buffer.push(values.next().flatten(), line_index + 1)?;
+        }
+        buffered += 1;
+
+        if buffered == ROW_GROUP_SIZE {
+            write_row_group(writer, columns)?;
+            written += buffered;
+            buffered = 0;
+        }
+    }
+    if buffered != 0 {
+        write_row_group(writer, columns)?;
+        written += buffered;
+    }
+    Ok(written)
+}
+
+fn write_row_group(
+    writer: &mut SerializedFileWriter<File>,
+    columns: &mut [ColumnBuffer],
+) -> Result<(), ParquetError> {
+    let mut row_group = writer.next_row_group()?;
+    for buffer in columns.iter_mut() {
+        let Some(mut column) = row_group.next_column()? else {
+            break;
+        };
+        buffer.write(&mut column)?;
+        column.close()?;
+    }
+    row_group.close()?;
+    Ok(())
+}
+
+/// The values of one column of the row group that is being built.
+struct ColumnBuffer {
+    name: String,
+    optional: bool,
+    values: ColumnValues,
+    definition_levels: Vec<i16>,
+}
+
+enum ColumnValues {
+    Boolean(Vec<bool>),
+    Int32(Vec<i32>),
+    Int64(Vec<i64>),
+    Float(Vec<f32>),
+    Double(Vec<f64>),
+    ByteArray(Vec<ByteArray>),
+}
+
+impl ColumnBuffer {
+    fn new(field: &Type) -> Result<Self, WSVParquetError> {
+        let unsupported = || WSVParquetError::UnsupportedColumn {
+            name: field.name().to_string(),
+        };
+        if !field.is_primitive() {
+            return Err(unsupported());
+        }
+        let optional = match field.get_basic_info().repetition() {
+            Repetition::REQUIRED => false,
+            Repetition::OPTIONAL => true,
+            Repetition::REPEATED => return Err(unsupported()),
+        };
+        let values = match field.get_physical_type() {
+            PhysicalType::BOOLEAN => ColumnValues::Boolean(Vec::new()),
+            PhysicalType::INT32 => ColumnValues::Int32(Vec::new()),
+            PhysicalType::INT64 => ColumnValues::Int64(Vec::new()),
+            PhysicalType::FLOAT => ColumnValues::Float(Vec::new()),
+            PhysicalType::DOUBLE => ColumnValues::Double(Vec::new()),
+            PhysicalType::BYTE_ARRAY => ColumnValues::ByteArray(Vec::new()),
+            _ => return Err(unsupported()),
+        };
+        Ok(Self {
+            name: field.name().to_string(),
+            optional,
+            values,
+            definition_levels: Vec::new(),
+        })
+    }
+
+    fn push(&mut self, value: Option<String>, line: usize) -> Result<(), WSVParquetError> {
+        let Some(value) = value else {
+            if !self.optional {
+                return Err(WSVParquetError::Null {
+                    line,
+                    column: self.name.clone(),
+                });
+            }
+            self.definition_levels.push(0);
+            return Ok(());
+        };
+
+        let invalid = |value: &str| WSVParquetError::InvalidValue {
+            line,
+            column: self.name.clone(),
+            value: value.to_string(),
+        };
+        match &mut self.values {
+            ColumnValues::Boolean(values) => {
+                values.push(value.parse().map_err(|_| invalid(&value))?)
+            }
+            ColumnValues::Int32(values) => values.push(value.parse().map_err(|_| invalid(&value))?),
+            ColumnValues::Int64(values) => values.push(value.parse().map_err(|_| invalid(&value))?),
+            ColumnValues::Float(values) => values.push(value.parse().map_err(|_| invalid(&value))?),
+            ColumnValues::Double(values) => {
+                values.push(value.parse().map_err(|_| invalid(&value))?)
+            }
+            ColumnValues::ByteArray(values) => values.push(ByteArray::from(value.into_bytes())),
+        }
+        if self.optional {
+            self.definition_levels.push(1);
+        }
+        Ok(())
+    }
+
+    /// Writes out and clears the buffered values.
+    fn write(&mut self, column: &mut SerializedColumnWriter<'_>) -> Result<(), ParquetError> {
+        let definition_levels = if self.optional {
+            Some(self.definition_levels.as_slice())
+        } else {
+            None
+        };
+        match &mut self.values {
+            ColumnValues::Boolean(values) => {
+                column
+                    .typed::<BoolType>()
+                    .write_batch(values, definition_levels, None)?;
+                values.clear();
+            }
+            ColumnValues::Int32(values) => {
+                column
+                    .typed::<Int32Type>()
+                    .write_batch(values, definition_levels, None)?;
+                values.clear();
+            }
+            ColumnValues::Int64(values) => {
+                column
+                    .typed::<Int64Type>()
+                    .write_batch(values, definition_levels, None)?;
+                values.clear();
+            }
+            ColumnValues::Float(values) => {
+                column
+                    .typed::<FloatType>()
+                    .write_batch(values, definition_levels, None)?;
+                values.clear();
+            }
+            ColumnValues::Double(values) => {
+                column
+                    .typed::<DoubleType>()
+                    .write_batch(values, definition_levels, None)?;
+                values.clear();
+            }
+            ColumnValues::ByteArray(values) => {
+                column
+                    .typed::<ByteArrayType>()
+                    .write_batch(values, definition_levels, None)?;
+                values.clear();
+            }
+        }
+        self.definition_levels.clear();
+        Ok(())
+    }
+}
+
+/// An error from to_parquet.
+#[derive(Debug)]
+pub enum WSVParquetError {
+    /// An IO error from creating the file.
+    Io(std::io::Error),
+    /// An error from the Parquet writer.
+    Parquet(ParquetError),
+    /// The WSV input was invalid.
+    WSV(WSVError),
+    /// A column in the schema isn't one of the supported types.
+    UnsupportedColumn { name: String },
+    /// A row has more values than the schema has columns.
+    ExtraValue { line: usize, column: usize },
+    /// A null was found in a required column.
+    Null { line: usize, column: String },
+    /// A value could not be parsed into its column's type.
+    InvalidValue {
+        line: usize,
+        column: String,
+        value: String,
+    },
+}
+
+impl Display for WSVParquetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WSVParquetError::Io(err) => write!(f, "{}", err),
+            WSVParquetError::Parquet(err) => write!(f, "{}", err),
+            WSVParquetError::WSV(err) => write!(f, "{}", err),
+            WSVParquetError::UnsupportedColumn { name } => {
+                write!(f, "Unsupported Column Type ({:?})", name)
+            }
+            WSVParquetError::ExtraValue { line, column } => write!(
+                f,
+                "(line: {}, value: {}) Value Without Column",
+                line,
+                column + 1
+            ),
+            WSVParquetError::Null { line, column } => {
+                write!(f, "(line: {}) Null In Required Column {:?}", line, column)
+            }
+            WSVParquetError::InvalidValue {
+                line,
+                column,
+                value,
+            } => write!(
+                f,
+                "(line: {}) Invalid Value For Column {:?} ({:?})",
+                line, column, value
+            ),
+        }
+    }
+}
+
+impl Error for WSVParquetError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WSVParquetError::Io(err) => Some(err),
+            WSVParquetError::Parquet(err) => Some(err),
+            WSVParquetError::WSV(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for WSVParquetError {
+    fn from(value: std::io::Error) -> Self {
+        WSVParquetError::Io(value)
+    }
+}
+
+impl From<ParquetError> for WSVParquetError {
+    fn from(value: ParquetError) -> Self {
+        WSVParquetError::Parquet(value)
+    }
+}
+
+impl From<WSVError> for WSVParquetError {
+    fn from(value: WSVError) -> Self {
+        WSVParquetError::WSV(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::schema::parser::parse_message_type;
+
+    use crate::{parse_lazy, to_parquet, WSVParquetError};
+
+    #[test]
+    fn writes_parquet_files() {
+        let schema = parse_message_type(
+            "message items { required int64 id; optional binary name (UTF8); optional double price; }",
+        )
+        .unwrap();
+        let path = std::env::temp_dir().join(format!("wsv-parquet-{}.parquet", std::process::id()));
+        let input = "id name price\n1 ann 2.5\n\n2 - 10\n3 \"c y\"";
+
+        let written = to_parquet(&path, &schema, parse_lazy(input.chars()).skip(1)).unwrap();
+        assert_eq!(3, written);
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let rows = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "{id: 1, name: \"ann\", price: 2.5}",
+                "{id: 2, name: null, price: 10.0}",
+                "{id: 3, name: \"c y\", price: null}",
+            ],
+            rows
+        );
+
+        let result = to_parquet(&path, &schema, parse_lazy("1 a 2\n- b 3".chars()));
+        assert!(matches!(result, Err(WSVParquetError::Null { line: 2, .. })));
+        // The failed export leaves the earlier file as it was.
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        assert_eq!(3, reader.metadata().file_metadata().num_rows());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,274 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::{parse, WSVError, WSVReadError};
+
+/// The number of lines handed to a worker thread at a time.
+const BATCH_LINE_COUNT: usize = 4096;
+
+/// Parses the .wsv file at the given path on a pool of threads and
+/// hands the parsed rows to worker_fn in batches. The file is read and
+/// decoded on its own thread, parsed on one thread per available core,
+/// and worker_fn is called on the calling thread, so IO and parsing run
+/// at the same time as your processing.
+///
+/// The ordering decides whether batches are handed to worker_fn in the
+/// order they appear in the file or as soon as they finish parsing.
+/// Processing stops at the first error. With BatchOrdering::InOrder,
+/// every batch before the error will have been passed to worker_fn.
+pub fn process_parallel<P, F>(
+    path: P,
+    mut worker_fn: F,
+    ordering: BatchOrdering,
+) -> Result<(), WSVReadError>
+where
+    P: AsRef<Path>,
+    F: FnMut(WSVRowBatch),
+{
+    let file = File::open(path)?;
+    let worker_count = thread::available_parallelism()
+        .map(|count| count.get())
+        .unwrap_or(1);
+
+    let (chunk_sender, chunk_receiver) = sync_channel::<Chunk>(worker_count * 2);
+    let chunk_receiver = Arc::new(Mutex::new(chunk_receiver));
+    let (batch_sender, batch_receiver) = sync_channel(worker_count * 2);
+
+    thread::scope(|scope| {
+        let reader = scope.spawn(move || read_chunks(file, chunk_sender));
+
+        for _ in 0..worker_count {
+            let chunk_receiver = chunk_receiver.clone();
+            let batch_sender = batch_sender.clone();
+            scope.spawn(move || loop {
+                let chunk = match chunk_receiver.lock() {
+                    Err(_) => break,
+                    Ok(receiver) => receiver.recv(),
+                };
+                let chunk = match chunk {
+                    // The reader is done.
+                    Err(_) => break,
+                    Ok(chunk) => chunk,
+                };
+                let index = chunk.index;
+                if batch_sender.send((index, parse_chunk(chunk))).is_err() {
+                    // The consumer stopped early.
+                    break;
+                }
+            });
+        }
+        // Only the workers hold these now, so once they exit the
+        // reader and the consumer will both see a disconnect.
+        drop(chunk_receiver);
+        drop(batch_sender);
+
+        let result = consume_batches(batch_receiver, &mut worker_fn, ordering);
+        // A panic on the reader thread cuts the input short, so it must
+        // not look like the end of the file.
+        let read_result = reader
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+
+        result?;
+        read_result?;
+        Ok(())
+    })
+}
+
+/// The order in which process_parallel hands batches to the worker function.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchOrdering {
+    /// Batches are handed over in the order they appear in the file.
+    #[default]
+    InOrder,
+    /// Batches are handed over as soon as they are parsed.
+    AsCompleted,
+}
+
+/// A batch of consecutive rows from a WSV file.
+#[derive(Debug, Clone)]
+pub struct WSVRowBatch {
+    first_line: usize,
+    rows: Vec<Vec<Option<String>>>,
+}
+
+impl WSVRowBatch {
+    /// The line number in the source file of the first row in this batch.
+    pub fn first_line(&self) -> usize {
+        self.first_line
+    }
+
+    /// The rows in this batch.
+    pub fn rows(&self) -> &[Vec<Option<String>>] {
+        &self.rows
+    }
+
+    /// Takes ownership of the rows in this batch.
+    pub fn into_rows(self) -> Vec<Vec<Option<String>>> {
+        self.rows
+    }
+}
+
+struct Chunk {
+    index: usize,
+    first_line: usize,
+    byte_offset: usize,
+    text: String,
+}
+
+fn read_chunks(file: File, chunk_sender: SyncSender<Chunk>) -> Result<(), std::io::Error> {
+    let mut reader = BufReader::new(file);
+    let mut first_line = 1;
+    let mut byte_offset = 0;
+    let mut index = 0;
+    loop {
+        let mut text = String::new();
+        let mut line_count = 0;
+        while line_count < BATCH_LINE_COUNT {
+            if reader.read_line(&mut text)? == 0 {
+                break;
+            }
+            line_count += 1;
+        }
+
+        if line_count == 0 {
+            return Ok(());
+        }
+
+        let text_len = text.len();
+        let chunk = Chunk {
+            index,
+            first_line,
+            byte_offset,
+            text,
+        };
+        if chunk_sender.send(chunk).is_err() {
+            // All of the workers are gone, so nobody wants the rest.
+            return Ok(());
+        }
+
+        index += 1;
+        first_line += line_count;
+        byte_offset += text_len;
+    }
+}
+
+fn parse_chunk(chunk: Chunk) -> Result<WSVRowBatch, WSVError> {
+    match parse(&chunk.text) {
+        Err(err) => Err(err.shifted(chunk.first_line - 1, chunk.byte_offset)),
+        Ok(rows) => Ok(WSVRowBatch {
+            first_line: chunk.first_line,
+            rows: rows
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|value| value.map(|value| value.into_owned()))
+                        .collect()
+                })
+                .collect(),
+        }),
+    }
+}
+
+fn consume_batches<F: FnMut(WSVRowBatch)>(
+    batch_receiver: Receiver<(usize, Result<WSVRowBatch, WSVError>)>,
+    worker_fn: &mut F,
+    ordering: BatchOrdering,
+) -> Result<(), WSVError> {
+    let mut pending = BTreeMap::new();
+    let mut next_index = 0;
+    for (index, result) in batch_receiver.iter() {
+        match ordering {
+            BatchOrdering::AsCompleted => worker_fn(result?),
+            BatchOrdering::InOrder => {
+                pending.insert(index, result);
+                while let Some(result) = pending.remove(&next_index) {
+                    next_index += 1;
+                    worker_fn(result?);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{parse, process_parallel, BatchOrdering, WSVErrorType, WSVReadError};
+
+    fn temp_file(name: &str, contents: &str) -> std::path::PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("whitespacesv_{}_{}.wsv", name, std::process::id()));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn process_parallel_in_order() {
+        let mut contents = String::new();
+        for i in 0..10_000 {
+            contents.push_str(&format!("{} \"value {}\" - # comment\n", i, i));
+            if i % 1000 == 0 {
+                contents.push('\n');
+            }
+        }
+        let path = temp_file("in_order", &contents);
+
+        let mut rows = Vec::new();
+        let mut next_line = 1;
+        process_parallel(
+            &path,
+            |batch| {
+                assert_eq!(next_line, batch.first_line());
+                next_line += batch.rows().len();
+                rows.extend(batch.into_rows());
+            },
+            BatchOrdering::InOrder,
+        )
+        .unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let expected = parse(&contents)
+            .unwrap()
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|value| value.map(|value| value.into_owned()))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(expected, rows);
+    }
+
+    #[test]
+    fn process_parallel_reports_error_line() {
+        let mut contents = "1 2 3\n".repeat(9000);
+        contents.push_str("\"unclosed\n");
+        contents.push_str(&"1 2 3\n".repeat(100));
+        let path = temp_file("error_line", &contents);
+
+        let mut row_count = 0;
+        let result = process_parallel(
+            &path,
+            |batch| row_count += batch.rows().len(),
+            BatchOrdering::AsCompleted,
+        );
+        fs::remove_file(&path).unwrap();
+
+        match result {
+            Err(WSVReadError::WSV(err)) => {
+                assert_eq!(WSVErrorType::StringNotClosed, err.err_type());
+                assert_eq!(9001, err.location().line());
+            }
+            _ => panic!("Expected a StringNotClosed error"),
+        }
+        assert!(row_count <= 9000);
+    }
+}
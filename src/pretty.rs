@@ -0,0 +1,123 @@
+/// Renders rows as a table with box-drawing borders, for showing parsed
+/// data in a terminal. The first row is drawn as a header, with a line
+/// under it. Nulls are shown as '-', line breaks in values are shown as
+/// "\n" and short rows are filled up with blank cells. Padding is based
+/// on how wide each character is in a terminal, so tables with CJK text
+/// or emoji still line up.
+pub fn pretty<Row, Str>(rows: &[Row]) -> String
+where
+    Row: AsRef<[Option<Str>]>,
+    Str: AsRef<str>,
+{
+    let cells = rows
+        .iter()
+        .map(|row| {
+            row.as_ref()
+                .iter()
+                .map(|value| match value {
+                    None => "-".to_string(),
+                    Some(value) => value.as_ref().replace('\r', "\\r").replace('\n', "\\n"),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut widths = Vec::<usize>::new();
+    for row in cells.iter() {
+        if widths.len() < row.len() {
+            widths.resize(row.len(), 0);
+        }
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(display_width(value));
+        }
+    }
+    if widths.is_empty() {
+        return String::new();
+    }
+
+    let mut result = String::new();
+    push_border(&mut result, &widths, ['┌', '┬', '┐']);
+    for (line_index, row) in cells.iter().enumerate() {
+        if line_index == 1 {
+            push_border(&mut result, &widths, ['├', '┼', '┤']);
+        }
+        result.push('│');
+        for (column, width) in widths.iter().enumerate() {
+            let value = row.get(column).map(String::as_str).unwrap_or_default();
+            result.push(' ');
+            result.push_str(value);
+            result.push_str(&" ".repeat(width - display_width(value)));
+            result.push_str(" │");
+        }
+        result.push('\n');
+    }
+    push_border(&mut result, &widths, ['└', '┴', '┘']);
+    result
+}
+
+fn push_border(result: &mut String, widths: &[usize], [left, middle, right]: [char; 3]) {
+    result.push(left);
+    for (column, width) in widths.iter().enumerate() {
+        if column != 0 {
+            result.push(middle);
+        }
+        result.push_str(&"─".repeat(width + 2));
+    }
+    result.push(right);
+    result.push('\n');
+}
+
+/// The number of terminal columns the text takes up. Combining marks and
+/// other zero width characters take none, while wide East Asian
+/// characters and most emoji take two.
+fn display_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+fn char_width(ch: char) -> usize {
+    match ch as u32 {
+        0x0000..=0x001F
+        | 0x007F..=0x009F
+        | 0x0300..=0x036F
+        | 0x200B..=0x200F
+        | 0x20D0..=0x20FF
+        | 0xFE00..=0xFE0F
+        | 0xFE20..=0xFE2F => 0,
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1F64F
+        | 0x1F900..=0x1F9FF
+        | 0x20000..=0x2FFFD
+        | 0x30000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, pretty};
+
+    #[test]
+    fn draws_bordered_table() {
+        let rows = parse("id name\n1 \"a\"/\"b\"\n22 日本 extra").unwrap();
+        assert_eq!(
+            "┌────┬──────┬───────┐\n\
+             │ id │ name │       │\n\
+             ├────┼──────┼───────┤\n\
+             │ 1  │ a\\nb │       │\n\
+             │ 22 │ 日本 │ extra │\n\
+             └────┴──────┴───────┘\n",
+            pretty(&rows)
+        );
+        assert_eq!("", pretty::<Vec<Option<&str>>, &str>(&[]));
+    }
+}
@@ -0,0 +1,177 @@
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use crate::{WSVError, WSVToken, WSVTokenizer, NEWLINE};
+
+/// An event from a WSVPullParser.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WSVPullEvent<'wsv> {
+    /// The start of a row. Its span is empty, at the start of the line.
+    StartRow,
+    /// A value in the current row. Nulls are None. The value borrows from
+    /// the source unless it had escape sequences, and its span includes
+    /// any quotes.
+    Cell(Option<Cow<'wsv, str>>),
+    /// A comment, without its '#'. Its span includes the '#'.
+    Comment(&'wsv str),
+    /// The end of a row. Its span covers the line feed, or is empty at the
+    /// end of the source.
+    EndRow,
+    /// The end of the source. This is always the last event.
+    Eof,
+}
+
+/// A pull parser that turns WSV source text into row and cell events,
+/// each with the byte range in the source it came from. It sits between
+/// WSVTokenizer, which knows nothing about rows, and parse, which builds
+/// every row, and is meant as a base for zero-copy binding layers.
+///
+/// Rows are the same as parse's: every line is a row, blank and
+/// comment-only lines included, except for a last line with no values. A
+/// comment on that line is still reported, outside of any row. After an
+/// error or Eof, the parser returns None.
+pub struct WSVPullParser<'wsv> {
+    source: &'wsv str,
+    tokenizer: WSVTokenizer<'wsv>,
+    pending: VecDeque<(WSVPullEvent<'wsv>, Range<usize>)>,
+    /// A comment on a line without values, which is only part of a row
+    /// if a line feed follows it.
+    pending_comment: Option<(WSVPullEvent<'wsv>, Range<usize>)>,
+    in_row: bool,
+    line_start: usize,
+    finished: bool,
+}
+
+impl<'wsv> WSVPullParser<'wsv> {
+    /// Creates a pull parser over the source text.
+    pub fn new(source_text: &'wsv str) -> Self {
+        Self {
+            source: source_text,
+            tokenizer: WSVTokenizer::new(source_text),
+            pending: VecDeque::new(),
+            pending_comment: None,
+            in_row: false,
+            line_start: 0,
+            finished: false,
+        }
+    }
+
+    fn start_row(&mut self) {
+        if !self.in_row {
+            self.pending
+                .push_back((WSVPullEvent::StartRow, self.line_start..self.line_start));
+            self.in_row = true;
+        }
+    }
+}
+
+impl<'wsv> Iterator for WSVPullParser<'wsv> {
+    type Item = Result<(WSVPullEvent<'wsv>, Range<usize>), WSVError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(Ok(event));
+            }
+            if self.finished {
+                return None;
+            }
+
+            let before = self
+                .tokenizer
+                .remainder()
+                .trim_start_matches(|ch| ch != NEWLINE && WSVTokenizer::is_whitespace(ch));
+            let start = self.source.len() - before.len();
+            let token = self.tokenizer.next();
+            let span = start..self.source.len() - self.tokenizer.remainder().len();
+            match token {
+                None => {
+                    let end = self.source.len();
+                    if self.in_row {
+                        self.pending.push_back((WSVPullEvent::EndRow, end..end));
+                    } else if let Some(comment) = self.pending_comment.take() {
+                        self.pending.push_back(comment);
+                    }
+                    self.pending.push_back((WSVPullEvent::Eof, end..end));
+                    self.finished = true;
+                }
+                Some(Err(err)) => {
+                    self.finished = true;
+                    return Some(Err(err));
+                }
+                Some(Ok(WSVToken::Comment(comment))) => {
+                    let comment = (WSVPullEvent::Comment(comment), span);
+                    if self.in_row {
+                        self.pending.push_back(comment);
+                    } else {
+                        self.pending_comment = Some(comment);
+                    }
+                }
+                Some(Ok(WSVToken::LF)) => {
+                    self.start_row();
+                    if let Some(comment) = self.pending_comment.take() {
+                        self.pending.push_back(comment);
+                    }
+                    self.line_start = span.end;
+                    self.pending.push_back((WSVPullEvent::EndRow, span));
+                    self.in_row = false;
+                }
+                Some(Ok(WSVToken::Null)) => {
+                    self.start_row();
+                    self.pending.push_back((WSVPullEvent::Cell(None), span));
+                }
+                Some(Ok(WSVToken::Value(value))) => {
+                    self.start_row();
+                    self.pending
+                        .push_back((WSVPullEvent::Cell(Some(value)), span));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::{parse, WSVPullEvent, WSVPullParser};
+
+    #[test]
+    fn pulls_events_with_spans() {
+        let input = "a  \"b\"\"c\" - #note\n\n# end";
+        let events = WSVPullParser::new(input)
+            .map(|event| event.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (WSVPullEvent::StartRow, 0..0),
+                (WSVPullEvent::Cell(Some(Cow::Borrowed("a"))), 0..1),
+                (
+                    WSVPullEvent::Cell(Some(Cow::Owned("b\"c".to_string()))),
+                    3..9
+                ),
+                (WSVPullEvent::Cell(None), 10..11),
+                (WSVPullEvent::Comment("note"), 12..17),
+                (WSVPullEvent::EndRow, 17..18),
+                (WSVPullEvent::StartRow, 18..18),
+                (WSVPullEvent::EndRow, 18..19),
+                (WSVPullEvent::Comment(" end"), 19..24),
+                (WSVPullEvent::Eof, 24..24),
+            ],
+            events
+        );
+        assert_eq!("\"b\"\"c\"", &input[3..9]);
+
+        for input in ["", "a", "a\n", "a\n\n", "\n# x", "# x\n"] {
+            let rows = WSVPullParser::new(input)
+                .filter(|event| matches!(event, Ok((WSVPullEvent::EndRow, _))))
+                .count();
+            assert_eq!(parse(input).unwrap().len(), rows, "{:?}", input);
+        }
+
+        let mut parser = WSVPullParser::new("a \"b");
+        assert!(matches!(parser.nth(2), Some(Err(_))));
+        assert!(parser.next().is_none());
+    }
+}
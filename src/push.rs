@@ -0,0 +1,290 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::io::Write;
+
+use crate::{measure_value, write_aligned_value, WSVPullEvent, WriterOptions, NEWLINE};
+
+/// An event based writer, the counterpart of WSVPullParser, so rows can
+/// be streamed into WSV from another format without being collected
+/// first. Rows are written packed with the quoting, null token, separator,
+/// line ending and encoding from the options.
+///
+/// Calls have to come in order: start_row, any number of write_cell,
+/// an optional write_comment and then end_row. Anything else is an error
+/// and writes nothing.
+pub struct WSVPushWriter<W: Write> {
+    writer: W,
+    options: WriterOptions,
+    /// Whether anything has been written, so the next line needs a line
+    /// ending in front of it.
+    started: bool,
+    state: RowState,
+    buffer: String,
+    /// buffer in the output encoding.
+    bytes: Vec<u8>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum RowState {
+    OutsideRow,
+    /// In a row that has the given number of values so far.
+    InRow(usize),
+    /// In a row that has had its comment written.
+    Commented,
+}
+
+impl<W: Write> WSVPushWriter<W> {
+    /// Creates a push writer that writes to the given writer.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            options: WriterOptions::default(),
+            started: false,
+            state: RowState::OutsideRow,
+            buffer: String::new(),
+            bytes: Vec::new(),
+        }
+    }
+
+    /// Sets the quoting, null token, separator, line ending and encoding
+    /// the rows are written with, and whether the last row gets a line
+    /// ending. Alignment is ignored.
+    pub fn with_options(mut self, options: WriterOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Starts a new row.
+    pub fn start_row(&mut self) -> Result<(), WSVPushError> {
+        if self.state != RowState::OutsideRow {
+            return Err(WSVPushError::RowNotEnded);
+        }
+        self.buffer.clear();
+        if self.started {
+            self.buffer.push_str(self.options.line_ending.as_str());
+        } else {
+            self.writer.write_all(self.options.encoding.bom())?;
+        }
+        self.write_buffer()?;
+        self.started = true;
+        self.state = RowState::InRow(0);
+        Ok(())
+    }
+
+    /// Writes a value into the current row. Nulls are None.
+    pub fn write_cell(&mut self, value: Option<&str>) -> Result<(), WSVPushError> {
+        let cells = match self.state {
+            RowState::InRow(cells) => cells,
+            RowState::OutsideRow => return Err(WSVPushError::CellOutsideRow),
+            RowState::Commented => return Err(WSVPushError::CellAfterComment),
+        };
+        self.buffer.clear();
+        if cells != 0 {
+            self.buffer.push_str(&self.options.separator);
+        }
+        let (needs_quotes, _) = measure_value(value, &self.options);
+        write_aligned_value(&mut self.buffer, value, needs_quotes, 0, &self.options);
+        self.write_buffer()?;
+        self.state = RowState::InRow(cells + 1);
+        Ok(())
+    }
+
+    /// Writes a comment (without its '#') at the end of the current row.
+    /// No values can follow it in the row.
+    pub fn write_comment(&mut self, comment: &str) -> Result<(), WSVPushError> {
+        let cells = match self.state {
+            RowState::InRow(cells) => cells,
+            RowState::OutsideRow => return Err(WSVPushError::CommentOutsideRow),
+            RowState::Commented => return Err(WSVPushError::CellAfterComment),
+        };
+        if comment.contains(NEWLINE) {
+            return Err(WSVPushError::InvalidComment);
+        }
+        self.buffer.clear();
+        if cells != 0 {
+            self.buffer.push(' ');
+        }
+        self.buffer.push('#');
+        self.buffer.push_str(comment);
+        self.write_buffer()?;
+        self.state = RowState::Commented;
+        Ok(())
+    }
+
+    /// Ends the current row.
+    pub fn end_row(&mut self) -> Result<(), WSVPushError> {
+        if self.state == RowState::OutsideRow {
+            return Err(WSVPushError::RowNotStarted);
+        }
+        self.state = RowState::OutsideRow;
+        Ok(())
+    }
+
+    /// Writes the event from a WSVPullParser. A comment that comes
+    /// outside of any row (on the last line of its source) gets a row of
+    /// its own. Eof does nothing.
+    pub fn write_event(&mut self, event: &WSVPullEvent) -> Result<(), WSVPushError> {
+        match event {
+            WSVPullEvent::StartRow => self.start_row(),
+            WSVPullEvent::Cell(value) => self.write_cell(value.as_deref()),
+            WSVPullEvent::Comment(comment) if self.state == RowState::OutsideRow => {
+                self.start_row()?;
+                self.write_comment(comment)?;
+                self.end_row()
+            }
+            WSVPullEvent::Comment(comment) => self.write_comment(comment),
+            WSVPullEvent::EndRow => self.end_row(),
+            WSVPullEvent::Eof => Ok(()),
+        }
+    }
+
+    /// Writes the trailing line ending if the options ask for one, flushes
+    /// the writer and returns it. Fails if a row is still open. Output
+    /// without any rows is just the encoding's byte order mark.
+    pub fn finish(mut self) -> Result<W, WSVPushError> {
+        if self.state != RowState::OutsideRow {
+            return Err(WSVPushError::RowNotEnded);
+        }
+        self.buffer.clear();
+        if !self.started {
+            self.writer.write_all(self.options.encoding.bom())?;
+        } else if self.options.trailing_line_ending {
+            self.buffer.push_str(self.options.line_ending.as_str());
+        }
+        self.write_buffer()?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    /// Writes the buffer to the writer in the output encoding.
+    fn write_buffer(&mut self) -> Result<(), std::io::Error> {
+        self.bytes.clear();
+        self.options
+            .encoding
+            .encode_str(&self.buffer, &mut self.bytes);
+        self.writer.write_all(&self.bytes)
+    }
+}
+
+/// An error from a WSVPushWriter: either an IO error from the writer or
+/// a call that came out of order.
+#[derive(Debug)]
+pub enum WSVPushError {
+    Io(std::io::Error),
+    /// start_row (or finish) was called before the current row ended.
+    RowNotEnded,
+    /// end_row was called outside of a row.
+    RowNotStarted,
+    /// write_cell was called outside of a row.
+    CellOutsideRow,
+    /// write_cell or write_comment was called after the row's comment.
+    CellAfterComment,
+    /// write_comment was called outside of a row.
+    CommentOutsideRow,
+    /// The comment contains a line feed.
+    InvalidComment,
+}
+
+impl Display for WSVPushError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WSVPushError::Io(err) => write!(f, "{}", err),
+            WSVPushError::RowNotEnded => write!(f, "Row Not Ended"),
+            WSVPushError::RowNotStarted => write!(f, "Row Not Started"),
+            WSVPushError::CellOutsideRow => write!(f, "Cell Outside Row"),
+            WSVPushError::CellAfterComment => write!(f, "Cell After Comment"),
+            WSVPushError::CommentOutsideRow => write!(f, "Comment Outside Row"),
+            WSVPushError::InvalidComment => write!(f, "Invalid Comment"),
+        }
+    }
+}
+
+impl Error for WSVPushError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WSVPushError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for WSVPushError {
+    fn from(value: std::io::Error) -> Self {
+        WSVPushError::Io(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, OutputEncoding, WSVPullParser, WSVPushError, WSVPushWriter, WriterOptions};
+
+    #[test]
+    fn pushes_rows() {
+        let mut writer = WSVPushWriter::new(Vec::new());
+        assert!(matches!(
+            writer.write_cell(Some("a")),
+            Err(WSVPushError::CellOutsideRow)
+        ));
+        writer.start_row().unwrap();
+        writer.write_cell(Some("a b")).unwrap();
+        writer.write_cell(None).unwrap();
+        writer.write_comment(" note").unwrap();
+        assert!(matches!(
+            writer.write_cell(Some("c")),
+            Err(WSVPushError::CellAfterComment)
+        ));
+        assert!(matches!(writer.start_row(), Err(WSVPushError::RowNotEnded)));
+        writer.end_row().unwrap();
+        writer.start_row().unwrap();
+        writer.end_row().unwrap();
+        assert!(matches!(writer.end_row(), Err(WSVPushError::RowNotStarted)));
+        writer.start_row().unwrap();
+        writer.write_cell(Some("-")).unwrap();
+        assert!(matches!(
+            writer.write_comment("a\nb"),
+            Err(WSVPushError::InvalidComment)
+        ));
+        writer.end_row().unwrap();
+        let written = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!("\"a b\" - # note\n\n\"-\"", written);
+    }
+
+    #[test]
+    fn transcodes_pull_events() {
+        let input = "a  \"b\"\"c\" - #note\n\n\"x\"\n# end";
+        let mut writer = WSVPushWriter::new(Vec::new())
+            .with_options(WriterOptions::new().trailing_line_ending(true));
+        for event in WSVPullParser::new(input) {
+            writer.write_event(&event.unwrap().0).unwrap();
+        }
+        let written = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!("a \"b\"\"c\" - #note\n\nx\n# end\n", written);
+        assert_eq!(parse(input).unwrap(), parse(&written).unwrap()[..3]);
+    }
+
+    #[test]
+    fn pushes_utf16() {
+        let options = WriterOptions::new().encoding(OutputEncoding::Utf16Be);
+        let mut writer = WSVPushWriter::new(Vec::new()).with_options(options);
+        writer.start_row().unwrap();
+        writer.write_cell(Some("a")).unwrap();
+        writer.write_cell(Some("😀")).unwrap();
+        writer.end_row().unwrap();
+        writer.start_row().unwrap();
+        writer.end_row().unwrap();
+        let written = writer.finish().unwrap();
+        assert_eq!([0xFE, 0xFF], written[..2]);
+        let units = written[2..]
+            .chunks(2)
+            .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+            .collect::<Vec<_>>();
+        assert_eq!("a 😀\n", String::from_utf16(&units).unwrap());
+
+        let options = WriterOptions::new().encoding(OutputEncoding::Utf16Le);
+        let written = WSVPushWriter::new(Vec::new())
+            .with_options(options)
+            .finish()
+            .unwrap();
+        assert_eq!(vec![0xFF, 0xFE], written);
+    }
+}
@@ -0,0 +1,733 @@
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::mem::take;
+use std::ops::Range;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::{drop_empty_last_row, parse, WSVError, WSVReadError, WSVToken, WSVTokenizer, NEWLINE};
+
+/// How many bytes the reverse reader reads from the source at a time.
+const REVERSE_CHUNK_LEN: usize = 8 * 1024;
+
+/// A pull reader over any io::BufRead source. Since a WSV row can never
+/// span multiple lines, the reader pulls one line at a time into an
+/// internal buffer and parses it from there. Unlike WSVLineIterator,
+/// an error on one line does not stop the reader, so you may keep
+/// reading the following rows after a WSVReadError::WSV.
+///
+/// WSVReader implements Iterator over owned rows. If you don't need to
+/// keep the values around, use next_row_borrowed instead to avoid
+/// allocating anything per row.
+pub struct WSVReader<R> {
+    reader: R,
+    line: String,
+    /// Holds the bytes of a row that spans the end of the reader's buffer.
+    spill: Vec<u8>,
+    /// How many bytes of the reader's buffer the last row borrowed from
+    /// it. They are consumed before anything else is read.
+    pending_consume: usize,
+    line_number: usize,
+    line_byte_offset: usize,
+    next_byte_offset: usize,
+}
+
+impl WSVReader<BufReader<File>> {
+    /// Opens the file at the given path and creates a buffered reader over it.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
+        Ok(Self::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: BufRead> WSVReader<R> {
+    /// Creates a WSVReader that pulls its input from the given reader.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: String::new(),
+            spill: Vec::new(),
+            pending_consume: 0,
+            line_number: 0,
+            line_byte_offset: 0,
+            next_byte_offset: 0,
+        }
+    }
+
+    /// The line number of the most recently read row, or 0 if nothing
+    /// has been read yet.
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// Reads the next row and returns its cells borrowing from the
+    /// reader's internal line buffer. The row must be dropped before
+    /// the next one can be read, which lets the same buffer be reused
+    /// for every line. Cells only allocate if they contain escape sequences.
+    pub fn next_row_borrowed(&mut self) -> Option<Result<WSVBorrowedRow<'_>, WSVReadError>> {
+        match self.read_line() {
+            Err(err) => Some(Err(WSVReadError::Io(err))),
+            Ok(false) => None,
+            Ok(true) => Some(Ok(WSVBorrowedRow {
+                tokenizer: WSVTokenizer::new(&self.line),
+                line_number: self.line_number,
+                byte_offset: self.line_byte_offset,
+            })),
+        }
+    }
+
+    /// Same as next_row_borrowed, but the row borrows straight from the
+    /// BufRead's own buffer when the whole line is already in it, so the
+    /// line is never copied. Lines that run past the end of the buffer
+    /// are copied into an internal buffer instead. Use a BufReader with
+    /// a capacity larger than most lines to get the most out of this.
+    pub fn next_row_from_buffer(&mut self) -> Option<Result<WSVBorrowedRow<'_>, WSVReadError>> {
+        self.fill_row().map_err(WSVReadError::Io).transpose()
+    }
+
+    /// Finds the next line for next_row_from_buffer, either in the
+    /// reader's buffer or copied into the spill buffer.
+    fn fill_row(&mut self) -> Result<Option<WSVBorrowedRow<'_>>, std::io::Error> {
+        self.reader.consume(take(&mut self.pending_consume));
+        self.spill.clear();
+
+        let buffer = self.reader.fill_buf()?;
+        if buffer.is_empty() {
+            return Ok(None);
+        }
+        let (line, read) = match buffer.iter().position(|byte| *byte == b'\n') {
+            Some(end) => {
+                self.pending_consume = end + 1;
+                (&self.reader.fill_buf()?[..end], end + 1)
+            }
+            None => {
+                loop {
+                    let buffer = self.reader.fill_buf()?;
+                    if buffer.is_empty() {
+                        break;
+                    }
+                    match buffer.iter().position(|byte| *byte == b'\n') {
+                        Some(end) => {
+                            self.spill.extend_from_slice(&buffer[..end]);
+                            self.reader.consume(end + 1);
+                            self.spill.push(b'\n');
+                            break;
+                        }
+                        None => {
+                            let len = buffer.len();
+                            self.spill.extend_from_slice(buffer);
+                            self.reader.consume(len);
+                        }
+                    }
+                }
+                let read = self.spill.len();
+                if self.spill.last() == Some(&b'\n') {
+                    self.spill.pop();
+                }
+                (self.spill.as_slice(), read)
+            }
+        };
+
+        self.line_number += 1;
+        self.line_byte_offset = self.next_byte_offset;
+        self.next_byte_offset += read;
+        let line = std::str::from_utf8(line).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "stream did not contain valid UTF-8",
+            )
+        })?;
+        Ok(Some(WSVBorrowedRow {
+            tokenizer: WSVTokenizer::new(line),
+            line_number: self.line_number,
+            byte_offset: self.line_byte_offset,
+        }))
+    }
+
+    /// Reads the next row into the given record, reusing the record's
+    /// buffers instead of allocating new ones for every row. Returns
+    /// false once the reader is exhausted. On error, the record is left
+    /// empty and reading may continue with the next row.
+    pub fn read_record(&mut self, record: &mut WSVRecord) -> Result<bool, WSVReadError> {
+        record.clear();
+        let row = match self.next_row_borrowed() {
+            None => return Ok(false),
+            Some(row) => row?,
+        };
+
+        record.line_number = row.line_number();
+        for cell in row {
+            match cell {
+                Err(err) => {
+                    record.clear();
+                    return Err(WSVReadError::WSV(err));
+                }
+                Ok(None) => record.cells.push(None),
+                Ok(Some(value)) => {
+                    let start = record.buffer.len();
+                    record.buffer.push_str(&value);
+                    record.cells.push(Some(start..record.buffer.len()));
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(mut self) -> R {
+        self.reader.consume(self.pending_consume);
+        self.reader
+    }
+
+    /// Reads the next line into the line buffer without the trailing line
+    /// feed. Returns false once the reader is exhausted.
+    fn read_line(&mut self) -> Result<bool, std::io::Error> {
+        self.reader.consume(take(&mut self.pending_consume));
+        self.line.clear();
+        let read = self.reader.read_line(&mut self.line)?;
+        if read == 0 {
+            return Ok(false);
+        }
+
+        self.line_number += 1;
+        self.line_byte_offset = self.next_byte_offset;
+        self.next_byte_offset += read;
+        if self.line.ends_with(NEWLINE) {
+            self.line.pop();
+        }
+        Ok(true)
+    }
+}
+
+impl<R: BufRead> WSVReader<R> {
+    /// Turns this reader into one that follows the source as more rows are
+    /// appended to it, like `tail -f`. At the end of the source, the
+    /// follower sleeps for poll_interval and then checks for new data.
+    /// Rows are only returned once their line feed has been written, so a
+    /// partially written last line is never returned early.
+    ///
+    /// Use this on a fresh reader to get the rows already in the source
+    /// followed by the new ones. If the reader already returned a last
+    /// line without a line feed, the rest of that line will be returned
+    /// as a separate row once it is finished.
+    pub fn follow(mut self, poll_interval: Duration) -> WSVFollower<R> {
+        self.reader.consume(self.pending_consume);
+        WSVFollower {
+            reader: self.reader,
+            partial: Vec::new(),
+            line_number: self.line_number,
+            byte_offset: self.next_byte_offset,
+            poll_interval,
+        }
+    }
+}
+
+impl<R: BufRead + Seek> WSVReader<R> {
+    /// Turns this reader into one that reads the source backwards from
+    /// its end and yields rows last row first. Only the rows that are
+    /// actually read are scanned, so taking the last few rows of a huge
+    /// file is cheap.
+    ///
+    /// Since the start of the source is never read, line numbers are not
+    /// known. Errors from the reverse reader count lines from the end of
+    /// the source instead (the last row is line 1), but their byte index
+    /// is still relative to the start of the source.
+    pub fn records_rev(self) -> WSVReverseRows<R> {
+        WSVReverseRows {
+            reader: self.reader,
+            buffer: Vec::new(),
+            position: None,
+            rows_read: 0,
+            started: false,
+            finished: false,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for WSVReader<R> {
+    type Item = Result<Vec<Option<String>>, WSVReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let row = match self.next_row_borrowed()? {
+            Err(err) => return Some(Err(err)),
+            Ok(row) => row,
+        };
+
+        let mut result = Vec::new();
+        for cell in row {
+            match cell {
+                Err(err) => return Some(Err(WSVReadError::WSV(err))),
+                Ok(cell) => result.push(cell.map(|value| value.into_owned())),
+            }
+        }
+        Some(Ok(result))
+    }
+}
+
+/// An iterator over the rows of a source from last to first.
+/// See WSVReader::records_rev for details.
+pub struct WSVReverseRows<R> {
+    reader: R,
+    /// The tail of the source that hasn't been split into rows yet.
+    buffer: Vec<u8>,
+    /// The byte offset of the start of the buffer, or None before
+    /// anything has been read.
+    position: Option<u64>,
+    rows_read: usize,
+    started: bool,
+    finished: bool,
+}
+
+impl<R: Read + Seek> WSVReverseRows<R> {
+    /// Takes the last line off the buffer, reading more of the source
+    /// as needed. Returns the line and its byte offset in the source.
+    fn next_line(&mut self) -> Result<Option<(Vec<u8>, u64)>, std::io::Error> {
+        let mut position = match self.position {
+            Some(position) => position,
+            None => self.reader.seek(SeekFrom::End(0))?,
+        };
+        self.position = Some(position);
+
+        loop {
+            if let Some(newline) = self.buffer.iter().rposition(|byte| *byte == b'\n') {
+                let line = self.buffer.split_off(newline + 1);
+                self.buffer.pop();
+                return Ok(Some((line, position + newline as u64 + 1)));
+            }
+            if position == 0 {
+                self.finished = true;
+                return Ok(Some((std::mem::take(&mut self.buffer), 0)));
+            }
+
+            let chunk_len = (REVERSE_CHUNK_LEN as u64).min(position);
+            position -= chunk_len;
+            let mut chunk = vec![0; chunk_len as usize];
+            self.reader.seek(SeekFrom::Start(position))?;
+            self.reader.read_exact(&mut chunk)?;
+            chunk.append(&mut self.buffer);
+            self.buffer = chunk;
+            self.position = Some(position);
+        }
+    }
+}
+
+impl<R: Read + Seek> Iterator for WSVReverseRows<R> {
+    type Item = Result<Vec<Option<String>>, WSVReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let (line, byte_offset) = match self.next_line() {
+            Err(err) => {
+                self.finished = true;
+                return Some(Err(WSVReadError::Io(err)));
+            }
+            Ok(line) => line?,
+        };
+
+        // The first line read is the last line of the source.
+        let mut lines = vec![(line, byte_offset)];
+        if !self.started {
+            self.started = true;
+            drop_empty_last_row(&mut lines, |(line, _)| line.is_empty());
+        }
+        let Some((line, byte_offset)) = lines.pop() else {
+            return self.next();
+        };
+        self.rows_read += 1;
+
+        let line = match String::from_utf8(line) {
+            Err(err) => {
+                let err = std::io::Error::new(std::io::ErrorKind::InvalidData, err);
+                return Some(Err(WSVReadError::Io(err)));
+            }
+            Ok(line) => line,
+        };
+        Some(parse_owned_line(
+            &line,
+            self.rows_read - 1,
+            byte_offset as usize,
+        ))
+    }
+}
+
+/// Parses a single line into an owned row. The line number and byte offset
+/// of any error are shifted by the given amounts.
+fn parse_owned_line(
+    line: &str,
+    lines: usize,
+    bytes: usize,
+) -> Result<Vec<Option<String>>, WSVReadError> {
+    match parse(line) {
+        Err(err) => Err(WSVReadError::WSV(err.shifted(lines, bytes))),
+        Ok(mut rows) => Ok(rows
+            .pop()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|value| value.map(|value| value.into_owned()))
+            .collect()),
+    }
+}
+
+/// A reader that follows a source as it grows, like `tail -f`.
+/// See WSVReader::follow for details.
+pub struct WSVFollower<R> {
+    reader: R,
+    /// The start of a line that hasn't been fully written yet.
+    partial: Vec<u8>,
+    line_number: usize,
+    byte_offset: usize,
+    poll_interval: Duration,
+}
+
+impl<R: BufRead> WSVFollower<R> {
+    /// The line number of the most recently read row.
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// Reads the next row if a complete one is available, without waiting.
+    /// Returns None if the source has no complete row yet. A row is only
+    /// complete once its line feed has been written, so a line that is
+    /// still being written is held back until it is finished.
+    pub fn poll(&mut self) -> Option<Result<Vec<Option<String>>, WSVReadError>> {
+        match self.reader.read_until(b'\n', &mut self.partial) {
+            Err(err) => return Some(Err(WSVReadError::Io(err))),
+            Ok(_) if self.partial.last() != Some(&b'\n') => return None,
+            Ok(_) => {}
+        }
+
+        let mut line = std::mem::take(&mut self.partial);
+        line.pop();
+        self.line_number += 1;
+        let byte_offset = self.byte_offset;
+        self.byte_offset += line.len() + 1;
+
+        Some(match String::from_utf8(line) {
+            Err(err) => Err(WSVReadError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                err,
+            ))),
+            Ok(line) => parse_owned_line(&line, self.line_number - 1, byte_offset),
+        })
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: BufRead> Iterator for WSVFollower<R> {
+    type Item = Result<Vec<Option<String>>, WSVReadError>;
+
+    /// Blocks until the next row is available. This never returns None.
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(row) = self.poll() {
+                return Some(row);
+            }
+            std::thread::sleep(self.poll_interval);
+        }
+    }
+}
+
+/// A single row read by WSVReader::next_row_borrowed. This is an
+/// iterator over the cells of the row, borrowing from the reader's
+/// line buffer. Comments are skipped.
+pub struct WSVBorrowedRow<'line> {
+    tokenizer: WSVTokenizer<'line>,
+    line_number: usize,
+    byte_offset: usize,
+}
+
+impl<'line> WSVBorrowedRow<'line> {
+    /// The line number of this row in the source.
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+}
+
+impl<'line> Iterator for WSVBorrowedRow<'line> {
+    type Item = Result<Option<Cow<'line, str>>, WSVError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.tokenizer.next()? {
+                Err(err) => return Some(Err(err.shifted(self.line_number - 1, self.byte_offset))),
+                Ok(WSVToken::Null) => return Some(Ok(None)),
+                Ok(WSVToken::Value(value)) => return Some(Ok(Some(value))),
+                // The line buffer never contains a line feed.
+                Ok(WSVToken::LF) | Ok(WSVToken::Comment(_)) => {}
+            }
+        }
+    }
+}
+
+/// A reusable buffer for a single row, filled by WSVReader::read_record.
+/// All of the values are stored back to back in one String, so reading
+/// rows into the same record does not allocate once the buffers have
+/// grown large enough.
+#[derive(Debug, Clone, Default)]
+pub struct WSVRecord {
+    buffer: String,
+    cells: Vec<Option<Range<usize>>>,
+    line_number: usize,
+}
+
+impl WSVRecord {
+    /// Creates an empty record.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of cells in the record.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Whether the record has no cells.
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// The line number of the row in the source, or 0 if nothing
+    /// has been read into this record.
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// Gets the cell at the given index or None if it is out of range.
+    /// A null ('-') cell is Some(None).
+    pub fn get(&self, index: usize) -> Option<Option<&str>> {
+        self.cells
+            .get(index)
+            .map(|cell| cell.as_ref().map(|range| &self.buffer[range.clone()]))
+    }
+
+    /// An iterator over the cells in the record.
+    pub fn iter(&self) -> impl Iterator<Item = Option<&str>> + '_ {
+        self.cells
+            .iter()
+            .map(|cell| cell.as_ref().map(|range| &self.buffer[range.clone()]))
+    }
+
+    /// Removes all cells from the record, keeping the allocated buffers.
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.cells.clear();
+        self.line_number = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufReader, Cursor};
+
+    use crate::{parse, WSVErrorType, WSVReadError, WSVReader, WSVRecord};
+
+    fn owned(rows: Vec<Vec<Option<std::borrow::Cow<'_, str>>>>) -> Vec<Vec<Option<String>>> {
+        rows.into_iter()
+            .map(|row| row.into_iter().map(|v| v.map(|v| v.into_owned())).collect())
+            .collect()
+    }
+
+    #[test]
+    fn reader_matches_parse() {
+        let input = include_str!("../tests/1_stenway.com");
+        let expected = parse(input).unwrap();
+        let actual = WSVReader::new(Cursor::new(input))
+            .map(|row| row.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(expected.len(), actual.len());
+        for (expected, actual) in expected.iter().zip(actual.iter()) {
+            assert_eq!(
+                expected.iter().map(|v| v.as_deref()).collect::<Vec<_>>(),
+                actual.iter().map(|v| v.as_deref()).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn borrowed_rows() {
+        let mut reader = WSVReader::new(Cursor::new("a b c\n\n\"x\"\"y\" - # comment\n"));
+        let mut counts = Vec::new();
+        while let Some(row) = reader.next_row_borrowed() {
+            counts.push(row.unwrap().count());
+        }
+        assert_eq!(vec![3, 0, 2], counts);
+        assert_eq!(3, reader.line_number());
+    }
+
+    #[test]
+    fn reader_continues_after_errors() {
+        let reader = WSVReader::new(Cursor::new("1 2\n3 \"4\n5 6"));
+        let rows = reader.collect::<Vec<_>>();
+        assert_eq!(3, rows.len());
+        match &rows[1] {
+            Err(WSVReadError::WSV(err)) => {
+                assert_eq!(WSVErrorType::StringNotClosed, err.err_type());
+                assert_eq!(2, err.location().line());
+            }
+            _ => panic!("Expected an error on the second line"),
+        }
+        assert_eq!(
+            vec![Some("5".to_string()), Some("6".to_string())],
+            *rows[2].as_ref().unwrap()
+        );
+    }
+
+    #[test]
+    fn read_record_reuses_buffers() {
+        let mut reader = WSVReader::new(Cursor::new("a \"b c\" -\n\nd \"\"\"\"\nbad\"\nlast"));
+        let mut record = WSVRecord::new();
+
+        assert!(reader.read_record(&mut record).unwrap());
+        assert_eq!(
+            vec![Some("a"), Some("b c"), None],
+            record.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(1, record.line_number());
+
+        assert!(reader.read_record(&mut record).unwrap());
+        assert!(record.is_empty());
+
+        assert!(reader.read_record(&mut record).unwrap());
+        assert_eq!(Some(Some("\"")), record.get(1));
+        assert_eq!(None, record.get(2));
+
+        assert!(reader.read_record(&mut record).is_err());
+        assert!(record.is_empty());
+
+        assert!(reader.read_record(&mut record).unwrap());
+        assert_eq!(Some(Some("last")), record.get(0));
+        assert_eq!(5, record.line_number());
+
+        assert!(!reader.read_record(&mut record).unwrap());
+    }
+
+    #[test]
+    fn reads_rows_in_reverse() {
+        let long_input = (0..5_000)
+            .map(|i| format!("{} \"value {}\" # comment", i, i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        for input in [
+            include_str!("../tests/1_stenway.com"),
+            "",
+            "\n",
+            "a\n\n",
+            "a\n\nb",
+            &long_input,
+        ] {
+            let mut expected = owned(parse(input).unwrap());
+            expected.reverse();
+            let actual = WSVReader::new(Cursor::new(input))
+                .records_rev()
+                .map(|row| row.unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn reverse_errors_count_lines_from_the_end() {
+        let input = "a\nb\"\nc\nd\n";
+        let rows = WSVReader::new(Cursor::new(input))
+            .records_rev()
+            .collect::<Vec<_>>();
+        assert_eq!(4, rows.len());
+        match &rows[2] {
+            Err(WSVReadError::WSV(err)) => {
+                assert_eq!(WSVErrorType::InvalidDoubleQuoteAfterValue, err.err_type());
+                assert_eq!(3, err.location().line());
+            }
+            _ => panic!("Expected an error on the second line"),
+        }
+        assert_eq!(vec![Some("a".to_string())], *rows[3].as_ref().unwrap());
+    }
+
+    #[test]
+    fn follows_appended_rows() {
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+
+        /// A source that another thread keeps appending to.
+        #[derive(Clone, Default)]
+        struct Growing(Arc<Mutex<(Vec<u8>, usize)>>);
+        impl std::io::Read for Growing {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let mut inner = self.0.lock().unwrap();
+                let (data, position) = &mut *inner;
+                let len = buf.len().min(data.len() - *position);
+                buf[..len].copy_from_slice(&data[*position..*position + len]);
+                *position += len;
+                Ok(len)
+            }
+        }
+
+        let source = Growing::default();
+        let append = |text: &str| {
+            source
+                .0
+                .lock()
+                .unwrap()
+                .0
+                .extend_from_slice(text.as_bytes())
+        };
+        append("a b\n\"par");
+        let mut follower = WSVReader::new(std::io::BufReader::new(source.clone()))
+            .follow(Duration::from_millis(1));
+
+        assert_eq!(
+            vec![Some("a".to_string()), Some("b".to_string())],
+            follower.poll().unwrap().unwrap()
+        );
+        assert!(follower.poll().is_none());
+        append("tial\" \u{e9}");
+        assert!(follower.poll().is_none());
+        append("\nbad\"\n");
+        assert_eq!(
+            vec![Some("partial".to_string()), Some("\u{e9}".to_string())],
+            follower.next().unwrap().unwrap()
+        );
+        match follower.next() {
+            Some(Err(WSVReadError::WSV(err))) => assert_eq!(3, err.location().line()),
+            _ => panic!("Expected an error on the third line"),
+        }
+        assert_eq!(3, follower.line_number());
+    }
+
+    #[test]
+    fn reads_rows_from_the_buffer() {
+        let input = include_str!("../tests/1_stenway.com");
+        let expected = parse(input).unwrap();
+        // A tiny buffer forces most rows down the spill path.
+        for capacity in [4, 64, 64 * 1024] {
+            let mut reader = WSVReader::new(BufReader::with_capacity(capacity, Cursor::new(input)));
+            let mut rows = 0;
+            while let Some(row) = reader.next_row_from_buffer() {
+                let row = row.unwrap();
+                let line = row.line_number();
+                let values = row.map(Result::unwrap).collect::<Vec<_>>();
+                assert_eq!(expected[line - 1], values);
+                rows += 1;
+            }
+            assert_eq!(expected.len(), rows);
+        }
+
+        let mut reader = WSVReader::new(Cursor::new("a b\nc \"d\nno newline"));
+        assert_eq!(2, reader.next_row_from_buffer().unwrap().unwrap().count());
+        let mut row = reader.next_row_from_buffer().unwrap().unwrap();
+        assert!(matches!(row.nth(1), Some(Err(err)) if err.location().line() == 2));
+        drop(row);
+        // The reading styles can be mixed.
+        assert_eq!(
+            vec![Some("no".to_string()), Some("newline".to_string())],
+            reader.next().unwrap().unwrap()
+        );
+        assert!(reader.next_row_from_buffer().is_none());
+    }
+}
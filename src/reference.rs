@@ -0,0 +1,244 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::{parse, WSVError, WSVReadError, WSVWriter};
+
+/// Owned rows, as returned by a reference implementation.
+pub type WSVReferenceRows = Vec<Vec<Option<String>>>;
+
+/// A single input along with what the reference implementation
+/// made of it. An expected value of None means the reference
+/// implementation rejected the input as invalid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WSVReferenceCase {
+    pub name: String,
+    pub input: String,
+    pub expected: Option<WSVReferenceRows>,
+}
+
+impl WSVReferenceCase {
+    /// Creates a case by shelling out to a reference implementation.
+    /// The command is given the input on stdin and is expected to print
+    /// its own serialization of the parsed rows to stdout, or exit with a
+    /// non-zero status if it rejects the input. A thin wrapper script
+    /// around any of the official Stenway libraries is enough for this.
+    pub fn from_command(
+        name: &str,
+        input: &str,
+        command: &mut Command,
+    ) -> Result<Self, WSVReadError> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        // Write the input on its own thread so a child that echoes large
+        // inputs can't fill up its stdout pipe and deadlock. Dropping stdin
+        // once the input is written lets the child see EOF.
+        let mut stdin = child.stdin.take().expect("stdin was piped");
+        let output = std::thread::scope(|scope| {
+            let writer = scope.spawn(move || stdin.write_all(input.as_bytes()));
+            let output = child.wait_with_output();
+            match writer.join().expect("stdin writer panicked") {
+                // The child is allowed to reject the input without reading it.
+                Err(err) if err.kind() != std::io::ErrorKind::BrokenPipe => Err(err),
+                _ => output,
+            }
+        })?;
+
+        let expected = if output.status.success() {
+            let text = String::from_utf8(output.stdout)
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+            Some(to_owned_rows(&text)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            input: input.to_string(),
+            expected,
+        })
+    }
+}
+
+/// Which half of the crate disagreed with the reference implementation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscrepancyKind {
+    /// Parsing the input gave different rows (or a different
+    /// success/failure) than the reference implementation.
+    Parse,
+    /// Writing the expected rows and parsing the output back
+    /// did not give the same rows.
+    Write,
+}
+
+/// A single disagreement with the reference implementation. The expected
+/// and actual values are debug-formatted rows or error messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WSVDiscrepancy {
+    pub case: String,
+    pub kind: DiscrepancyKind,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// The result of running a corpus through compare_with_reference.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WSVReferenceReport {
+    pub cases_run: usize,
+    pub discrepancies: Vec<WSVDiscrepancy>,
+}
+
+impl WSVReferenceReport {
+    /// Whether every case agreed with the reference implementation.
+    pub fn is_clean(&self) -> bool {
+        self.discrepancies.is_empty()
+    }
+}
+
+/// Runs every case through this crate's parser and writer and reports
+/// where the results differ from the reference implementation. This is
+/// meant for bindings authors and for catching spec drift, not for use
+/// in production code.
+pub fn compare_with_reference<Cases>(cases: Cases) -> WSVReferenceReport
+where
+    Cases: IntoIterator<Item = WSVReferenceCase>,
+{
+    let mut report = WSVReferenceReport::default();
+    for case in cases {
+        report.cases_run += 1;
+
+        let actual = to_owned_rows(&case.input).ok();
+        if actual != case.expected {
+            report.discrepancies.push(WSVDiscrepancy {
+                case: case.name.clone(),
+                kind: DiscrepancyKind::Parse,
+                expected: describe(&case.expected),
+                actual: describe(&actual),
+            });
+        }
+
+        if let Some(expected) = &case.expected {
+            let written = WSVWriter::new(expected.clone()).to_string();
+            let round_tripped = to_owned_rows(&written).ok();
+            if round_tripped.as_ref() != Some(expected) {
+                report.discrepancies.push(WSVDiscrepancy {
+                    case: case.name,
+                    kind: DiscrepancyKind::Write,
+                    expected: describe(&case.expected),
+                    actual: format!("{:?} (written as {:?})", round_tripped, written),
+                });
+            }
+        }
+    }
+    report
+}
+
+/// A small corpus of inputs with their expected results, derived by
+/// hand from the examples in the spec. They were not generated by
+/// running the official Stenway implementations.
+pub fn reference_vectors() -> Vec<WSVReferenceCase> {
+    type Rows<'a> = Vec<Vec<Option<&'a str>>>;
+    fn case(name: &str, input: &str, expected: Option<Rows>) -> WSVReferenceCase {
+        WSVReferenceCase {
+            name: name.to_string(),
+            input: input.to_string(),
+            expected: expected.map(|rows| {
+                rows.into_iter()
+                    .map(|row| row.into_iter().map(|v| v.map(str::to_string)).collect())
+                    .collect()
+            }),
+        }
+    }
+
+    vec![
+        case(
+            "plain values",
+            "a b c\n1 2 3",
+            Some(vec![
+                vec![Some("a"), Some("b"), Some("c")],
+                vec![Some("1"), Some("2"), Some("3")],
+            ]),
+        ),
+        case(
+            "unicode whitespace",
+            "a\tb\u{00A0}c\u{3000}d",
+            Some(vec![vec![Some("a"), Some("b"), Some("c"), Some("d")]]),
+        ),
+        case(
+            "nulls",
+            "- \"-\" x",
+            Some(vec![vec![None, Some("-"), Some("x")]]),
+        ),
+        case(
+            "empty string",
+            "\"\" a",
+            Some(vec![vec![Some(""), Some("a")]]),
+        ),
+        case(
+            "escape sequences",
+            "\"a\"\"b\" \"line1\"/\"line2\" \"#\"",
+            Some(vec![vec![Some("a\"b"), Some("line1\nline2"), Some("#")]]),
+        ),
+        case(
+            "comments",
+            "a # comment\n# only a comment\nb#no space",
+            Some(vec![vec![Some("a")], vec![], vec![Some("b")]]),
+        ),
+        case("unclosed string", "\"unclosed", None),
+        case("quote after value", "a\"b", None),
+        case("character after string", "\"a\"b", None),
+        case("invalid line break", "\"a\"/b\"", None),
+    ]
+}
+
+fn to_owned_rows(source_text: &str) -> Result<WSVReferenceRows, WSVError> {
+    Ok(parse(source_text)?
+        .into_iter()
+        .map(|row| row.into_iter().map(|v| v.map(|v| v.into_owned())).collect())
+        .collect())
+}
+
+fn describe(rows: &Option<WSVReferenceRows>) -> String {
+    match rows {
+        None => "rejected".to_string(),
+        Some(rows) => format!("{:?}", rows),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{compare_with_reference, reference_vectors, DiscrepancyKind};
+
+    #[test]
+    fn parser_agrees_with_reference_vectors() {
+        let report = compare_with_reference(reference_vectors());
+        assert_eq!(reference_vectors().len(), report.cases_run);
+        assert!(report.is_clean(), "{:?}", report.discrepancies);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn cases_from_commands() {
+        use std::process::Command;
+
+        use crate::WSVReferenceCase;
+
+        // cat echoes the input back, so it acts like a reference
+        // implementation that agrees with this crate.
+        let input = "a \"b c\" -\n";
+        let echoed =
+            WSVReferenceCase::from_command("echo", input, &mut Command::new("cat")).unwrap();
+        assert!(echoed.expected.is_some());
+        assert!(compare_with_reference([echoed]).is_clean());
+
+        let rejected =
+            WSVReferenceCase::from_command("reject", input, &mut Command::new("false")).unwrap();
+        assert_eq!(None, rejected.expected);
+        let report = compare_with_reference([rejected]);
+        assert_eq!(1, report.discrepancies.len());
+        assert_eq!(DiscrepancyKind::Parse, report.discrepancies[0].kind);
+        assert_eq!("rejected", report.discrepancies[0].expected);
+    }
+}
@@ -0,0 +1,140 @@
+use std::fs::{self, OpenOptions};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use crate::{WSVAppender, WSVReadError, WSVReader, WriterOptions};
+
+/// Rewrites the WSV file at the given path in place, passing each row
+/// through the transform. Returning None from the transform drops the
+/// row. The rows are streamed into a temporary file next to the original
+/// which is synced to disk and then renamed over it, so the file is
+/// never left half written: if reading or writing fails, the original
+/// is untouched. The original's permissions are kept. Rows are written
+/// packed with the given options, in their encoding (starting with its
+/// byte order mark), and comments are not kept.
+pub fn rewrite_file<P, F>(
+    path: P,
+    options: WriterOptions,
+    mut transform: F,
+) -> Result<(), WSVReadError>
+where
+    P: AsRef<Path>,
+    F: FnMut(Vec<Option<String>>) -> Option<Vec<Option<String>>>,
+{
+    let path = path.as_ref();
+    let reader = WSVReader::open(path)?;
+    let permissions = fs::metadata(path)?.permissions();
+
+    let temp_path = temp_path(path);
+    let temp_file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&temp_path)?;
+    let result = (|| {
+        let mut appender = WSVAppender::new(BufWriter::new(temp_file)).with_options(options);
+        for row in reader {
+            if let Some(row) = transform(row?) {
+                appender.append_row(&row)?;
+            }
+        }
+        appender.flush()?;
+        let temp_file = appender
+            .into_inner()
+            .into_inner()
+            .map_err(|err| err.into_error())?;
+        temp_file.set_permissions(permissions)?;
+        temp_file.sync_all()?;
+        fs::rename(&temp_path, path)?;
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+        return result;
+    }
+
+    // Sync the directory too, so the rename itself survives a crash.
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        let dir = if dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir
+        };
+        fs::File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+/// A hidden file next to the given path, so renaming it over the path
+/// stays on the same file system.
+pub(crate) fn temp_path(path: &Path) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{rewrite_file, OutputEncoding, WSVReadError, WriterOptions};
+
+    #[test]
+    fn rewrites_files_in_place() {
+        let path =
+            std::env::temp_dir().join(format!("whitespacesv_rewrite_{}.wsv", std::process::id()));
+        fs::write(&path, "id price\n1 10\n2 -\n3 7 # sale").unwrap();
+
+        rewrite_file(&path, WriterOptions::default(), |mut row| {
+            if row.get(1).is_some_and(Option::is_none) {
+                return None;
+            }
+            if row[0].as_deref() == Some("3") {
+                row[1] = Some("7.50 USD".to_string());
+            }
+            Some(row)
+        })
+        .unwrap();
+        assert_eq!(
+            "id price\n1 10\n3 \"7.50 USD\"\n",
+            fs::read_to_string(&path).unwrap()
+        );
+
+        let options = WriterOptions::new().encoding(OutputEncoding::Utf16Be);
+        rewrite_file(&path, options.clone(), Some).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        assert_eq!([0xFE, 0xFF], bytes[..2]);
+        let units = bytes[2..]
+            .chunks(2)
+            .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            "id price\n1 10\n3 \"7.50 USD\"\n",
+            String::from_utf16(&units).unwrap()
+        );
+        fs::write(&path, "# nothing").unwrap();
+        rewrite_file(&path, options, |_| None).unwrap();
+        assert_eq!(vec![0xFE, 0xFF], fs::read(&path).unwrap());
+
+        // A file that fails to parse is left alone.
+        fs::write(&path, "a\n\"b").unwrap();
+        let result = rewrite_file(&path, WriterOptions::default(), Some);
+        assert!(matches!(result, Err(WSVReadError::WSV(_))));
+        assert_eq!("a\n\"b", fs::read_to_string(&path).unwrap());
+        assert_eq!(
+            1,
+            fs::read_dir(path.parent().unwrap())
+                .unwrap()
+                .filter(|entry| entry
+                    .as_ref()
+                    .unwrap()
+                    .file_name()
+                    .to_string_lossy()
+                    .contains(&format!("whitespacesv_rewrite_{}", std::process::id())))
+                .count()
+        );
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,269 @@
+use std::error::Error;
+use std::fmt::Display;
+
+use crate::{ParserOptions, WSVToken, WSVTokenizer, WSVWriter, WriterOptions};
+
+/// A value that didn't parse back to what was written, from
+/// check_round_trip or WSVWriter::to_string_checked. Rows and columns
+/// are 0 based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WSVRoundTripMismatch {
+    pub row: usize,
+    pub column: usize,
+    /// The value that was written. None if the row had no value at the
+    /// column, which happens when more values are read back than were
+    /// written.
+    pub expected: Option<Option<String>>,
+    /// The value that was read back. None if no value was read back at
+    /// the column, because the row came back shorter or couldn't be
+    /// parsed past that point. When both are None, a whole empty row was
+    /// lost, as happens to an empty last row written without a trailing
+    /// line ending.
+    pub actual: Option<Option<String>>,
+}
+
+impl Display for WSVRoundTripMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn describe(value: &Option<Option<String>>) -> String {
+            match value {
+                None => "no value".to_string(),
+                Some(None) => "null".to_string(),
+                Some(Some(value)) => format!("{:?}", value),
+            }
+        }
+        if self.expected.is_none() && self.actual.is_none() {
+            return write!(
+                f,
+                "(line: {}) Round Trip Mismatch (empty row lost)",
+                self.row + 1
+            );
+        }
+        write!(
+            f,
+            "(line: {}, value: {}) Round Trip Mismatch (wrote {}, read back {})",
+            self.row + 1,
+            self.column + 1,
+            describe(&self.expected),
+            describe(&self.actual)
+        )
+    }
+}
+
+impl Error for WSVRoundTripMismatch {}
+
+/// Writes the rows with the default writer options, parses them back and
+/// returns the first value that doesn't come back the same. Handy in tests
+/// and fuzz harnesses. Note that an empty last row is lost without a
+/// trailing line ending, which check_round_trip_with_options can set.
+pub fn check_round_trip<Row, S>(rows: &[Row]) -> Result<(), WSVRoundTripMismatch>
+where
+    Row: AsRef<[Option<S>]>,
+    S: AsRef<str>,
+{
+    check_round_trip_with_options(rows, WriterOptions::default())
+}
+
+/// Same as check_round_trip, but writes the rows with the given options.
+pub fn check_round_trip_with_options<Row, S>(
+    rows: &[Row],
+    options: WriterOptions,
+) -> Result<(), WSVRoundTripMismatch>
+where
+    Row: AsRef<[Option<S>]>,
+    S: AsRef<str>,
+{
+    WSVWriter::new(
+        rows.iter()
+            .map(|row| row.as_ref().iter().map(Option::as_ref)),
+    )
+    .with_options(options)
+    .to_string_checked()
+    .map(|_| ())
+}
+
+/// Panics with the first mismatch if the rows don't round trip. See
+/// check_round_trip.
+#[track_caller]
+pub fn assert_round_trips<Row, S>(rows: &[Row])
+where
+    Row: AsRef<[Option<S>]>,
+    S: AsRef<str>,
+{
+    if let Err(mismatch) = check_round_trip(rows) {
+        panic!("rows don't round trip: {}", mismatch);
+    }
+}
+
+/// Parses the written text back, reading the writer's null token as null,
+/// and returns the first value that differs from the rows it was written
+/// from.
+pub(crate) fn find_mismatch<Row, S>(
+    rows: &[Row],
+    written: &str,
+    options: &WriterOptions,
+) -> Option<WSVRoundTripMismatch>
+where
+    Row: AsRef<[Option<S>]>,
+    S: AsRef<str>,
+{
+    let parser_options = ParserOptions::new().null_literal(options.null_token.clone());
+    let mut parsed = Vec::new();
+    let mut current = Vec::new();
+    // The row that failed to parse, if any. Nothing after it is compared.
+    let mut failed = None;
+    for token in WSVTokenizer::with_options(written, parser_options) {
+        match token {
+            Ok(WSVToken::LF) => parsed.push(std::mem::take(&mut current)),
+            Ok(WSVToken::Null) => current.push(None),
+            Ok(WSVToken::Value(value)) => current.push(Some(value.into_owned())),
+            Ok(WSVToken::Comment(_)) => {}
+            Err(_) => {
+                failed = Some(parsed.len());
+                break;
+            }
+        }
+    }
+    // Like parse, there is no empty row after a trailing line feed.
+    if failed.is_some() || !(current.is_empty() && (written.is_empty() || written.ends_with('\n')))
+    {
+        parsed.push(current);
+    }
+
+    let row_count = rows.len().max(parsed.len());
+    for row in 0..row_count {
+        let expected = rows.get(row).map_or(&[][..], |row| row.as_ref());
+        let actual = parsed.get(row).map_or(&[][..], Vec::as_slice);
+        for column in 0..expected.len().max(actual.len()) {
+            let expected = expected
+                .get(column)
+                .map(|value| value.as_ref().map(|value| value.as_ref()));
+            let actual = actual.get(column).map(Option::as_deref);
+            if expected != actual {
+                return Some(WSVRoundTripMismatch {
+                    row,
+                    column,
+                    expected: expected.map(|value| value.map(str::to_string)),
+                    actual: actual.map(|value| value.map(str::to_string)),
+                });
+            }
+        }
+        if expected.is_empty() && actual.is_empty() && rows.len() != parsed.len() {
+            // Only an empty row can go missing without a value going too.
+            return Some(WSVRoundTripMismatch {
+                row,
+                column: 0,
+                expected: None,
+                actual: None,
+            });
+        }
+        if failed == Some(row) {
+            return Some(WSVRoundTripMismatch {
+                row,
+                column: actual.len(),
+                expected: expected
+                    .get(actual.len())
+                    .map(|value| value.as_ref().map(|value| value.as_ref().to_string())),
+                actual: None,
+            });
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        assert_round_trips, check_round_trip, check_round_trip_with_options, parse,
+        ColumnAlignment, WSVRoundTripMismatch, WSVWriter, WriterOptions,
+    };
+
+    #[test]
+    fn checks_written_values() {
+        let rows = vec![
+            vec![Some("-"), None, Some("")],
+            vec![Some(" a b "), Some("#x"), Some("line\nbreak")],
+        ];
+        let written = WSVWriter::new(rows.clone())
+            .with_options(WriterOptions::new().alignment(ColumnAlignment::Right))
+            .to_string_checked()
+            .unwrap();
+        assert_eq!(
+            WSVWriter::new(rows.clone())
+                .align_columns(ColumnAlignment::Right)
+                .to_string(),
+            written
+        );
+
+        // Nulls are read back with the writer's null token, and values
+        // that look like it are quoted.
+        let rows = vec![vec![Some("NULL"), None, Some("-")]];
+        let written = WSVWriter::new(rows.clone())
+            .with_options(WriterOptions::new().null_token("NULL"))
+            .to_string_checked()
+            .unwrap();
+        assert_eq!("\"NULL\" NULL \"-\"", written.trim_end());
+        assert_eq!(
+            Ok(()),
+            check_round_trip_with_options(&rows, WriterOptions::new().null_token("NULL"))
+        );
+        assert_eq!(
+            "(line: 1, value: 2) Round Trip Mismatch (wrote null, read back \"NULL\")",
+            WSVRoundTripMismatch {
+                row: 0,
+                column: 1,
+                expected: Some(None),
+                actual: Some(Some("NULL".to_string())),
+            }
+            .to_string()
+        );
+
+        // An empty last row needs a trailing line ending to survive.
+        let rows = vec![vec![Some("a")], vec![]];
+        let err = WSVWriter::new(rows.clone())
+            .to_string_checked()
+            .unwrap_err();
+        assert_eq!(
+            "(line: 2) Round Trip Mismatch (empty row lost)",
+            err.to_string()
+        );
+        let written = WSVWriter::new(rows)
+            .with_options(WriterOptions::new().trailing_line_ending(true))
+            .to_string_checked()
+            .unwrap();
+        assert_eq!(2, parse(&written).unwrap().len());
+    }
+
+    #[test]
+    fn checks_round_trips() {
+        assert_round_trips(&[
+            vec![Some("a b"), None, Some("-"), Some("")],
+            vec![],
+            vec![Some("\"/\"")],
+        ]);
+        assert_eq!(Ok(()), check_round_trip::<Vec<Option<&str>>, _>(&[]));
+        assert_eq!(
+            Ok(()),
+            check_round_trip_with_options(
+                &[vec![Some("a")], vec![]],
+                WriterOptions::new().trailing_line_ending(true)
+            )
+        );
+        assert_eq!(
+            Err(WSVRoundTripMismatch {
+                row: 1,
+                column: 0,
+                expected: None,
+                actual: None,
+            }),
+            check_round_trip(&[vec![Some("a")], vec![]])
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "rows don't round trip: (line: 2) Round Trip Mismatch (empty row lost)"
+    )]
+    fn panics_on_mismatches() {
+        assert_round_trips(&[vec![Some("a")], vec![]]);
+    }
+}
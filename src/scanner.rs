@@ -0,0 +1,134 @@
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use crate::{Location, WSVError, WSVErrorType, WSVTokenizer, NEWLINE};
+
+/// A minimal cursor over the source text. It tracks locations the
+/// same way WSVTokenizer does so that the errors reported by both
+/// are identical.
+pub(crate) struct Scanner<'wsv> {
+    source: &'wsv str,
+    chars: Peekable<CharIndices<'wsv>>,
+    current_location: Location,
+}
+
+impl<'wsv> Scanner<'wsv> {
+    pub(crate) fn new(source: &'wsv str) -> Self {
+        Self {
+            source,
+            chars: source.char_indices().peekable(),
+            current_location: Location::default(),
+        }
+    }
+
+    pub(crate) fn source(&self) -> &'wsv str {
+        self.source
+    }
+
+    pub(crate) fn peek(&mut self) -> Option<char> {
+        self.chars.peek().map(|(_, ch)| *ch)
+    }
+
+    /// The byte index of the next character, or the length of the
+    /// source if everything has been consumed.
+    pub(crate) fn position(&mut self) -> usize {
+        match self.chars.peek() {
+            None => self.source.len(),
+            Some((i, _)) => *i,
+        }
+    }
+
+    pub(crate) fn next(&mut self) -> Option<char> {
+        let (i, ch) = self.chars.next()?;
+        if ch == NEWLINE {
+            self.current_location.line += 1;
+            self.current_location.col = 1;
+        } else {
+            self.current_location.col += 1;
+        }
+        self.current_location.byte_index = i;
+        Some(ch)
+    }
+
+    pub(crate) fn skip_while<F: Fn(char) -> bool>(&mut self, predicate: F) {
+        while let Some(ch) = self.peek() {
+            if !predicate(ch) {
+                break;
+            }
+            self.next();
+        }
+    }
+
+    pub(crate) fn error(&self, err_type: WSVErrorType) -> WSVError {
+        WSVError {
+            err_type,
+            location: self.current_location.clone(),
+        }
+    }
+
+    /// The error for a string that hit a line feed or the end of the
+    /// source before its closing quote.
+    pub(crate) fn string_not_closed(&mut self) -> WSVError {
+        let mut err = self.error(WSVErrorType::StringNotClosed);
+        if self.peek().is_none() {
+            err.location.byte_index = self.source.len();
+        }
+        err
+    }
+
+    pub(crate) fn scan_string(&mut self) -> Result<(), WSVError> {
+        // Opening quote
+        self.next();
+        loop {
+            match self.peek() {
+                None | Some(NEWLINE) => return Err(self.string_not_closed()),
+                Some('"') => {
+                    self.next();
+                    match self.peek() {
+                        Some('"') => {
+                            self.next();
+                        }
+                        Some('/') => {
+                            self.next();
+                            if let Some('"') = self.peek() {
+                                self.next();
+                            } else {
+                                return Err(self.error(WSVErrorType::InvalidStringLineBreak));
+                            }
+                        }
+                        Some(ch) => {
+                            if ch != NEWLINE && ch != '#' && !WSVTokenizer::is_whitespace(ch) {
+                                return Err(self.error(WSVErrorType::InvalidCharacterAfterString));
+                            }
+                            return Ok(());
+                        }
+                        None => return Ok(()),
+                    }
+                }
+                Some(_) => {
+                    self.next();
+                }
+            }
+        }
+    }
+
+    pub(crate) fn scan_value(&mut self) -> Result<(), WSVError> {
+        if self.peek().is_none() {
+            return Ok(());
+        }
+        let start = self.position();
+        self.skip_while(|ch| {
+            ch != NEWLINE && ch != '"' && ch != '#' && !WSVTokenizer::is_whitespace(ch)
+        });
+        let end = self.position();
+
+        // The tokenizer treats a lone '-' as a null before it looks
+        // for a trailing quote, so mirror that here.
+        if &self.source[start..end] != "-" {
+            if let Some('"') = self.peek() {
+                return Err(self.error(WSVErrorType::InvalidDoubleQuoteAfterValue));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,356 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use crate::{parse, parse_lazy, WSVError};
+
+/// Converts a single value of a row for a RowSchema. This is implemented
+/// by TextColumn, ParsedColumn and OptionalColumn, and by any closure
+/// that takes an Option<&str> and returns a Result.
+pub trait ColumnConverter {
+    type Output;
+
+    /// Converts a value, where None is a null ('-') or a value that is
+    /// missing from the end of a short row. Errors are returned as a
+    /// message describing what went wrong.
+    fn convert(&self, value: Option<&str>) -> Result<Self::Output, String>;
+}
+
+/// A column of non-null strings.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TextColumn;
+
+impl ColumnConverter for TextColumn {
+    type Output = String;
+
+    fn convert(&self, value: Option<&str>) -> Result<Self::Output, String> {
+        value.map(str::to_string).ok_or_else(null_value)
+    }
+}
+
+/// A column of non-null values that are parsed into T with FromStr.
+#[derive(Debug, Clone, Copy)]
+pub struct ParsedColumn<T>(PhantomData<fn() -> T>);
+
+impl<T> ParsedColumn<T> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Default for ParsedColumn<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ColumnConverter for ParsedColumn<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    type Output = T;
+
+    fn convert(&self, value: Option<&str>) -> Result<Self::Output, String> {
+        value
+            .ok_or_else(null_value)?
+            .parse()
+            .map_err(|err: T::Err| err.to_string())
+    }
+}
+
+/// Wraps another converter to allow nulls in the column, which become None.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OptionalColumn<C>(pub C);
+
+impl<C: ColumnConverter> ColumnConverter for OptionalColumn<C> {
+    type Output = Option<C::Output>;
+
+    fn convert(&self, value: Option<&str>) -> Result<Self::Output, String> {
+        match value {
+            None => Ok(None),
+            Some(value) => self.0.convert(Some(value)).map(Some),
+        }
+    }
+}
+
+impl<F, T, E> ColumnConverter for F
+where
+    F: Fn(Option<&str>) -> Result<T, E>,
+    E: Display,
+{
+    type Output = T;
+
+    fn convert(&self, value: Option<&str>) -> Result<Self::Output, String> {
+        self(value).map_err(|err| err.to_string())
+    }
+}
+
+pub(crate) fn null_value() -> String {
+    "Null Value".to_string()
+}
+
+/// Converts whole rows for a RowSchema. This is implemented for tuples
+/// of up to 12 ColumnConverters, one per column, and produces a tuple
+/// of their outputs.
+pub trait RowConverter {
+    type Output;
+
+    /// The number of columns in a row.
+    fn column_count(&self) -> usize;
+
+    /// Converts a row. The row must not have more values than there are
+    /// columns, but it may have fewer.
+    fn convert_row(
+        &self,
+        row: &[Option<&str>],
+        line: usize,
+    ) -> Result<Self::Output, WSVSchemaError>;
+}
+
+macro_rules! impl_row_converter {
+    ($count:literal; $($converter:ident $index:tt),+) => {
+        impl<$($converter: ColumnConverter),+> RowConverter for ($($converter,)+) {
+            type Output = ($($converter::Output,)+);
+
+            fn column_count(&self) -> usize {
+                $count
+            }
+
+            fn convert_row(
+                &self,
+                row: &[Option<&str>],
+                line: usize,
+            ) -> Result<Self::Output, WSVSchemaError> {
+                Ok(($(
+                    {
+                        let value = row.get($index).copied().flatten();
+                        self.$index.convert(value).map_err(|message| {
+                            WSVSchemaError::Conversion {
+                                line,
+                                column: $index,
+                                value: value.map(str::to_string),
+                                message,
+                            }
+                        })?
+                    },
+                )+))
+            }
+        }
+    };
+}
+
+impl_row_converter!(1; A 0);
+impl_row_converter!(2; A 0, B 1);
+impl_row_converter!(3; A 0, B 1, C 2);
+impl_row_converter!(4; A 0, B 1, C 2, D 3);
+impl_row_converter!(5; A 0, B 1, C 2, D 3, E 4);
+impl_row_converter!(6; A 0, B 1, C 2, D 3, E 4, F 5);
+impl_row_converter!(7; A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_row_converter!(8; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+impl_row_converter!(9; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+impl_row_converter!(10; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+impl_row_converter!(11; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+impl_row_converter!(12; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
+
+/// Describes how to convert each column of a table, so that rows can be
+/// parsed straight into typed tuples. For example, a file of names, ages
+/// and optional scores could use
+///
+/// ```rust
+/// use whitespacesv::{OptionalColumn, ParsedColumn, RowSchema, TextColumn};
+///
+/// let schema = RowSchema::new((
+///     TextColumn,
+///     ParsedColumn::<u32>::new(),
+///     OptionalColumn(ParsedColumn::<f64>::new()),
+/// ));
+/// let rows = schema.parse("ann 31 9.5\nbob 27 -").unwrap();
+/// assert_eq!(("bob".to_string(), 27, None), rows[1]);
+/// ```
+///
+/// Use Iterator::map on the results to turn the tuples into structs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RowSchema<C> {
+    columns: C,
+}
+
+impl<C: RowConverter> RowSchema<C> {
+    /// Creates a schema from a tuple of ColumnConverters, one per column.
+    pub fn new(columns: C) -> Self {
+        Self { columns }
+    }
+
+    /// Converts a single row. The line number is only used for errors.
+    pub fn convert<Str: AsRef<str>>(
+        &self,
+        row: &[Option<Str>],
+        line: usize,
+    ) -> Result<C::Output, WSVSchemaError> {
+        if row.len() > self.columns.column_count() {
+            return Err(WSVSchemaError::ColumnCount {
+                line,
+                expected: self.columns.column_count(),
+                actual: row.len(),
+            });
+        }
+        let row = row
+            .iter()
+            .map(|value| value.as_ref().map(|value| value.as_ref()))
+            .collect::<Vec<_>>();
+        self.columns.convert_row(&row, line)
+    }
+
+    /// Parses the source text (see parse for details) and converts every
+    /// row. Rows without any values (blank or comment-only lines) are
+    /// skipped.
+    pub fn parse(&self, source_text: &str) -> Result<Vec<C::Output>, WSVSchemaError> {
+        parse(source_text)?
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| !row.is_empty())
+            .map(|(line_index, row)| self.convert(row, line_index + 1))
+            .collect()
+    }
+
+    /// Same as parse, but parses lazily (see parse_lazy for details).
+    /// Conversion errors don't stop the iterator, but WSV syntax errors do.
+    pub fn parse_lazy<'schema, Chars>(
+        &'schema self,
+        source_text: Chars,
+    ) -> impl Iterator<Item = Result<C::Output, WSVSchemaError>> + 'schema
+    where
+        Chars: IntoIterator<Item = char> + 'schema,
+    {
+        parse_lazy(source_text)
+            .enumerate()
+            .filter(|(_, row)| !matches!(row, Ok(row) if row.is_empty()))
+            .map(|(line_index, row)| self.convert(&row?, line_index + 1))
+    }
+}
+
+/// An error from a RowSchema. Line numbers are 1 based and columns are
+/// 0 based indexes into the row.
+#[derive(Debug, Clone)]
+pub enum WSVSchemaError {
+    /// The source text was not valid WSV.
+    WSV(WSVError),
+    /// A row has more values than the schema has columns.
+    ColumnCount {
+        line: usize,
+        expected: usize,
+        actual: usize,
+    },
+    /// A column's converter failed. The value is None if it was null or
+    /// missing.
+    Conversion {
+        line: usize,
+        column: usize,
+        value: Option<String>,
+        message: String,
+    },
+}
+
+impl Display for WSVSchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WSVSchemaError::WSV(err) => write!(f, "{}", err),
+            WSVSchemaError::ColumnCount {
+                line,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "(line: {}) Too Many Values (expected {}, found {})",
+                line, expected, actual
+            ),
+            WSVSchemaError::Conversion {
+                line,
+                column,
+                value,
+                message,
+            } => {
+                write!(f, "(line: {}, value: {}) ", line, column + 1)?;
+                match value {
+                    Some(value) => write!(f, "Invalid Value ({:?}): {}", value, message),
+                    None => write!(f, "{}", message),
+                }
+            }
+        }
+    }
+}
+
+impl Error for WSVSchemaError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WSVSchemaError::WSV(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<WSVError> for WSVSchemaError {
+    fn from(value: WSVError) -> Self {
+        WSVSchemaError::WSV(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{OptionalColumn, ParsedColumn, RowSchema, TextColumn, WSVSchemaError};
+
+    #[test]
+    fn converts_rows_with_schema() {
+        let schema = RowSchema::new((
+            TextColumn,
+            ParsedColumn::<i64>::new(),
+            |value: Option<&str>| match value {
+                Some("yes") => Ok(true),
+                Some("no") | None => Ok(false),
+                Some(_) => Err("Expected yes or no"),
+            },
+        ));
+        let input = "a 1 yes\n# comment\nb -2\n\"c d\" 3 no";
+        assert_eq!(
+            vec![
+                ("a".to_string(), 1, true),
+                ("b".to_string(), -2, false),
+                ("c d".to_string(), 3, false),
+            ],
+            schema.parse(input).unwrap()
+        );
+        assert_eq!(
+            schema.parse(input).unwrap(),
+            schema
+                .parse_lazy(input.chars().collect::<Vec<_>>())
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        );
+
+        let err = schema.parse("a 1 maybe").unwrap_err();
+        assert_eq!(
+            "(line: 1, value: 3) Invalid Value (\"maybe\"): Expected yes or no",
+            err.to_string()
+        );
+        let results = schema
+            .parse_lazy("- 1\nb x\nc 2 no extra".chars().collect::<Vec<_>>())
+            .map(|row| row.map_err(|err| err.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                Err("(line: 1, value: 1) Null Value".to_string()),
+                Err(
+                    "(line: 2, value: 2) Invalid Value (\"x\"): invalid digit found in string"
+                        .to_string()
+                ),
+                Err("(line: 3) Too Many Values (expected 3, found 4)".to_string()),
+            ],
+            results
+        );
+
+        let optional = RowSchema::new((OptionalColumn(TextColumn),));
+        assert_eq!(vec![(None,)], optional.parse("-").unwrap());
+        assert!(matches!(optional.parse("\"a"), Err(WSVSchemaError::WSV(_))));
+    }
+}
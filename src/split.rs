@@ -0,0 +1,153 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use crate::{WSVAppender, WSVReadError, WSVReader, WriterOptions};
+
+/// Streams the WSV file at the input path into numbered chunk files of at
+/// most rows_per_chunk rows each, for systems that only take files up to
+/// a certain size. The chunk paths come from the output pattern with "{}"
+/// replaced by the chunk number, starting from 1, so "out/part_{}.wsv"
+/// gives "out/part_1.wsv", "out/part_2.wsv" and so on. Existing files are
+/// overwritten.
+///
+/// With repeat_header, the first non-empty row of the input is treated as
+/// a header and written at the top of every chunk, on top of its
+/// rows_per_chunk data rows. Blank and comment-only lines before it are
+/// dropped. Rows are written packed, and comments are not kept.
+///
+/// Returns the paths of the chunks that were written. An empty input
+/// gives no chunks. If reading or writing fails, the chunks written so
+/// far are left in place.
+///
+/// Panics if rows_per_chunk is 0 or the pattern doesn't contain "{}".
+pub fn split<P: AsRef<Path>>(
+    input: P,
+    rows_per_chunk: usize,
+    output_pattern: &str,
+    repeat_header: bool,
+) -> Result<Vec<PathBuf>, WSVReadError> {
+    split_with_options(
+        input,
+        rows_per_chunk,
+        output_pattern,
+        repeat_header,
+        WriterOptions::default(),
+    )
+}
+
+/// Same as split, but writes the chunks with the given options, in their
+/// encoding (each chunk starting with its byte order mark).
+pub fn split_with_options<P: AsRef<Path>>(
+    input: P,
+    rows_per_chunk: usize,
+    output_pattern: &str,
+    repeat_header: bool,
+    options: WriterOptions,
+) -> Result<Vec<PathBuf>, WSVReadError> {
+    assert!(rows_per_chunk != 0, "rows_per_chunk must not be 0");
+    assert!(
+        output_pattern.contains("{}"),
+        "output pattern {:?} has no \"{{}}\" for the chunk number",
+        output_pattern
+    );
+
+    let mut rows = WSVReader::open(input)?;
+    let mut header = None;
+    if repeat_header {
+        for row in rows.by_ref() {
+            let row = row?;
+            if !row.is_empty() {
+                header = Some(row);
+                break;
+            }
+        }
+    }
+
+    let mut chunks = Vec::new();
+    let mut chunk: Option<WSVAppender<BufWriter<File>>> = None;
+    let mut chunk_rows = 0;
+    for row in rows {
+        let row = row?;
+        if chunk.is_none() || chunk_rows == rows_per_chunk {
+            if let Some(mut full) = chunk.take() {
+                full.flush()?;
+            }
+            let path = PathBuf::from(output_pattern.replace("{}", &(chunks.len() + 1).to_string()));
+            let mut appender = WSVAppender::new(BufWriter::new(File::create(&path)?))
+                .with_options(options.clone());
+            if let Some(header) = &header {
+                appender.append_row(header)?;
+            }
+            chunks.push(path);
+            chunk = Some(appender);
+            chunk_rows = 0;
+        }
+        chunk.as_mut().unwrap().append_row(&row)?;
+        chunk_rows += 1;
+    }
+    if let Some(mut last) = chunk {
+        last.flush()?;
+    }
+    Ok(chunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{split, split_with_options, OutputEncoding, WriterOptions};
+
+    #[test]
+    fn splits_files_into_chunks() {
+        let dir = std::env::temp_dir().join(format!("whitespacesv_split_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("input.wsv");
+        fs::write(&input, "id name\n1 a\n2 \"b c\"\n# skipped\n3 -\n4 d\n5 e").unwrap();
+
+        let pattern = dir.join("part_{}.wsv");
+        let chunks = split(&input, 2, pattern.to_str().unwrap(), true).unwrap();
+        assert_eq!(3, chunks.len());
+        assert_eq!(dir.join("part_1.wsv"), chunks[0]);
+        assert_eq!(
+            "id name\n1 a\n2 \"b c\"\n",
+            fs::read_to_string(&chunks[0]).unwrap()
+        );
+        // The comment line is an empty row.
+        assert_eq!("id name\n\n3 -\n", fs::read_to_string(&chunks[1]).unwrap());
+        assert_eq!(
+            "id name\n4 d\n5 e\n",
+            fs::read_to_string(&chunks[2]).unwrap()
+        );
+
+        let chunks = split(&input, 4, pattern.to_str().unwrap(), false).unwrap();
+        assert_eq!(2, chunks.len());
+        assert_eq!("3 -\n4 d\n5 e\n", fs::read_to_string(&chunks[1]).unwrap());
+
+        fs::write(&input, "# exported today\n\nid name\n1 a\n2 b\n3 c").unwrap();
+        let chunks = split(&input, 2, pattern.to_str().unwrap(), true).unwrap();
+        assert_eq!(2, chunks.len());
+        assert_eq!(
+            "id name\n1 a\n2 b\n",
+            fs::read_to_string(&chunks[0]).unwrap()
+        );
+        assert_eq!("id name\n3 c\n", fs::read_to_string(&chunks[1]).unwrap());
+
+        let options = WriterOptions::new().encoding(OutputEncoding::Utf16Le);
+        let chunks =
+            split_with_options(&input, 2, pattern.to_str().unwrap(), true, options).unwrap();
+        let bytes = fs::read(&chunks[1]).unwrap();
+        assert_eq!([0xFF, 0xFE], bytes[..2]);
+        let units = bytes[2..]
+            .chunks(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+            .collect::<Vec<_>>();
+        assert_eq!("id name\n3 c\n", String::from_utf16(&units).unwrap());
+
+        fs::write(&input, "id name").unwrap();
+        assert!(split(&input, 4, pattern.to_str().unwrap(), true)
+            .unwrap()
+            .is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,235 @@
+use std::error::Error;
+use std::fmt::Display;
+
+use rusqlite::types::ValueRef;
+use rusqlite::{params_from_iter, Connection, Params};
+
+/// Bulk loads a table into SQLite, creating the SQLite table if it
+/// doesn't exist yet. The first row is used as the header row and gives
+/// the column names. Each column is declared as INTEGER or REAL if every
+/// non-null value in it parses as one, and TEXT otherwise, so numbers
+/// compare and sort as numbers in queries. Nulls become SQL NULLs and
+/// rows shorter than the header are filled up with NULLs.
+///
+/// All rows are inserted in a single transaction. Returns the number of
+/// rows inserted.
+pub fn load_sqlite<Row, Str>(
+    connection: &mut Connection,
+    table: &str,
+    rows: &[Row],
+) -> Result<usize, WSVSqliteError>
+where
+    Row: AsRef<[Option<Str>]>,
+    Str: AsRef<str>,
+{
+    let Some((header, rows)) = rows.split_first() else {
+        return Ok(0);
+    };
+    let mut columns = Vec::with_capacity(header.as_ref().len());
+    for (column, name) in header.as_ref().iter().enumerate() {
+        let Some(name) = name else {
+            return Err(WSVSqliteError::NullHeader { column });
+        };
+        columns.push(quote_identifier(name.as_ref()));
+    }
+    for (index, row) in rows.iter().enumerate() {
+        if row.as_ref().len() > columns.len() {
+            return Err(WSVSqliteError::ExtraValue {
+                row: index + 1,
+                column: columns.len(),
+            });
+        }
+    }
+
+    let definitions = columns
+        .iter()
+        .enumerate()
+        .map(|(column, name)| format!("{} {}", name, column_type(rows, column)))
+        .collect::<Vec<_>>();
+    let table = quote_identifier(table);
+    let transaction = connection.transaction()?;
+    transaction.execute(
+        &format!(
+            "CREATE TABLE IF NOT EXISTS {} ({})",
+            table,
+            definitions.join(", ")
+        ),
+        (),
+    )?;
+    {
+        let mut insert = transaction.prepare(&format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            table,
+            columns.join(", "),
+            vec!["?"; columns.len()].join(", ")
+        ))?;
+        for row in rows {
+            let row = row.as_ref();
+            let values = (0..columns.len()).map(|column| {
+                row.get(column)
+                    .and_then(|value| value.as_ref())
+                    .map(|value| value.as_ref())
+            });
+            insert.execute(params_from_iter(values))?;
+        }
+    }
+    transaction.commit()?;
+    Ok(rows.len())
+}
+
+/// Runs a query and returns its results as rows, starting with a header
+/// row of the result's column names. SQL NULLs become nulls ('-') and
+/// numbers are written out as text.
+pub fn query_sqlite<P: Params>(
+    connection: &Connection,
+    sql: &str,
+    params: P,
+) -> Result<Vec<Vec<Option<String>>>, WSVSqliteError> {
+    let mut statement = connection.prepare(sql)?;
+    let mut result = vec![statement
+        .column_names()
+        .into_iter()
+        .map(|name| Some(name.to_string()))
+        .collect::<Vec<_>>()];
+    let column_count = statement.column_count();
+
+    let mut rows = statement.query(params)?;
+    while let Some(row) = rows.next()? {
+        let mut values = Vec::with_capacity(column_count);
+        for column in 0..column_count {
+            values.push(match row.get_ref(column)? {
+                ValueRef::Null => None,
+                ValueRef::Integer(value) => Some(value.to_string()),
+                ValueRef::Real(value) => Some(value.to_string()),
+                ValueRef::Text(value) => Some(String::from_utf8_lossy(value).into_owned()),
+                ValueRef::Blob(_) => {
+                    return Err(WSVSqliteError::BlobValue {
+                        row: result.len(),
+                        column,
+                    })
+                }
+            });
+        }
+        result.push(values);
+    }
+    Ok(result)
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn column_type<Row, Str>(rows: &[Row], column: usize) -> &'static str
+where
+    Row: AsRef<[Option<Str>]>,
+    Str: AsRef<str>,
+{
+    let mut values = rows
+        .iter()
+        .filter_map(|row| row.as_ref().get(column)?.as_ref())
+        .map(|value| value.as_ref())
+        .peekable();
+    if values.peek().is_none() {
+        return "TEXT";
+    }
+
+    let mut column_type = "INTEGER";
+    for value in values {
+        if value.parse::<i64>().is_ok() {
+            continue;
+        } else if value.parse::<f64>().is_ok() {
+            column_type = "REAL";
+        } else {
+            return "TEXT";
+        }
+    }
+    column_type
+}
+
+/// An error from load_sqlite or query_sqlite. Rows and columns are 0
+/// based and count the header row.
+#[derive(Debug)]
+pub enum WSVSqliteError {
+    /// An error from SQLite.
+    Sqlite(rusqlite::Error),
+    /// A value in the header row was null, so it can't be a column name.
+    NullHeader { column: usize },
+    /// A data row has more values than the header row.
+    ExtraValue { row: usize, column: usize },
+    /// A query returned a blob, which can't be written as text.
+    BlobValue { row: usize, column: usize },
+}
+
+impl Display for WSVSqliteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WSVSqliteError::Sqlite(err) => write!(f, "{}", err),
+            WSVSqliteError::NullHeader { column } => {
+                write!(f, "(value: {}) Null Header", column + 1)
+            }
+            WSVSqliteError::ExtraValue { row, column } => write!(
+                f,
+                "(line: {}, value: {}) Value Without Header",
+                row + 1,
+                column + 1
+            ),
+            WSVSqliteError::BlobValue { row, column } => {
+                write!(f, "(line: {}, value: {}) Blob Value", row + 1, column + 1)
+            }
+        }
+    }
+}
+
+impl Error for WSVSqliteError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WSVSqliteError::Sqlite(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<rusqlite::Error> for WSVSqliteError {
+    fn from(value: rusqlite::Error) -> Self {
+        WSVSqliteError::Sqlite(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::Connection;
+
+    use crate::{load_sqlite, parse, query_sqlite, WSVSqliteError};
+
+    #[test]
+    fn loads_and_queries_tables() {
+        let mut connection = Connection::open_in_memory().unwrap();
+        let rows = parse("id name \"the \"\"price\"\"\"\n1 ann 2.5\n2 - 10\n10 cy").unwrap();
+        assert_eq!(3, load_sqlite(&mut connection, "my items", &rows).unwrap());
+
+        let result = query_sqlite(
+            &connection,
+            "SELECT id, name, \"the \"\"price\"\"\" AS price FROM \"my items\" \
+             WHERE id > ?1 ORDER BY id",
+            [1],
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                vec![Some("id"), Some("name"), Some("price")],
+                vec![Some("2"), None, Some("10")],
+                vec![Some("10"), Some("cy"), None],
+            ],
+            result
+                .iter()
+                .map(|row| row.iter().map(|v| v.as_deref()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+
+        let rows = parse("a -\n1 2").unwrap();
+        assert!(matches!(
+            load_sqlite(&mut connection, "other", &rows),
+            Err(WSVSqliteError::NullHeader { column: 1 })
+        ));
+    }
+}
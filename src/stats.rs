@@ -0,0 +1,312 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashSet};
+use std::hash::{Hash, Hasher};
+
+use crate::{measure_value, WSVError, WriterOptions};
+
+/// The number of hashes kept per column when estimating distinct counts
+/// for a stream. Estimates are usually within a few percent.
+const SKETCH_SIZE: usize = 1024;
+
+/// Collects statistics about each column of a table. Distinct counts
+/// are exact. See stats_lazy for streams of rows.
+pub fn stats<Row, Str>(rows: &[Row]) -> WSVStats
+where
+    Row: AsRef<[Option<Str>]>,
+    Str: AsRef<str>,
+{
+    let mut builder = StatsBuilder::new(|| Distinct::Exact(HashSet::new()));
+    for row in rows {
+        let row = row.as_ref();
+        builder.add_row(row.len());
+        for (column, value) in row.iter().enumerate() {
+            let value = value.as_ref().map(|value| value.as_ref());
+            if let (Some(value), Distinct::Exact(values)) = (
+                builder.add_value(column, value),
+                &mut builder.columns[column].1,
+            ) {
+                values.insert(value);
+            }
+        }
+    }
+    builder.finish()
+}
+
+/// Same as stats, but reads a stream of rows (such as the ones from
+/// parse_lazy) one at a time, so the file never has to be in memory.
+/// Distinct counts are estimated once a column has more than about a
+/// thousand distinct values. Stops at the first error.
+pub fn stats_lazy<Lines>(lines: Lines) -> Result<WSVStats, WSVError>
+where
+    Lines: IntoIterator<Item = Result<Vec<Option<String>>, WSVError>>,
+{
+    let mut builder = StatsBuilder::new(|| Distinct::Sketch(BTreeSet::new()));
+    for row in lines {
+        let row = row?;
+        builder.add_row(row.len());
+        for (column, value) in row.iter().enumerate() {
+            if let (Some(value), Distinct::Sketch(hashes)) = (
+                builder.add_value(column, value.as_deref()),
+                &mut builder.columns[column].1,
+            ) {
+                let mut hasher = DefaultHasher::new();
+                value.hash(&mut hasher);
+                hashes.insert(hasher.finish());
+                // One hash more than the sketch size is kept, to tell
+                // exactly SKETCH_SIZE distinct values from more of them.
+                if hashes.len() > SKETCH_SIZE + 1 {
+                    hashes.pop_last();
+                }
+            }
+        }
+    }
+    Ok(builder.finish())
+}
+
+/// Statistics about each column of a table. See stats and stats_lazy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WSVStats {
+    rows: usize,
+    columns: Vec<WSVColumnStats>,
+}
+
+impl WSVStats {
+    /// The number of rows, including blank ones.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// The statistics for each column, in column order. There is one
+    /// entry per column of the widest row.
+    pub fn columns(&self) -> &[WSVColumnStats] {
+        &self.columns
+    }
+
+    /// Renders the statistics as a table with a header row and one row
+    /// per column, ready to be written with WSVWriter.
+    pub fn to_table(&self) -> Vec<Vec<Option<String>>> {
+        let mut table = Vec::with_capacity(self.columns.len() + 1);
+        table.push(
+            ["column", "nulls", "distinct", "min", "max", "width"]
+                .into_iter()
+                .map(|header| Some(header.to_string()))
+                .collect(),
+        );
+        for (index, column) in self.columns.iter().enumerate() {
+            table.push(vec![
+                Some(index.to_string()),
+                Some(column.nulls.to_string()),
+                Some(column.distinct.to_string()),
+                column.min.map(|min| min.to_string()),
+                column.max.map(|max| max.to_string()),
+                Some(column.max_width.to_string()),
+            ]);
+        }
+        table
+    }
+}
+
+/// Statistics about a single column. Rows that are too short to have a
+/// value in the column are left out of every count.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WSVColumnStats {
+    nulls: usize,
+    distinct: usize,
+    distinct_is_estimate: bool,
+    min: Option<f64>,
+    max: Option<f64>,
+    max_width: usize,
+}
+
+impl WSVColumnStats {
+    /// The number of null values.
+    pub fn nulls(&self) -> usize {
+        self.nulls
+    }
+
+    /// The number of distinct non-null values.
+    pub fn distinct(&self) -> usize {
+        self.distinct
+    }
+
+    /// Whether distinct is an estimate rather than an exact count.
+    pub fn distinct_is_estimate(&self) -> bool {
+        self.distinct_is_estimate
+    }
+
+    /// The smallest of the values that look like numbers, if any.
+    pub fn min(&self) -> Option<f64> {
+        self.min
+    }
+
+    /// The largest of the values that look like numbers, if any.
+    pub fn max(&self) -> Option<f64> {
+        self.max
+    }
+
+    /// The width (in characters) of the widest value as it would be
+    /// written, including quotes and escape sequences.
+    pub fn max_width(&self) -> usize {
+        self.max_width
+    }
+}
+
+enum Distinct<'a> {
+    Exact(HashSet<&'a str>),
+    Sketch(BTreeSet<u64>),
+}
+
+impl Distinct<'_> {
+    /// The number of distinct values and whether it's an estimate.
+    fn count(&self) -> (usize, bool) {
+        match self {
+            Distinct::Exact(values) => (values.len(), false),
+            Distinct::Sketch(hashes) if hashes.len() <= SKETCH_SIZE => (hashes.len(), false),
+            Distinct::Sketch(hashes) => {
+                // The hashes are spread evenly over the range of u64, so
+                // the largest of the smallest k hashes gives the density.
+                let largest = *hashes.last().unwrap() as f64;
+                let estimate = SKETCH_SIZE as f64 * (u64::MAX as f64 / largest);
+                (estimate.round() as usize, true)
+            }
+        }
+    }
+}
+
+struct StatsBuilder<'a> {
+    rows: usize,
+    columns: Vec<(WSVColumnStats, Distinct<'a>)>,
+    new_distinct: fn() -> Distinct<'a>,
+    writer_options: WriterOptions,
+}
+
+impl<'a> StatsBuilder<'a> {
+    fn new(new_distinct: fn() -> Distinct<'a>) -> Self {
+        Self {
+            rows: 0,
+            columns: Vec::new(),
+            new_distinct,
+            writer_options: WriterOptions::default(),
+        }
+    }
+
+    fn add_row(&mut self, len: usize) {
+        self.rows += 1;
+        while self.columns.len() < len {
+            let stats = WSVColumnStats {
+                nulls: 0,
+                distinct: 0,
+                distinct_is_estimate: false,
+                min: None,
+                max: None,
+                max_width: 0,
+            };
+            self.columns.push((stats, (self.new_distinct)()));
+        }
+    }
+
+    /// Counts everything but the distinct values, which are left to the
+    /// caller. Returns the value if it isn't null.
+    fn add_value<'value>(
+        &mut self,
+        column: usize,
+        value: Option<&'value str>,
+    ) -> Option<&'value str> {
+        let stats = &mut self.columns[column].0;
+        let (_, width) = measure_value(value, &self.writer_options);
+        stats.max_width = stats.max_width.max(width);
+
+        let Some(value) = value else {
+            stats.nulls += 1;
+            return None;
+        };
+        if let Some(number) = value
+            .parse::<f64>()
+            .ok()
+            .filter(|number| number.is_finite())
+        {
+            stats.min = Some(stats.min.map_or(number, |min| min.min(number)));
+            stats.max = Some(stats.max.map_or(number, |max| max.max(number)));
+        }
+        Some(value)
+    }
+
+    fn finish(self) -> WSVStats {
+        WSVStats {
+            rows: self.rows,
+            columns: self
+                .columns
+                .into_iter()
+                .map(|(mut stats, distinct)| {
+                    (stats.distinct, stats.distinct_is_estimate) = distinct.count();
+                    stats
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SKETCH_SIZE;
+    use crate::{parse, parse_lazy, stats, stats_lazy};
+
+    #[test]
+    fn collects_column_stats() {
+        let input = "id name score\n1 ann 3.5\n2 \"b \"\"c\"\"\" -\n3 ann 10\n\n4";
+        let table = stats(&parse(input).unwrap());
+        assert_eq!(6, table.rows());
+        assert_eq!(3, table.columns().len());
+
+        let name = &table.columns()[1];
+        assert_eq!(0, name.nulls());
+        assert_eq!(3, name.distinct());
+        assert_eq!(None, name.min());
+        assert_eq!(9, name.max_width());
+
+        let score = &table.columns()[2];
+        assert_eq!(1, score.nulls());
+        assert_eq!(Some(3.5), score.min());
+        assert_eq!(Some(10.0), score.max());
+
+        assert_eq!(table, stats_lazy(parse_lazy(input.chars())).unwrap());
+        assert_eq!(
+            vec![
+                Some("2".to_string()),
+                Some("1".to_string()),
+                Some("3".to_string()),
+                Some("3.5".to_string()),
+                Some("10".to_string()),
+                Some("5".to_string()),
+            ],
+            table.to_table()[3]
+        );
+    }
+
+    #[test]
+    fn estimates_distinct_counts_of_streams() {
+        let input = (0..20_000)
+            .map(|i| (i % 5_000).to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let stats = stats_lazy(parse_lazy(input.chars())).unwrap();
+        let column = &stats.columns()[0];
+        assert!(column.distinct_is_estimate());
+        assert!((4_500..5_500).contains(&column.distinct()));
+        assert_eq!(Some(4999.0), column.max());
+    }
+
+    #[test]
+    fn counts_exactly_up_to_the_sketch_size() {
+        let input = (0..SKETCH_SIZE)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let stats = stats_lazy(parse_lazy(input.chars())).unwrap();
+        assert!(!stats.columns()[0].distinct_is_estimate());
+        assert_eq!(SKETCH_SIZE, stats.columns()[0].distinct());
+
+        let stats = stats_lazy(parse_lazy(format!("{}\n-1", input).chars())).unwrap();
+        assert!(stats.columns()[0].distinct_is_estimate());
+    }
+}
@@ -0,0 +1,98 @@
+use std::borrow::Cow;
+use std::mem::take;
+
+use crate::{WSVError, WSVToken, WSVTokenizer};
+
+/// Same as parse_lazy (see the documentation there for behavior
+/// details), but for input that is already in memory as a &str. The
+/// values borrow from the source wherever possible, the same as parse,
+/// so only values with escape sequences are copied.
+pub fn parse_lazy_str(source_text: &str) -> WSVStrLineIterator<'_> {
+    WSVStrLineIterator {
+        tokenizer: WSVTokenizer::new(source_text),
+        lookahead_error: None,
+        errored: false,
+    }
+}
+
+/// An iterator over the lines of a &str. See parse_lazy_str.
+pub struct WSVStrLineIterator<'wsv> {
+    tokenizer: WSVTokenizer<'wsv>,
+    lookahead_error: Option<WSVError>,
+    errored: bool,
+}
+
+impl<'wsv> Iterator for WSVStrLineIterator<'wsv> {
+    type Item = Result<Vec<Option<Cow<'wsv, str>>>, WSVError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(err) = take(&mut self.lookahead_error) {
+            return Some(Err(err));
+        }
+        if self.errored {
+            return None;
+        }
+
+        let mut line = Vec::new();
+        loop {
+            match self.tokenizer.next() {
+                None => {
+                    return if line.is_empty() {
+                        None
+                    } else {
+                        Some(Ok(line))
+                    }
+                }
+                // Same as WSVLineIterator, the values read before an error
+                // are returned first and the error comes next.
+                Some(Err(err)) => {
+                    self.errored = true;
+                    if line.is_empty() {
+                        return Some(Err(err));
+                    }
+                    self.lookahead_error = Some(err);
+                    return Some(Ok(line));
+                }
+                Some(Ok(WSVToken::LF)) => return Some(Ok(line)),
+                Some(Ok(WSVToken::Comment(_))) => {}
+                Some(Ok(WSVToken::Null)) => line.push(None),
+                Some(Ok(WSVToken::Value(value))) => line.push(Some(value)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::{parse_lazy, parse_lazy_str};
+
+    #[test]
+    fn borrows_like_parse_and_lines_like_parse_lazy() {
+        for input in [
+            include_str!("../tests/1_stenway.com"),
+            include_str!("../tests/my_test.txt"),
+            "a\n\nb\n",
+            "a b\n\"c",
+        ] {
+            let expected = parse_lazy(input.chars().collect::<Vec<_>>())
+                .map(|line| line.map_err(|err| err.to_string()))
+                .collect::<Vec<_>>();
+            let actual = parse_lazy_str(input)
+                .map(|line| {
+                    line.map(|line| line.into_iter().map(|v| v.map(Cow::into_owned)).collect())
+                        .map_err(|err| err.to_string())
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(expected, actual);
+        }
+
+        let line = parse_lazy_str("plain \"esc\"\"aped\"")
+            .next()
+            .unwrap()
+            .unwrap();
+        assert!(matches!(line[0], Some(Cow::Borrowed("plain"))));
+        assert!(matches!(line[1], Some(Cow::Owned(_))));
+    }
+}
@@ -0,0 +1,702 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt::Display;
+use std::ops::Index;
+
+use crate::{
+    check_column_order, move_column_order, parse, reorder_values, write_packed_row, ValueQuoting,
+    WSVError, WSVHeaderError, WSVHeaders, WSVTokenizer, WriterOptions, NEWLINE,
+};
+
+/// What separates the tables of a multi-table document.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum TableSeparator {
+    /// One or more lines that contain nothing but whitespace.
+    #[default]
+    BlankLine,
+    /// A line that contains nothing but the given text (ignoring
+    /// surrounding whitespace). For example "---" or "# end".
+    Line(String),
+}
+
+impl TableSeparator {
+    fn matches(&self, line: &str) -> bool {
+        let line = line.trim_matches(WSVTokenizer::is_whitespace);
+        match self {
+            TableSeparator::BlankLine => line.is_empty(),
+            TableSeparator::Line(separator) => line == separator,
+        }
+    }
+}
+
+/// The comment that names the table after it, as in `# table: users`.
+const TABLE_DIRECTIVE: &str = "table:";
+
+/// A single table from a multi-table document. See parse_tables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WSVTable<'wsv> {
+    name: Option<String>,
+    rows: Vec<Vec<Option<Cow<'wsv, str>>>>,
+    first_line: usize,
+}
+
+impl<'wsv> WSVTable<'wsv> {
+    /// Creates a table from the given rows, for example to write it
+    /// out with write_tables.
+    pub fn new(rows: Vec<Vec<Option<Cow<'wsv, str>>>>) -> Self {
+        Self {
+            name: None,
+            rows,
+            first_line: 0,
+        }
+    }
+
+    /// Sets the name of the table. Named tables are written with a
+    /// `# table: <name>` comment in front of them.
+    pub fn with_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// The name given to the table by a `# table: <name>` comment.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The rows of the table.
+    pub fn rows(&self) -> &[Vec<Option<Cow<'wsv, str>>>] {
+        &self.rows
+    }
+
+    /// The number of rows in the table.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Whether the table has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// The line number (1 based) of the first row of the table in the
+    /// document it was parsed from, or 0 if it was created with new.
+    pub fn first_line(&self) -> usize {
+        self.first_line
+    }
+
+    /// Consumes the table and returns its rows.
+    pub fn into_rows(self) -> Vec<Vec<Option<Cow<'wsv, str>>>> {
+        self.rows
+    }
+
+    /// Puts the columns of every row, the header included, in the given
+    /// order. order[i] is the index the column at i had before, and has to
+    /// name each column of the widest row exactly once. Shorter rows are
+    /// padded with nulls.
+    ///
+    /// Panics if the order isn't an order of the table's columns.
+    pub fn reorder_columns(&mut self, order: &[usize]) {
+        let column_count = self.rows.iter().map(Vec::len).max().unwrap_or(0);
+        check_column_order(order, column_count);
+        for row in &mut self.rows {
+            reorder_values(row, order);
+        }
+    }
+
+    /// Moves the column at from so it ends up at to, shifting the columns
+    /// in between over by one.
+    ///
+    /// Panics if either index is out of range.
+    pub fn move_column(&mut self, from: usize, to: usize) {
+        let column_count = self.rows.iter().map(Vec::len).max().unwrap_or(0);
+        assert!(
+            from < column_count && to < column_count,
+            "column index {} out of range for a table with {} columns",
+            from.max(to),
+            column_count
+        );
+        self.reorder_columns(&move_column_order(column_count, from, to));
+    }
+
+    /// Reads the column names from the first row of the table. Null and
+    /// duplicate names are errors located at the line of the header row
+    /// (see WSVHeaders::new). An empty table has no columns.
+    pub fn headers(&self) -> Result<WSVHeaders, WSVHeaderError> {
+        self.read_headers(false)
+    }
+
+    /// Same as headers, but renames duplicate names instead of failing
+    /// (see WSVHeaders::disambiguated).
+    pub fn disambiguated_headers(&self) -> Result<WSVHeaders, WSVHeaderError> {
+        self.read_headers(true)
+    }
+
+    /// Renames a column by changing its name in the header row.
+    ///
+    /// Fails if the header row can't be read, if no column has the old
+    /// name, or if another column already has the new one.
+    pub fn rename_column<S: Into<String>>(
+        &mut self,
+        old: &str,
+        new: S,
+    ) -> Result<(), WSVHeaderError> {
+        let new = new.into();
+        let headers = self.headers()?;
+        let column = self.column_index(&headers, old)?;
+        if let Some(first_column) = headers.index(&new).filter(|&other| other != column) {
+            return Err(WSVHeaderError::DuplicateHeader {
+                line: self.header_line(),
+                name: new,
+                first_column,
+                column,
+            });
+        }
+        self.rows[0][column] = Some(Cow::Owned(new));
+        Ok(())
+    }
+
+    /// Adds a column with the given name after the last column, with the
+    /// default value in every other row. Rows shorter than the header row
+    /// are padded with nulls first, so the values line up, and values past
+    /// the end of the header row are shifted over. An empty table
+    /// gets a header row.
+    ///
+    /// Fails if the header row can't be read or already has the name.
+    pub fn add_column<N, S>(&mut self, name: N, default: Option<S>) -> Result<(), WSVHeaderError>
+    where
+        N: Into<String>,
+        S: Into<Cow<'wsv, str>>,
+    {
+        let name = name.into();
+        let headers = self.headers()?;
+        if let Some(first_column) = headers.index(&name) {
+            return Err(WSVHeaderError::DuplicateHeader {
+                line: self.header_line(),
+                name,
+                first_column,
+                column: headers.len(),
+            });
+        }
+        if self.rows.is_empty() {
+            self.rows.push(Vec::new());
+        }
+        let default = default.map(Into::into);
+        let (header, rows) = self.rows.split_first_mut().unwrap();
+        header.push(Some(Cow::Owned(name)));
+        for row in rows {
+            if row.len() < headers.len() {
+                row.resize(headers.len(), None);
+            }
+            row.insert(headers.len(), default.clone());
+        }
+        Ok(())
+    }
+
+    /// Removes the column with the given name from every row, and returns
+    /// its values from the rows after the header row. Rows that are too
+    /// short to have the column give a null.
+    ///
+    /// Fails if the header row can't be read or no column has the name.
+    pub fn drop_column(
+        &mut self,
+        name: &str,
+    ) -> Result<Vec<Option<Cow<'wsv, str>>>, WSVHeaderError> {
+        let headers = self.headers()?;
+        let column = self.column_index(&headers, name)?;
+        self.rows[0].remove(column);
+        Ok(self.rows[1..]
+            .iter_mut()
+            .map(|row| {
+                if column < row.len() {
+                    row.remove(column)
+                } else {
+                    None
+                }
+            })
+            .collect())
+    }
+
+    fn column_index(&self, headers: &WSVHeaders, name: &str) -> Result<usize, WSVHeaderError> {
+        headers
+            .index(name)
+            .ok_or_else(|| WSVHeaderError::UnknownHeader {
+                line: self.header_line(),
+                name: name.to_string(),
+            })
+    }
+
+    fn header_line(&self) -> usize {
+        self.first_line.max(1)
+    }
+
+    fn read_headers(&self, disambiguate: bool) -> Result<WSVHeaders, WSVHeaderError> {
+        match self.rows.first() {
+            None => Ok(WSVHeaders::default()),
+            Some(header) => WSVHeaders::from_row(header, self.header_line(), disambiguate),
+        }
+    }
+}
+
+impl<'wsv> Index<usize> for WSVTable<'wsv> {
+    type Output = Vec<Option<Cow<'wsv, str>>>;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.rows[index]
+    }
+}
+
+impl<'wsv, Row, BorrowStr> FromIterator<Row> for WSVTable<'wsv>
+where
+    Row: IntoIterator<Item = Option<BorrowStr>>,
+    BorrowStr: Into<Cow<'wsv, str>>,
+{
+    fn from_iter<T: IntoIterator<Item = Row>>(rows: T) -> Self {
+        let mut table = WSVTable::new(Vec::new());
+        table.extend(rows);
+        table
+    }
+}
+
+impl<'wsv, Row, BorrowStr> Extend<Row> for WSVTable<'wsv>
+where
+    Row: IntoIterator<Item = Option<BorrowStr>>,
+    BorrowStr: Into<Cow<'wsv, str>>,
+{
+    /// Appends each row to the end of the table.
+    fn extend<T: IntoIterator<Item = Row>>(&mut self, rows: T) {
+        self.rows.extend(
+            rows.into_iter()
+                .map(|row| row.into_iter().map(|value| value.map(Into::into)).collect()),
+        );
+    }
+}
+
+/// The tables of a multi-table document, as returned by parse_tables.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WSVTables<'wsv> {
+    tables: Vec<WSVTable<'wsv>>,
+}
+
+impl<'wsv> WSVTables<'wsv> {
+    /// Creates a document from the given tables.
+    pub fn new(tables: Vec<WSVTable<'wsv>>) -> Self {
+        Self { tables }
+    }
+
+    /// The first table with the given name.
+    pub fn table(&self, name: &str) -> Option<&WSVTable<'wsv>> {
+        self.tables.iter().find(|table| table.name() == Some(name))
+    }
+
+    /// All of the tables in the order they appear in the document.
+    pub fn tables(&self) -> &[WSVTable<'wsv>] {
+        &self.tables
+    }
+
+    /// The number of tables in the document.
+    pub fn len(&self) -> usize {
+        self.tables.len()
+    }
+
+    /// Whether the document has no tables.
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// An iterator over the tables.
+    pub fn iter(&self) -> std::slice::Iter<'_, WSVTable<'wsv>> {
+        self.tables.iter()
+    }
+
+    /// Consumes the document and returns its tables.
+    pub fn into_tables(self) -> Vec<WSVTable<'wsv>> {
+        self.tables
+    }
+}
+
+impl<'wsv> Index<usize> for WSVTables<'wsv> {
+    type Output = WSVTable<'wsv>;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.tables[index]
+    }
+}
+
+impl<'doc, 'wsv> IntoIterator for &'doc WSVTables<'wsv> {
+    type Item = &'doc WSVTable<'wsv>;
+    type IntoIter = std::slice::Iter<'doc, WSVTable<'wsv>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tables.iter()
+    }
+}
+
+impl<'wsv> FromIterator<WSVTable<'wsv>> for WSVTables<'wsv> {
+    fn from_iter<T: IntoIterator<Item = WSVTable<'wsv>>>(tables: T) -> Self {
+        Self::new(tables.into_iter().collect())
+    }
+}
+
+impl<'wsv> Extend<WSVTable<'wsv>> for WSVTables<'wsv> {
+    fn extend<T: IntoIterator<Item = WSVTable<'wsv>>>(&mut self, tables: T) {
+        self.tables.extend(tables);
+    }
+}
+
+/// Parses a document made up of several tables separated by blank lines.
+/// See parse_tables_with_separator for details.
+pub fn parse_tables(source_text: &str) -> Result<WSVTables<'_>, WSVError> {
+    parse_tables_with_separator(source_text, &TableSeparator::BlankLine)
+}
+
+/// Parses a document made up of several tables. Each table is parsed the
+/// same way as parse (see the documentation there for behavior details),
+/// except that lines without any values (such as comment-only lines) are
+/// left out of the table's rows. Any number of separators in a row (or at
+/// the start or end of the document) count as a single separator, so the
+/// returned tables are never empty.
+///
+/// A comment-only line like `# table: users` names the table that
+/// follows it, which can then be looked up with WSVTables::table. A name
+/// comment also starts a new table, even without a separator before it.
+pub fn parse_tables_with_separator<'wsv>(
+    source_text: &'wsv str,
+    separator: &TableSeparator,
+) -> Result<WSVTables<'wsv>, WSVError> {
+    let mut tables = Vec::new();
+    let mut current = WSVTable::new(Vec::new());
+    let mut byte_offset = 0;
+
+    for (line_index, line) in source_text.split(NEWLINE).enumerate() {
+        if separator.matches(line) {
+            if !current.is_empty() {
+                tables.push(std::mem::replace(&mut current, WSVTable::new(Vec::new())));
+            }
+        } else if let Some(name) = table_name(line) {
+            if !current.is_empty() {
+                tables.push(std::mem::replace(&mut current, WSVTable::new(Vec::new())));
+            }
+            current.name = Some(name.to_string());
+        } else {
+            let row = parse(line)
+                .map_err(|err| err.shifted(line_index, byte_offset))?
+                .pop()
+                .unwrap_or_default();
+            if !row.is_empty() {
+                if current.is_empty() {
+                    current.first_line = line_index + 1;
+                }
+                current.rows.push(row);
+            }
+        }
+        byte_offset += line.len() + 1;
+    }
+
+    if !current.is_empty() {
+        tables.push(current);
+    }
+    Ok(WSVTables::new(tables))
+}
+
+/// Gets the table name out of a `# table: <name>` comment line.
+fn table_name(line: &str) -> Option<&str> {
+    let name = line
+        .trim_matches(WSVTokenizer::is_whitespace)
+        .strip_prefix('#')?
+        .trim_start_matches(WSVTokenizer::is_whitespace)
+        .strip_prefix(TABLE_DIRECTIVE)?
+        .trim_matches(WSVTokenizer::is_whitespace);
+    if name.is_empty() {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+/// Writes the tables out as packed rows in a single document, with the
+/// given separator between each pair of tables. The output can be read
+/// back with parse_tables_with_separator using the same separator. Rows
+/// that would read back as the separator line have all of their values
+/// quoted.
+///
+/// Fails if a table or one of its rows is empty, since parsing leaves
+/// those out, or if a row reads back as the separator even when quoted.
+pub fn write_tables<'table, 'wsv: 'table, Tables>(
+    tables: Tables,
+    separator: &TableSeparator,
+) -> Result<String, WSVWriteTablesError>
+where
+    Tables: IntoIterator<Item = &'table WSVTable<'wsv>>,
+{
+    let separator_line = match separator {
+        TableSeparator::BlankLine => "\n\n".to_string(),
+        TableSeparator::Line(separator) => format!("\n{}\n", separator),
+    };
+    let packed = WriterOptions::default();
+    let quoted = WriterOptions::new().quoting(ValueQuoting::Always);
+
+    let mut written = Vec::new();
+    for (table_index, table) in tables.into_iter().enumerate() {
+        if table.rows.is_empty() {
+            return Err(WSVWriteTablesError::EmptyTable { table: table_index });
+        }
+        let mut text = String::new();
+        if let Some(name) = table.name() {
+            text.push_str(&format!("# {} {}\n", TABLE_DIRECTIVE, name));
+        }
+        for (row_index, row) in table.rows.iter().enumerate() {
+            if row.is_empty() {
+                return Err(WSVWriteTablesError::EmptyRow {
+                    table: table_index,
+                    row: row_index,
+                });
+            }
+            if row_index != 0 {
+                text.push(NEWLINE);
+            }
+            let line_start = text.len();
+            write_packed_row(&mut text, row, &packed);
+            if separator.matches(&text[line_start..]) {
+                text.truncate(line_start);
+                write_packed_row(&mut text, row, &quoted);
+                if separator.matches(&text[line_start..]) {
+                    return Err(WSVWriteTablesError::RowMatchesSeparator {
+                        table: table_index,
+                        row: row_index,
+                    });
+                }
+            }
+        }
+        written.push(text);
+    }
+    Ok(written.join(&separator_line))
+}
+
+/// An error from write_tables. Tables and rows are 0 based indexes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WSVWriteTablesError {
+    /// The table has no rows, so it would not be read back at all.
+    EmptyTable { table: usize },
+    /// The row has no values. Parsing leaves empty lines out of tables,
+    /// and with TableSeparator::BlankLine it would split the table in two.
+    EmptyRow { table: usize, row: usize },
+    /// The row reads back as the separator line, even with its values
+    /// quoted (ex. a row of nulls when the separator is "- -").
+    RowMatchesSeparator { table: usize, row: usize },
+}
+
+impl Display for WSVWriteTablesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WSVWriteTablesError::EmptyTable { table } => {
+                write!(f, "(table: {}) Empty Table", table + 1)
+            }
+            WSVWriteTablesError::EmptyRow { table, row } => {
+                write!(f, "(table: {}, row: {}) Empty Row", table + 1, row + 1)
+            }
+            WSVWriteTablesError::RowMatchesSeparator { table, row } => write!(
+                f,
+                "(table: {}, row: {}) Row Matches Separator",
+                table + 1,
+                row + 1
+            ),
+        }
+    }
+}
+
+impl Error for WSVWriteTablesError {}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::{
+        parse_tables, parse_tables_with_separator, write_tables, TableSeparator, WSVErrorType,
+        WSVHeaderError, WSVTable, WSVTables, WSVWriteTablesError,
+    };
+
+    #[test]
+    fn splits_tables_on_blank_lines() {
+        let input = "\n\nid name\n1 a\n# just a comment\n2 b\n \n\t\nsku\nx-1\n\n";
+        let tables = parse_tables(input).unwrap();
+        assert_eq!(2, tables.len());
+        assert_eq!(3, tables[0].len());
+        assert_eq!(3, tables[0].first_line());
+        assert_eq!(Some("b"), tables[0].rows()[2][1].as_deref());
+        assert_eq!(9, tables[1].first_line());
+
+        let written = write_tables(&tables, &TableSeparator::BlankLine).unwrap();
+        let reparsed = parse_tables(&written).unwrap();
+        assert_eq!(
+            tables.iter().map(|t| t.rows()).collect::<Vec<_>>(),
+            reparsed.iter().map(|t| t.rows()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn splits_tables_on_custom_separator() {
+        let separator = TableSeparator::Line("---".to_string());
+        let input = "a b\n\nc d\n  ---  \ne\n---\n\"bad";
+        let err = parse_tables_with_separator(input, &separator).unwrap_err();
+        assert_eq!(WSVErrorType::StringNotClosed, err.err_type());
+        assert_eq!(7, err.location().line());
+
+        let tables = parse_tables_with_separator(&input[..input.len() - 5], &separator).unwrap();
+        assert_eq!(2, tables.len());
+        assert_eq!(2, tables[0].len());
+
+        let written = write_tables(&tables, &separator).unwrap();
+        let reparsed = parse_tables_with_separator(&written, &separator).unwrap();
+        assert_eq!(
+            tables.iter().map(|t| t.rows()).collect::<Vec<_>>(),
+            reparsed.iter().map(|t| t.rows()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn writes_tables_that_read_back() {
+        let separator = TableSeparator::Line("---".to_string());
+        let tables = [
+            WSVTable::from_iter([[Some("a")], [Some("---")], [Some("b")]]),
+            WSVTable::from_iter([[Some("c")]]),
+        ];
+        let written = write_tables(&tables, &separator).unwrap();
+        assert_eq!("a\n\"---\"\nb\n---\nc", written);
+        let reparsed = parse_tables_with_separator(&written, &separator).unwrap();
+        assert_eq!(2, reparsed.len());
+        assert_eq!(tables[0].rows(), reparsed[0].rows());
+
+        let separator = TableSeparator::Line("- -".to_string());
+        let nulls = [WSVTable::from_iter([
+            vec![Some("a"), None],
+            vec![None, None],
+        ])];
+        assert_eq!(
+            Err(WSVWriteTablesError::RowMatchesSeparator { table: 0, row: 1 }),
+            write_tables(&nulls, &separator)
+        );
+
+        let with_empty_row = [WSVTable::new(vec![
+            vec![Some(Cow::Borrowed("a"))],
+            vec![],
+            vec![Some(Cow::Borrowed("b"))],
+        ])];
+        let err = write_tables(&with_empty_row, &TableSeparator::BlankLine).unwrap_err();
+        assert_eq!(WSVWriteTablesError::EmptyRow { table: 0, row: 1 }, err);
+        assert_eq!("(table: 1, row: 2) Empty Row", err.to_string());
+        assert_eq!(
+            Err(WSVWriteTablesError::EmptyTable { table: 1 }),
+            write_tables(
+                &[tables[1].clone(), WSVTable::new(Vec::new())],
+                &TableSeparator::BlankLine
+            )
+        );
+    }
+
+    #[test]
+    fn names_tables_with_comments() {
+        let input =
+            "# table: users\nid name\n1 ann\n# table:  orders \nid user\n7 1\n\n# table:\nx";
+        let tables = parse_tables(input).unwrap();
+        assert_eq!(3, tables.len());
+        assert_eq!(
+            Some("ann"),
+            tables.table("users").unwrap().rows()[1][1].as_deref()
+        );
+        assert_eq!(5, tables.table("orders").unwrap().first_line());
+        assert_eq!(None, tables[2].name());
+        assert!(tables.table("x").is_none());
+
+        let mut renamed = tables.clone().into_tables();
+        renamed[2] = WSVTable::new(vec![vec![Some(Cow::Borrowed("y"))]]).with_name("misc");
+        let written = write_tables(&renamed, &TableSeparator::BlankLine).unwrap();
+        let reparsed = parse_tables(&written).unwrap();
+        assert_eq!(
+            vec![Some("users"), Some("orders"), Some("misc")],
+            reparsed.iter().map(|t| t.name()).collect::<Vec<_>>()
+        );
+        assert_eq!(tables[1].rows(), reparsed[1].rows());
+    }
+
+    #[test]
+    fn collects_tables_from_iterators() {
+        let mut users = [["1", "a"], ["2", "b"]]
+            .into_iter()
+            .map(|row| row.map(Some))
+            .collect::<WSVTable>()
+            .with_name("users");
+        users.extend([vec![Some("3".to_string()), None]]);
+        let document = [users, WSVTable::from_iter([[Some("x")]])]
+            .into_iter()
+            .collect::<WSVTables>();
+
+        let written = write_tables(&document, &TableSeparator::BlankLine).unwrap();
+        let parsed = parse_tables(&written).unwrap();
+        assert_eq!(document.tables()[0].rows(), parsed.tables()[0].rows());
+        assert_eq!(3, parsed.table("users").unwrap().len());
+        assert_eq!(document.tables()[1].rows(), parsed.tables()[1].rows());
+    }
+
+    #[test]
+    fn reorders_columns() {
+        let mut table = parse_tables("id name age\n1 ann 30\n2 bob")
+            .unwrap()
+            .into_tables()[0]
+            .clone();
+        table.reorder_columns(&[2, 0, 1]);
+        assert_eq!(
+            vec!["age", "id", "name"],
+            table.headers().unwrap().names().to_vec()
+        );
+        assert_eq!(
+            vec![None, Some(Cow::Borrowed("2")), Some(Cow::Borrowed("bob"))],
+            table[2]
+        );
+
+        table.move_column(0, 2);
+        assert_eq!(
+            vec!["id", "name", "age"],
+            table.headers().unwrap().names().to_vec()
+        );
+    }
+
+    #[test]
+    fn edits_columns_by_name() {
+        let mut table = parse_tables("\nid name age\n1 ann 30\n2 bob")
+            .unwrap()
+            .into_tables()[0]
+            .clone();
+        table.rename_column("age", "years").unwrap();
+        table.add_column("active", Some("yes")).unwrap();
+        assert_eq!(
+            Some("yes"),
+            table.headers().unwrap().get(&table[2], "active")
+        );
+        assert_eq!(None, table[2][2]);
+
+        let ages = table.drop_column("years").unwrap();
+        assert_eq!(vec![Some(Cow::Borrowed("30")), None], ages);
+        assert_eq!(
+            vec!["id", "name", "active"],
+            table.headers().unwrap().names().to_vec()
+        );
+
+        let err = table.rename_column("id", "name").unwrap_err();
+        assert_eq!(
+            "(line: 2, value: 1) Duplicate Header (\"name\", first used by value 2)",
+            err.to_string()
+        );
+        assert_eq!(
+            Err(WSVHeaderError::UnknownHeader {
+                line: 2,
+                name: "years".to_string()
+            }),
+            table.drop_column("years")
+        );
+        assert!(table.add_column("id", None::<&str>).is_err());
+
+        let mut empty = WSVTable::new(Vec::new());
+        empty.add_column("x", None::<&str>).unwrap();
+        assert_eq!(vec![vec![Some(Cow::Borrowed("x"))]], empty.rows());
+    }
+}
@@ -0,0 +1,115 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::io::Write;
+
+use crate::{measure_value, write_aligned_value, WriterOptions, NEWLINE};
+
+/// Writes rows of values as packed WSV, where each row comes out of a
+/// fallible source such as a lazy parse. Writing stops at the first Err
+/// row, which is returned as WSVWriteError::Row, so pipelines don't have
+/// to unwrap every row before handing it to a WSVWriter.
+///
+/// Rows are written as they arrive, so anything before the failed row
+/// has already been written when the error is returned. Wrap the writer
+/// in a BufWriter if it is unbuffered.
+pub fn try_write<Rows, Row, BorrowStr, E, W>(
+    rows: Rows,
+    mut writer: W,
+) -> Result<(), WSVWriteError<E>>
+where
+    Rows: IntoIterator<Item = Result<Row, E>>,
+    Row: IntoIterator<Item = Option<BorrowStr>>,
+    BorrowStr: AsRef<str>,
+    W: Write,
+{
+    let options = WriterOptions::default();
+    let mut line = String::new();
+    for (index, row) in rows.into_iter().enumerate() {
+        let row = row.map_err(WSVWriteError::Row)?;
+        line.clear();
+        if index != 0 {
+            line.push(NEWLINE);
+        }
+        for (index, value) in row.into_iter().enumerate() {
+            if index != 0 {
+                line.push(' ');
+            }
+            let value = value.as_ref().map(|value| value.as_ref());
+            let (needs_quotes, _) = measure_value(value, &options);
+            write_aligned_value(&mut line, value, needs_quotes, 0, &options);
+        }
+        writer
+            .write_all(line.as_bytes())
+            .map_err(WSVWriteError::Io)?;
+    }
+    Ok(())
+}
+
+/// Same as try_write, but collects the packed WSV into a String.
+pub fn try_to_string<Rows, Row, BorrowStr, E>(rows: Rows) -> Result<String, E>
+where
+    Rows: IntoIterator<Item = Result<Row, E>>,
+    Row: IntoIterator<Item = Option<BorrowStr>>,
+    BorrowStr: AsRef<str>,
+{
+    let mut bytes = Vec::new();
+    match try_write(rows, &mut bytes) {
+        Ok(()) => Ok(String::from_utf8(bytes).expect("the writer only writes strs")),
+        Err(WSVWriteError::Row(err)) => Err(err),
+        Err(WSVWriteError::Io(_)) => unreachable!("writing to a Vec can't fail"),
+    }
+}
+
+/// An error from try_write: either the first Err row from the input or
+/// an IO error from the writer.
+#[derive(Debug)]
+pub enum WSVWriteError<E> {
+    Io(std::io::Error),
+    Row(E),
+}
+
+impl<E: Display> Display for WSVWriteError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WSVWriteError::Io(err) => write!(f, "{}", err),
+            WSVWriteError::Row(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for WSVWriteError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WSVWriteError::Io(err) => Some(err),
+            WSVWriteError::Row(err) => Some(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, parse_lazy, try_to_string, try_write, WSVErrorType, WSVWriteError};
+
+    #[test]
+    fn writes_until_the_first_error() {
+        let input = "1 2\n\"a b\" -\n\n3";
+        let doubled = parse_lazy(input.chars()).map(|line| {
+            line.map(|line| {
+                line.into_iter()
+                    .map(|value| value.map(|value| value.repeat(2)))
+                    .collect::<Vec<_>>()
+            })
+        });
+        let written = try_to_string(doubled).unwrap();
+        assert_eq!("11 22\n\"a ba b\" -\n\n33", written);
+        assert_eq!(4, parse(&written).unwrap().len());
+
+        let mut output = Vec::new();
+        let err = try_write(parse_lazy("a\n\"b".chars()), &mut output).unwrap_err();
+        assert!(matches!(
+            err,
+            WSVWriteError::Row(err) if err.err_type() == WSVErrorType::StringNotClosed
+        ));
+        assert_eq!("a", String::from_utf8(output).unwrap());
+    }
+}
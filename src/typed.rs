@@ -0,0 +1,119 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::{parse, WSVError};
+
+/// Same as parse (see the documentation there for behavior details), but
+/// converts every non-null value into T with FromStr. This is handy for
+/// files where every value has the same type, such as a grid of numbers.
+pub fn parse_as<T: FromStr>(
+    source_text: &str,
+) -> Result<Vec<Vec<Option<T>>>, TypedParseError<T::Err>> {
+    parse(source_text)?
+        .into_iter()
+        .enumerate()
+        .map(|(line_index, row)| {
+            row.into_iter()
+                .enumerate()
+                .map(|(column, value)| {
+                    let Some(value) = value else {
+                        return Ok(None);
+                    };
+                    value
+                        .parse()
+                        .map(Some)
+                        .map_err(|error| TypedParseError::Conversion {
+                            line: line_index + 1,
+                            column,
+                            value: value.into_owned(),
+                            error,
+                        })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// An error from parse_as.
+#[derive(Debug, Clone)]
+pub enum TypedParseError<E> {
+    /// The source text was not valid WSV.
+    WSV(WSVError),
+    /// A value could not be converted. The line number is 1 based and
+    /// the column is the 0 based index of the value in its row.
+    Conversion {
+        line: usize,
+        column: usize,
+        value: String,
+        error: E,
+    },
+}
+
+impl<E: Display> Display for TypedParseError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypedParseError::WSV(err) => write!(f, "{}", err),
+            TypedParseError::Conversion {
+                line,
+                column,
+                value,
+                error,
+            } => write!(
+                f,
+                "(line: {}, value: {}) Invalid Value ({:?}): {}",
+                line,
+                column + 1,
+                value,
+                error
+            ),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for TypedParseError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TypedParseError::WSV(err) => Some(err),
+            TypedParseError::Conversion { error, .. } => Some(error),
+        }
+    }
+}
+
+impl<E> From<WSVError> for TypedParseError<E> {
+    fn from(value: WSVError) -> Self {
+        TypedParseError::WSV(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_as, TypedParseError};
+
+    #[test]
+    fn parses_typed_values() {
+        assert_eq!(
+            vec![vec![Some(1.5), None], vec![], vec![Some(-3.0)]],
+            parse_as::<f64>("1.5 -\n\n\"-3\"").unwrap()
+        );
+
+        let err = parse_as::<u8>("1 2\n3 300").unwrap_err();
+        assert!(matches!(
+            &err,
+            TypedParseError::Conversion {
+                line: 2,
+                column: 1,
+                value,
+                ..
+            } if value == "300"
+        ));
+        assert_eq!(
+            "(line: 2, value: 2) Invalid Value (\"300\"): number too large to fit in target type",
+            err.to_string()
+        );
+        assert!(matches!(
+            parse_as::<u8>("\"1"),
+            Err(TypedParseError::WSV(_))
+        ));
+    }
+}
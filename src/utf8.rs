@@ -0,0 +1,188 @@
+use crate::{
+    Location, OwnedWSVToken, WSVError, WSVErrorType, WSVFallibleLines, WSVFallibleTokenizer,
+    WSVSourceError, NEWLINE,
+};
+
+/// Decodes UTF-8 one char at a time. An invalid or truncated sequence
+/// is returned as the location of its first byte and ends decoding.
+struct Utf8Decoder<Bytes> {
+    bytes: Bytes,
+    location: Location,
+    next_byte_index: usize,
+    finished: bool,
+}
+
+impl<Bytes: Iterator<Item = u8>> Iterator for Utf8Decoder<Bytes> {
+    type Item = Result<char, Location>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let first = self.bytes.next()?;
+        let width = match first {
+            0x00..=0x7F => 1,
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => 0,
+        };
+
+        let mut buffer = [first, 0, 0, 0];
+        let mut len = 1;
+        while len < width {
+            match self.bytes.next() {
+                Some(byte) => buffer[len] = byte,
+                None => break,
+            }
+            len += 1;
+        }
+
+        // from_utf8 also rejects overlong encodings and surrogates.
+        let decoded = std::str::from_utf8(&buffer[..len])
+            .ok()
+            .filter(|_| width != 0)
+            .and_then(|decoded| decoded.chars().next());
+        let Some(ch) = decoded else {
+            self.finished = true;
+            let mut location = self.location.clone();
+            location.byte_index = self.next_byte_index;
+            return Some(Err(location));
+        };
+
+        // Track locations the same way WSVLazyTokenizer does.
+        if ch == NEWLINE {
+            self.location.line += 1;
+            self.location.col = 1;
+        } else {
+            self.location.col += 1;
+        }
+        self.location.byte_index = self.next_byte_index;
+        self.next_byte_index += len;
+        Some(Ok(ch))
+    }
+}
+
+fn decoder<Bytes: IntoIterator<Item = u8>>(bytes: Bytes) -> Utf8Decoder<Bytes::IntoIter> {
+    Utf8Decoder {
+        bytes: bytes.into_iter(),
+        location: Location::default(),
+        next_byte_index: 0,
+        finished: false,
+    }
+}
+
+fn invalid_utf8(err: WSVSourceError<Location>) -> WSVError {
+    match err {
+        WSVSourceError::WSV(err) => err,
+        WSVSourceError::Source(location) => WSVError {
+            err_type: WSVErrorType::InvalidUtf8,
+            location,
+        },
+    }
+}
+
+/// Same as WSVLazyTokenizer, but decodes UTF-8 bytes (for example from
+/// io::Read::bytes) itself. Invalid UTF-8 is an InvalidUtf8 error at the
+/// first byte of the bad sequence, and ends the iteration.
+pub struct WSVByteTokenizer<Bytes>
+where
+    Bytes: Iterator<Item = u8>,
+{
+    tokenizer: WSVFallibleTokenizer<Utf8Decoder<Bytes>, Location>,
+}
+
+impl<Bytes> WSVByteTokenizer<Bytes>
+where
+    Bytes: Iterator<Item = u8>,
+{
+    pub fn new<Source>(source_bytes: Source) -> Self
+    where
+        Source: IntoIterator<Item = u8, IntoIter = Bytes>,
+    {
+        Self {
+            tokenizer: WSVFallibleTokenizer::new(decoder(source_bytes)),
+        }
+    }
+}
+
+impl<Bytes> Iterator for WSVByteTokenizer<Bytes>
+where
+    Bytes: Iterator<Item = u8>,
+{
+    type Item = Result<OwnedWSVToken, WSVError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.tokenizer.next()?.map_err(invalid_utf8))
+    }
+}
+
+/// Same as parse_lazy, but decodes UTF-8 bytes itself. See
+/// WSVByteTokenizer for how invalid UTF-8 is reported.
+pub fn parse_lazy_bytes<Source>(source_bytes: Source) -> WSVByteLines<Source::IntoIter>
+where
+    Source: IntoIterator<Item = u8>,
+{
+    WSVByteLines {
+        lines: crate::parse_lazy_fallible(decoder(source_bytes)),
+    }
+}
+
+/// An iterator over the lines of UTF-8 bytes. See parse_lazy_bytes.
+pub struct WSVByteLines<Bytes>
+where
+    Bytes: Iterator<Item = u8>,
+{
+    lines: WSVFallibleLines<Utf8Decoder<Bytes>, Location>,
+}
+
+impl<Bytes> Iterator for WSVByteLines<Bytes>
+where
+    Bytes: Iterator<Item = u8>,
+{
+    type Item = Result<Vec<Option<String>>, WSVError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.lines.next()?.map_err(invalid_utf8))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use crate::{parse, parse_lazy_bytes, WSVByteTokenizer, WSVErrorType};
+
+    #[test]
+    fn decodes_utf8_bytes() {
+        let input = include_str!("../tests/1_stenway.com");
+        let expected = parse(input).unwrap();
+        let lines = parse_lazy_bytes(Cursor::new(input).bytes().map(Result::unwrap))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            expected
+                .iter()
+                .map(|row| row.iter().map(|v| v.as_deref()).collect::<Vec<_>>())
+                .collect::<Vec<_>>(),
+            lines
+                .iter()
+                .map(|row| row.iter().map(|v| v.as_deref()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+
+        let mut lines = parse_lazy_bytes(b"\xC3\xA9 a\nb \xE2\x82".to_vec());
+        assert_eq!(Some("é"), lines.next().unwrap().unwrap()[0].as_deref());
+        let err = lines.next().unwrap().unwrap_err();
+        assert_eq!(WSVErrorType::InvalidUtf8, err.err_type());
+        assert_eq!("(line: 2, column: 3) Invalid UTF-8", err.to_string());
+        assert!(lines.next().is_none());
+
+        let mut tokens = WSVByteTokenizer::new(b"\xC0\x80".to_vec());
+        assert!(matches!(
+            tokens.next(),
+            Some(Err(err)) if err.err_type() == WSVErrorType::InvalidUtf8
+        ));
+        assert!(tokens.next().is_none());
+    }
+}
@@ -0,0 +1,96 @@
+use crate::scanner::Scanner;
+use crate::{WSVError, WSVTokenizer, NEWLINE};
+
+/// Checks the syntax of a .wsv (whitespace separated value) file without
+/// building any tokens or Vecs. This is intended as a fast pre-flight check
+/// for very large inputs where the values themselves are not needed yet.
+///
+/// Unlike the tokenizers, this does not stop at the first error. Since a row
+/// can never span multiple lines in WSV, the scanner skips to the next line
+/// after an error and keeps going, so every invalid line is reported. The
+/// only allocation made is the Vec of errors in the failure case.
+pub fn validate(source_text: &str) -> Result<(), Vec<WSVError>> {
+    let mut scanner = Scanner::new(source_text);
+    let mut errors = Vec::new();
+
+    loop {
+        scanner.skip_while(WSVTokenizer::is_whitespace);
+        let result = match scanner.peek() {
+            None => break,
+            Some(NEWLINE) => {
+                scanner.next();
+                Ok(())
+            }
+            Some('#') => {
+                scanner.skip_while(|ch| ch != NEWLINE);
+                Ok(())
+            }
+            Some('"') => scanner.scan_string(),
+            Some(_) => scanner.scan_value(),
+        };
+
+        if let Err(err) = result {
+            errors.push(err);
+            scanner.skip_while(|ch| ch != NEWLINE);
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{validate, WSVErrorType, WSVTokenizer};
+
+    #[test]
+    fn valid_files_pass() {
+        assert!(validate(include_str!("../tests/1_stenway.com")).is_ok());
+        assert!(validate(include_str!("../tests/my_test.txt")).is_ok());
+        assert!(validate("").is_ok());
+        assert!(validate("- \"\"\n\n#comment only").is_ok());
+    }
+
+    #[test]
+    fn reports_every_invalid_line() {
+        let input = "a b\"c\n\"unclosed\n\"bad\"/ break\"\n\"x\"y\nok";
+        let errors = validate(input).unwrap_err();
+        let types = errors.iter().map(|err| err.err_type()).collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                WSVErrorType::InvalidDoubleQuoteAfterValue,
+                WSVErrorType::StringNotClosed,
+                WSVErrorType::InvalidStringLineBreak,
+                WSVErrorType::InvalidCharacterAfterString,
+            ],
+            types
+        );
+        let lines = errors
+            .iter()
+            .map(|err| err.location().line())
+            .collect::<Vec<_>>();
+        assert_eq!(vec![1, 2, 3, 4], lines);
+    }
+
+    #[test]
+    fn locations_match_tokenizer() {
+        for input in [
+            "value\"",
+            "\"unclosed",
+            "1 2\n  \"unclosed\n",
+            "\"bad\"/x\"",
+            "\"string\"trailing",
+        ] {
+            let expected = WSVTokenizer::new(input)
+                .find_map(|token| token.err())
+                .unwrap();
+            let actual = validate(input).unwrap_err().remove(0);
+            assert_eq!(expected.err_type(), actual.err_type());
+            assert_eq!(expected.location().line(), actual.location().line());
+            assert_eq!(expected.location().col(), actual.location().col());
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::borrow::Cow;
+
+use crate::{WSVError, WSVToken, WSVTokenizer};
+
+/// Callbacks for parse_visit. Every method does nothing by default, so
+/// implementations only need the ones they care about. Line numbers are
+/// 1 based.
+pub trait WSVVisitor<'wsv> {
+    /// Called at the start of every row, before any of its values.
+    fn start_row(&mut self, _line: usize) {}
+    /// Called for every value in the row, in order. Nulls are None. The
+    /// value borrows from the source unless it had escape sequences.
+    fn cell(&mut self, _value: Option<Cow<'wsv, str>>) {}
+    /// Called for a comment, without its '#'. Comments come after the
+    /// values of their row.
+    fn comment(&mut self, _comment: &'wsv str) {}
+    /// Called at the end of every row, after all of its values.
+    fn end_row(&mut self, _line: usize) {}
+    /// Called if the source can't be parsed, before parse_visit returns
+    /// the error. No more callbacks are made after it.
+    fn error(&mut self, _error: &WSVError) {}
+}
+
+/// Parses the source text and hands each part of it to the visitor as it
+/// goes, without building any rows. This is the cheapest way to bind WSV
+/// to custom types. Rows are the same as parse's: every line is a row,
+/// blank and comment-only lines included, except for a last line with no
+/// values. A comment on that line is still passed to comment, outside of
+/// any row.
+pub fn parse_visit<'wsv, V>(source_text: &'wsv str, visitor: &mut V) -> Result<(), WSVError>
+where
+    V: WSVVisitor<'wsv> + ?Sized,
+{
+    let mut line = 1;
+    let mut in_row = false;
+    // A comment on a line without values, which is only a row if a line
+    // feed follows it.
+    let mut pending_comment = None;
+    for token in WSVTokenizer::new(source_text) {
+        let token = match token {
+            Ok(token) => token,
+            Err(error) => {
+                visitor.error(&error);
+                return Err(error);
+            }
+        };
+        match token {
+            WSVToken::Comment(comment) if in_row => visitor.comment(comment),
+            WSVToken::Comment(comment) => pending_comment = Some(comment),
+            WSVToken::LF => {
+                if !in_row {
+                    visitor.start_row(line);
+                }
+                if let Some(comment) = pending_comment.take() {
+                    visitor.comment(comment);
+                }
+                visitor.end_row(line);
+                in_row = false;
+                line += 1;
+            }
+            WSVToken::Null | WSVToken::Value(_) => {
+                if !in_row {
+                    visitor.start_row(line);
+                    in_row = true;
+                }
+                let value = match token {
+                    WSVToken::Value(value) => Some(value),
+                    _ => None,
+                };
+                visitor.cell(value);
+            }
+        }
+    }
+    if in_row {
+        visitor.end_row(line);
+    } else if let Some(comment) = pending_comment {
+        visitor.comment(comment);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::{parse, parse_visit, WSVError, WSVVisitor};
+
+    #[derive(Default)]
+    struct Recorder(Vec<String>);
+
+    impl<'wsv> WSVVisitor<'wsv> for Recorder {
+        fn start_row(&mut self, line: usize) {
+            self.0.push(format!("start {}", line));
+        }
+        fn cell(&mut self, value: Option<Cow<'wsv, str>>) {
+            let borrowed = matches!(value, Some(Cow::Borrowed(_)));
+            self.0
+                .push(format!("cell {:?} {}", value.as_deref(), borrowed));
+        }
+        fn comment(&mut self, comment: &'wsv str) {
+            self.0.push(format!("comment {}", comment));
+        }
+        fn end_row(&mut self, line: usize) {
+            self.0.push(format!("end {}", line));
+        }
+        fn error(&mut self, error: &WSVError) {
+            self.0.push(format!("error {}", error.location().line()));
+        }
+    }
+
+    #[test]
+    fn visits_rows_and_cells() {
+        let mut recorder = Recorder::default();
+        parse_visit("a \"b\"\"c\" - #note\n\n\"d\"\n", &mut recorder).unwrap();
+        assert_eq!(
+            vec![
+                "start 1",
+                "cell Some(\"a\") true",
+                "cell Some(\"b\\\"c\") false",
+                "cell None false",
+                "comment note",
+                "end 1",
+                "start 2",
+                "end 2",
+                "start 3",
+                "cell Some(\"d\") true",
+                "end 3",
+            ],
+            recorder.0
+        );
+
+        // Counting rows without building them.
+        struct Rows(usize);
+        impl WSVVisitor<'_> for Rows {
+            fn end_row(&mut self, _line: usize) {
+                self.0 += 1;
+            }
+        }
+        for input in ["", "a", "a\n", "a\n\n", "\n# x", "# x\n"] {
+            let mut rows = Rows(0);
+            parse_visit(input, &mut rows).unwrap();
+            assert_eq!(parse(input).unwrap().len(), rows.0, "{:?}", input);
+        }
+
+        let mut recorder = Recorder::default();
+        assert!(parse_visit("a\n\"b", &mut recorder).is_err());
+        assert_eq!(
+            vec!["start 1", "cell Some(\"a\") true", "end 1", "error 2"],
+            recorder.0
+        );
+    }
+}
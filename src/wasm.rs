@@ -0,0 +1,91 @@
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+use crate::{parse, validate, ColumnAlignment, WSVError, WSVWriter};
+
+/// Parses WSV into an array of rows, where each row is an array of
+/// strings and nulls. Throws an Error object with the details of the
+/// first syntax error (see validate for its properties).
+#[wasm_bindgen(js_name = parse)]
+pub fn parse_js(source: &str) -> Result<Array, JsValue> {
+    let rows = parse(source).map_err(|err| JsValue::from(error_object(&err)))?;
+    Ok(rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|value| match value {
+                    None => JsValue::NULL,
+                    Some(value) => JsValue::from_str(value),
+                })
+                .collect::<Array>()
+        })
+        .collect())
+}
+
+/// Writes an array of rows (arrays of strings, nulls or undefined) as WSV.
+/// The alignment may be "packed" (the default), "left" or "right".
+#[wasm_bindgen(js_name = write)]
+pub fn write_js(rows: Array, alignment: Option<String>) -> Result<String, JsValue> {
+    let alignment = match alignment.as_deref() {
+        None | Some("packed") => ColumnAlignment::Packed,
+        Some("left") => ColumnAlignment::Left,
+        Some("right") => ColumnAlignment::Right,
+        Some(other) => {
+            return Err(js_sys::TypeError::new(&format!("Unknown Alignment ({:?})", other)).into())
+        }
+    };
+
+    let mut values = Vec::with_capacity(rows.length() as usize);
+    for row in rows.iter() {
+        let row = row
+            .dyn_into::<Array>()
+            .map_err(|_| js_sys::TypeError::new("Expected An Array Of Rows"))?;
+        let row = row
+            .iter()
+            .map(|value| {
+                if value.is_null() || value.is_undefined() {
+                    Ok(None)
+                } else {
+                    value
+                        .as_string()
+                        .map(Some)
+                        .ok_or_else(|| js_sys::TypeError::new("Expected A String Or Null"))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        values.push(row);
+    }
+    Ok(WSVWriter::new(values).align_columns(alignment).to_string())
+}
+
+/// Checks WSV for syntax errors and returns every error found, as
+/// objects with a message, type, line, column and byteIndex. An
+/// empty array means the input is valid.
+#[wasm_bindgen(js_name = validate)]
+pub fn validate_js(source: &str) -> Array {
+    match validate(source) {
+        Ok(()) => Array::new(),
+        Err(errors) => errors
+            .iter()
+            .map(|err| JsValue::from(error_object(err)))
+            .collect(),
+    }
+}
+
+/// Converts a WSVError into a JavaScript Error with the error's
+/// type and location as extra properties.
+fn error_object(err: &WSVError) -> Object {
+    let object = js_sys::Error::new(&err.to_string());
+    let location = err.location();
+    let properties = [
+        ("type", JsValue::from_str(&format!("{:?}", err.err_type()))),
+        ("line", JsValue::from(location.line() as u32)),
+        ("column", JsValue::from(location.col() as u32)),
+        ("byteIndex", JsValue::from(location.byte_index as u32)),
+    ];
+    for (name, value) in properties {
+        // Setting a property on a fresh Error object can't fail.
+        let _ = Reflect::set(&object, &JsValue::from_str(name), &value);
+    }
+    object.into()
+}
@@ -0,0 +1,193 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::{parse, WSVReadError};
+
+/// How long a file has to go without changes before it's reloaded.
+const DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// The rows of a .wsv file, shared between readers of a WSVWatchedTable.
+pub type WSVSnapshot = Arc<Vec<Vec<Option<String>>>>;
+
+/// A .wsv file that is parsed again every time it changes on disk, for
+/// services that use WSV files as config that can be edited while they
+/// run. Readers get the latest rows with snapshot, which stays valid
+/// (and unchanged) even if the file is reloaded while it's being used.
+///
+/// The directory holding the file is watched rather than the file
+/// itself, so editors and tools (like rewrite_file) that replace the
+/// file with a new one are picked up too. If a new version of the file
+/// can't be read or parsed, the last good rows are kept and the error
+/// can be taken with take_error.
+pub struct WSVWatchedTable {
+    state: Arc<Mutex<WatchState>>,
+    // Watching stops when the watcher is dropped.
+    _watcher: RecommendedWatcher,
+}
+
+struct WatchState {
+    snapshot: WSVSnapshot,
+    version: u64,
+    error: Option<WSVReadError>,
+}
+
+impl WSVWatchedTable {
+    /// Parses the file at the given path and starts watching it. Fails if
+    /// the file can't be read or parsed, or can't be watched.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, WSVReadError> {
+        let path = path.as_ref().to_path_buf();
+        let state = Arc::new(Mutex::new(WatchState {
+            snapshot: Arc::new(load(&path)?),
+            version: 0,
+            error: None,
+        }));
+
+        // Writes often come as a truncate followed by the new contents,
+        // so changes are only reloaded once the file has been quiet for
+        // a moment, on a thread of their own.
+        let (changed, changes) = channel();
+        let file_name = path.file_name().map(|name| name.to_os_string());
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+                if matches!(event.kind, EventKind::Access(_) | EventKind::Remove(_)) {
+                    return;
+                }
+                if event
+                    .paths
+                    .iter()
+                    .any(|changed| changed.file_name() == file_name.as_deref())
+                {
+                    let _ = changed.send(());
+                }
+            })
+            .map_err(std::io::Error::other)?;
+
+        let reload_state = state.clone();
+        let reload_path = path.clone();
+        // The thread stops once the watcher (and with it the sender) is
+        // dropped.
+        thread::spawn(move || {
+            while changes.recv().is_ok() {
+                while changes.recv_timeout(DEBOUNCE).is_ok() {}
+                let loaded = load(&reload_path);
+                let Ok(mut state) = reload_state.lock() else {
+                    return;
+                };
+                match loaded {
+                    // The file is being replaced, so wait for the new one.
+                    Err(WSVReadError::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(err) => state.error = Some(err),
+                    Ok(rows) => {
+                        if *state.snapshot != rows {
+                            state.snapshot = Arc::new(rows);
+                            state.version += 1;
+                        }
+                    }
+                }
+            }
+        });
+
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .map_err(std::io::Error::other)?;
+
+        Ok(Self {
+            state,
+            _watcher: watcher,
+        })
+    }
+
+    /// The latest successfully parsed rows of the file.
+    pub fn snapshot(&self) -> WSVSnapshot {
+        self.lock().snapshot.clone()
+    }
+
+    /// How many times the rows have changed since the file was opened.
+    /// Compare it to an earlier value to find out if a reload happened.
+    pub fn version(&self) -> u64 {
+        self.lock().version
+    }
+
+    /// Takes the error from the last reload that failed, if any. The
+    /// rows from before the failure are still being used.
+    pub fn take_error(&self) -> Option<WSVReadError> {
+        self.lock().error.take()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, WatchState> {
+        // The state is only ever replaced whole, so it can't be left
+        // inconsistent by a panic while locked.
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn load(path: &Path) -> Result<Vec<Vec<Option<String>>>, WSVReadError> {
+    let text = fs::read_to_string(path)?;
+    Ok(parse(&text)?
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|value| value.map(|value| value.into_owned()))
+                .collect()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use crate::{WSVReadError, WSVWatchedTable};
+
+    #[test]
+    fn reloads_changed_files() {
+        let path =
+            std::env::temp_dir().join(format!("whitespacesv_watch_{}.wsv", std::process::id()));
+        fs::write(&path, "name value\nlimit 10").unwrap();
+
+        let table = WSVWatchedTable::open(&path).unwrap();
+        let before = table.snapshot();
+        assert_eq!(Some("10"), before[1][1].as_deref());
+
+        let wait_for = |done: &mut dyn FnMut() -> bool| {
+            let start = Instant::now();
+            while !done() && start.elapsed() < Duration::from_secs(10) {
+                thread::sleep(Duration::from_millis(10));
+            }
+        };
+
+        fs::write(&path, "name value\nlimit 20").unwrap();
+        wait_for(&mut || table.version() != 0);
+        assert_eq!(Some("20"), table.snapshot()[1][1].as_deref());
+        assert_eq!(Some("10"), before[1][1].as_deref());
+
+        fs::write(&path, "name \"value").unwrap();
+        let mut error = None;
+        wait_for(&mut || {
+            error = error.take().or_else(|| table.take_error());
+            error.is_some()
+        });
+        assert!(matches!(error, Some(WSVReadError::WSV(_))));
+        assert_eq!(Some("20"), table.snapshot()[1][1].as_deref());
+
+        drop(table);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,161 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::path::Path;
+
+use calamine::{open_workbook_auto, Data, Reader};
+use rust_xlsxwriter::{Workbook, XlsxError};
+
+/// Reads a worksheet from an Excel (or OpenDocument) spreadsheet into
+/// rows. If no sheet name is given, the first sheet is read. Empty cells
+/// become nulls and trailing empty cells are left off the end of each
+/// row. Every other cell is written out as the text Excel would show
+/// without formatting, so dates come out as Excel's serial numbers and
+/// error cells as their error code (like "#DIV/0!").
+///
+/// The rows start at the first used row and column of the sheet.
+pub fn xlsx_to_wsv<P: AsRef<Path>>(
+    path: P,
+    sheet: Option<&str>,
+) -> Result<Vec<Vec<Option<String>>>, WSVXlsxError> {
+    let mut workbook = open_workbook_auto(path)?;
+    let range = match sheet {
+        Some(sheet) => {
+            if !workbook.sheet_names().iter().any(|name| name == sheet) {
+                return Err(WSVXlsxError::SheetNotFound {
+                    name: sheet.to_string(),
+                });
+            }
+            workbook.worksheet_range(sheet)?
+        }
+        None => match workbook.worksheet_range_at(0) {
+            Some(range) => range?,
+            None => return Ok(Vec::new()),
+        },
+    };
+
+    Ok(range
+        .rows()
+        .map(|cells| {
+            let mut row = cells
+                .iter()
+                .map(|cell| match cell {
+                    Data::Empty => None,
+                    cell => Some(cell.to_string()),
+                })
+                .collect::<Vec<_>>();
+            while let Some(None) = row.last() {
+                row.pop();
+            }
+            row
+        })
+        .collect())
+}
+
+/// Writes rows into a new spreadsheet at the given path, with a single
+/// worksheet of the given name. Nulls become empty cells. Values that
+/// read back as exactly the same number (like "1.5", but not "1.50" or
+/// "007") are written as numbers so Excel can calculate with them, and
+/// everything else is written as text.
+pub fn wsv_to_xlsx<P, Row, Str>(path: P, sheet: &str, rows: &[Row]) -> Result<(), WSVXlsxError>
+where
+    P: AsRef<Path>,
+    Row: AsRef<[Option<Str>]>,
+    Str: AsRef<str>,
+{
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name(sheet)?;
+
+    for (row_index, row) in rows.iter().enumerate() {
+        let row_index = u32::try_from(row_index).map_err(|_| WSVXlsxError::TooLarge)?;
+        for (column, value) in row.as_ref().iter().enumerate() {
+            let Some(value) = value else { continue };
+            let value = value.as_ref();
+            let column = u16::try_from(column).map_err(|_| WSVXlsxError::TooLarge)?;
+            match value.parse::<f64>() {
+                Ok(number) if number.is_finite() && number.to_string() == value => {
+                    worksheet.write_number(row_index, column, number)?;
+                }
+                _ => {
+                    worksheet.write_string(row_index, column, value)?;
+                }
+            }
+        }
+    }
+    workbook.save(path)?;
+    Ok(())
+}
+
+/// An error from xlsx_to_wsv or wsv_to_xlsx.
+#[derive(Debug)]
+pub enum WSVXlsxError {
+    /// An error from reading the spreadsheet.
+    Read(calamine::Error),
+    /// An error from writing the spreadsheet.
+    Write(XlsxError),
+    /// The spreadsheet has no sheet with the given name.
+    SheetNotFound { name: String },
+    /// There are more rows or columns than a worksheet can hold.
+    TooLarge,
+}
+
+impl Display for WSVXlsxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WSVXlsxError::Read(err) => write!(f, "{}", err),
+            WSVXlsxError::Write(err) => write!(f, "{}", err),
+            WSVXlsxError::SheetNotFound { name } => write!(f, "Sheet Not Found ({:?})", name),
+            WSVXlsxError::TooLarge => write!(f, "Too Large For A Worksheet"),
+        }
+    }
+}
+
+impl Error for WSVXlsxError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WSVXlsxError::Read(err) => Some(err),
+            WSVXlsxError::Write(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<calamine::Error> for WSVXlsxError {
+    fn from(value: calamine::Error) -> Self {
+        WSVXlsxError::Read(value)
+    }
+}
+
+impl From<XlsxError> for WSVXlsxError {
+    fn from(value: XlsxError) -> Self {
+        WSVXlsxError::Write(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, wsv_to_xlsx, xlsx_to_wsv, WSVXlsxError};
+
+    #[test]
+    fn converts_to_and_from_xlsx() {
+        let path = std::env::temp_dir().join(format!("wsv-xlsx-{}.xlsx", std::process::id()));
+        let rows = parse("id name price\n1 \"a b\" 1.50\n2 - 3.25\n007 \"x\"/\"y\"").unwrap();
+        wsv_to_xlsx(&path, "items", &rows).unwrap();
+
+        let read = xlsx_to_wsv(&path, Some("items")).unwrap();
+        assert_eq!(
+            rows.iter()
+                .map(|row| row.iter().map(|v| v.as_deref()).collect::<Vec<_>>())
+                .collect::<Vec<_>>(),
+            read.iter()
+                .map(|row| row.iter().map(|v| v.as_deref()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(read, xlsx_to_wsv(&path, None).unwrap());
+        assert!(matches!(
+            xlsx_to_wsv(&path, Some("other")),
+            Err(WSVXlsxError::SheetNotFound { .. })
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
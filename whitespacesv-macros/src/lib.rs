@@ -0,0 +1,187 @@
+//! The wsv! macro. This crate is re-exported by whitespacesv behind its
+//! macros feature, so depend on that instead of using this directly.
+//!
+//! A proc-macro crate can't depend on the crate that re-exports it, so
+//! this has its own small parser that follows the same rules (and reports
+//! the same errors) as whitespacesv::parse.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, LitStr};
+
+/// Parses a string literal of WSV at compile time into a
+/// `&'static [&'static [Option<&'static str>]]`, with None for nulls
+/// ('-'). A trailing empty row is dropped, the same as parse. Invalid WSV
+/// is a compile error with the line and column of the problem.
+///
+/// ```rust,ignore
+/// use whitespacesv::wsv;
+///
+/// const CODES: &[&[Option<&str>]] = wsv!(r#"
+///     code  name
+///     US    "United States"
+///     -     Unknown
+/// "#);
+/// ```
+#[proc_macro]
+pub fn wsv(input: TokenStream) -> TokenStream {
+    let literal = parse_macro_input!(input as LitStr);
+    match parse(&literal.value()) {
+        Err(err) => syn::Error::new(literal.span(), err)
+            .to_compile_error()
+            .into(),
+        Ok(rows) => {
+            let rows = rows.iter().map(|row| {
+                let values = row.iter().map(|value| match value {
+                    None => quote!(::core::option::Option::None),
+                    Some(value) => quote!(::core::option::Option::Some(#value)),
+                });
+                quote!(&[#(#values),*])
+            });
+            quote!({
+                const WSV: &[&[::core::option::Option<&str>]] = &[#(#rows),*];
+                WSV
+            })
+            .into()
+        }
+    }
+}
+
+fn parse(source: &str) -> Result<Vec<Vec<Option<String>>>, String> {
+    let mut rows = vec![Vec::new()];
+    for (line_index, line) in source.split('\n').enumerate() {
+        if line_index > 0 {
+            rows.push(Vec::new());
+        }
+        let row = rows.last_mut().expect("rows always has a row");
+        let error = |column: usize, message: &str| {
+            format!(
+                "(line: {}, column: {}) {}",
+                line_index + 1,
+                column + 1,
+                message
+            )
+        };
+
+        let chars = line.chars().collect::<Vec<_>>();
+        let mut index = 0;
+        loop {
+            while index < chars.len() && is_whitespace(chars[index]) {
+                index += 1;
+            }
+            match chars.get(index) {
+                None | Some('#') => break,
+                Some('"') => {
+                    let mut value = String::new();
+                    index += 1;
+                    loop {
+                        match chars.get(index) {
+                            None => return Err(error(index, "String Not Closed")),
+                            Some('"') => match chars.get(index + 1) {
+                                Some('"') => {
+                                    value.push('"');
+                                    index += 2;
+                                }
+                                Some('/') => {
+                                    if chars.get(index + 2) != Some(&'"') {
+                                        return Err(error(index + 2, "Invalid String Line Break"));
+                                    }
+                                    value.push('\n');
+                                    index += 3;
+                                }
+                                _ => {
+                                    index += 1;
+                                    break;
+                                }
+                            },
+                            Some(ch) => {
+                                value.push(*ch);
+                                index += 1;
+                            }
+                        }
+                    }
+                    if let Some(ch) = chars.get(index) {
+                        if *ch != '#' && !is_whitespace(*ch) {
+                            return Err(error(index, "Invalid Character After String"));
+                        }
+                    }
+                    row.push(Some(value));
+                }
+                Some(_) => {
+                    let start = index;
+                    while index < chars.len()
+                        && !matches!(chars[index], '"' | '#')
+                        && !is_whitespace(chars[index])
+                    {
+                        index += 1;
+                    }
+                    if chars.get(index) == Some(&'"') {
+                        return Err(error(index, "Invalid Double Quote After Value"));
+                    }
+                    let value = chars[start..index].iter().collect::<String>();
+                    row.push(if value == "-" { None } else { Some(value) });
+                }
+            }
+        }
+    }
+
+    if rows.last().is_some_and(Vec::is_empty) {
+        rows.pop();
+    }
+    Ok(rows)
+}
+
+fn is_whitespace(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{0009}'
+            | '\u{000B}'
+            | '\u{000C}'
+            | '\u{000D}'
+            | '\u{0020}'
+            | '\u{0085}'
+            | '\u{00A0}'
+            | '\u{1680}'
+            | '\u{2000}'
+            | '\u{2001}'
+            | '\u{2002}'
+            | '\u{2003}'
+            | '\u{2004}'
+            | '\u{2005}'
+            | '\u{2006}'
+            | '\u{2007}'
+            | '\u{2008}'
+            | '\u{2009}'
+            | '\u{200A}'
+            | '\u{2028}'
+            | '\u{2029}'
+            | '\u{202F}'
+            | '\u{205F}'
+            | '\u{3000}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    #[test]
+    fn parses_like_the_runtime_parser() {
+        assert_eq!(
+            vec![
+                vec![Some("a".to_string()), None, Some("b\"c\nd".to_string())],
+                vec![],
+                vec![Some("-".to_string()), Some("x#".to_string())],
+            ],
+            parse("a - \"b\"\"c\"/\"d\" # note\n\n\"-\" \"x#\"\n").unwrap()
+        );
+        assert_eq!(
+            "(line: 2, column: 4) Invalid Double Quote After Value",
+            parse("a\nabc\"d\"").unwrap_err()
+        );
+        assert_eq!(
+            "(line: 1, column: 5) String Not Closed",
+            parse("\"abc").unwrap_err()
+        );
+    }
+}